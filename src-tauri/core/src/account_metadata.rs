@@ -0,0 +1,174 @@
+// 账户元数据模块
+// 保存用户为账户设置的标签、备注、分类标签、颜色和置顶标记
+// 存放在账户版本目录中（antigravity-accounts/<email>/account.meta），不写入 Antigravity 备份内容
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backup_history;
+
+/// 元数据文件名（扩展名不是 .json，不会被当作备份版本）
+pub const METADATA_FILE_NAME: &str = "account.meta";
+
+/// 账户元数据
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountMetadata {
+    /// 显示标签（如“团队共享账号”）
+    pub label: Option<String>,
+    /// 备注
+    pub note: Option<String>,
+    /// 分类标签
+    pub tags: Vec<String>,
+    /// 颜色（#RRGGBB）
+    pub color: Option<String>,
+    /// 是否置顶
+    pub pinned: bool,
+}
+
+impl AccountMetadata {
+    /// 规范化并校验用户输入：去除首尾空白、空字符串视为未设置、标签去重，颜色必须为 #RRGGBB
+    pub fn normalized(self) -> Result<Self, String> {
+        fn clean(value: Option<String>) -> Option<String> {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        }
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags {
+            let tag = tag.trim().to_string();
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+
+        let color = clean(self.color);
+        if let Some(color) = &color {
+            let valid = color.len() == 7
+                && color.starts_with('#')
+                && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+            if !valid {
+                return Err(format!("颜色格式无效（应为 #RRGGBB）: {}", color));
+            }
+        }
+
+        Ok(Self {
+            label: clean(self.label),
+            note: clean(self.note),
+            tags,
+            color,
+            pinned: self.pinned,
+        })
+    }
+
+    /// 显示名称（有标签时使用标签，否则使用账户名）
+    pub fn display_name<'a>(&'a self, account: &'a str) -> &'a str {
+        self.label.as_deref().unwrap_or(account)
+    }
+}
+
+/// 附带元数据的账户（用于列表显示）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountWithMetadata {
+    /// 账户名（备份目录名，即登录邮箱）
+    pub account: String,
+    #[serde(flatten)]
+    pub metadata: AccountMetadata,
+}
+
+/// 账户筛选条件（所有条件同时满足）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AccountFilter {
+    /// 必须包含的标签（全部包含）
+    pub tags: Vec<String>,
+    /// 只保留置顶（true）或非置顶（false）的账户
+    pub pinned: Option<bool>,
+    /// 颜色（不区分大小写）
+    pub color: Option<String>,
+    /// 在账户名、标签、备注中搜索的关键字（不区分大小写）
+    pub query: Option<String>,
+}
+
+impl AccountFilter {
+    /// 判断账户是否满足筛选条件
+    pub fn matches(&self, account: &str, metadata: &AccountMetadata) -> bool {
+        if !self.tags.iter().all(|tag| metadata.tags.contains(tag)) {
+            return false;
+        }
+        if self.pinned.is_some_and(|pinned| pinned != metadata.pinned) {
+            return false;
+        }
+        if let Some(color) = &self.color {
+            if !metadata
+                .color
+                .as_deref()
+                .is_some_and(|c| c.eq_ignore_ascii_case(color))
+            {
+                return false;
+            }
+        }
+        if let Some(query) = self
+            .query
+            .as_deref()
+            .map(str::trim)
+            .filter(|q| !q.is_empty())
+        {
+            let query = query.to_lowercase();
+            let haystack = [
+                Some(account),
+                metadata.label.as_deref(),
+                metadata.note.as_deref(),
+            ];
+            let found = haystack
+                .into_iter()
+                .flatten()
+                .chain(metadata.tags.iter().map(String::as_str))
+                .any(|text| text.to_lowercase().contains(&query));
+            if !found {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// 元数据文件路径
+pub fn metadata_file(accounts_dir: &Path, account: &str) -> Result<PathBuf, String> {
+    Ok(backup_history::account_dir(accounts_dir, account)?.join(METADATA_FILE_NAME))
+}
+
+/// 读取账户元数据（文件不存在或损坏时返回默认值）
+pub fn load(accounts_dir: &Path, account: &str) -> AccountMetadata {
+    let Ok(path) = metadata_file(accounts_dir, account) else {
+        return AccountMetadata::default();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return AccountMetadata::default();
+    };
+
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        tracing::warn!("⚠️ 账户元数据已损坏，使用默认值 {}: {}", path.display(), e);
+        AccountMetadata::default()
+    })
+}
+
+/// 写入账户元数据（内容为空时删除文件）
+pub fn save(accounts_dir: &Path, account: &str, metadata: &AccountMetadata) -> Result<(), String> {
+    let path = metadata_file(accounts_dir, account)?;
+
+    if *metadata == AccountMetadata::default() {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| format!("删除账户元数据失败: {}", e))?;
+        }
+        return Ok(());
+    }
+
+    fs::create_dir_all(backup_history::account_dir(accounts_dir, account)?)
+        .map_err(|e| format!("创建版本目录失败: {}", e))?;
+    let content = serde_json::to_string_pretty(metadata)
+        .map_err(|e| format!("序列化账户元数据失败: {}", e))?;
+    fs::write(&path, content).map_err(|e| format!("写入账户元数据失败: {}", e))
+}
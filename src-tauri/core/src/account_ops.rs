@@ -0,0 +1,305 @@
+// 账户操作模块
+// 组合备份、恢复、清理和启动模块完成完整的账户操作流程
+// 不依赖 Tauri，供 Tauri 命令和命令行工具共用
+
+use serde_json::Value;
+use std::path::PathBuf;
+
+use crate::account_registry;
+use crate::account_store::AccountStore;
+use crate::antigravity_restore::RestoreError;
+use crate::audit_log::{self, AuditAction, AuditEntry};
+use crate::data_category::{self, DataCategory};
+use crate::db_snapshot::DbSnapshot;
+use crate::process::AntigravityProcess;
+use crate::state_db::StateDb;
+use crate::switch_snapshot;
+
+pub use crate::antigravity_backup::BackupTrigger;
+
+pub use crate::account_store::{
+    BackupData, ExportBundle, FailedBackup, RestoreResult, EXPORT_FORMAT_VERSION,
+};
+
+/// 获取账户备份目录
+pub fn accounts_dir() -> Result<PathBuf, String> {
+    AccountStore::open_default().map(|store| store.root().to_path_buf())
+}
+
+/// 读取当前登录的认证信息（附带数据库路径）
+pub fn current_account_info() -> Result<Value, String> {
+    let state_db = StateDb::locate()?;
+
+    if !state_db.exists() {
+        return Err(format!(
+            "Antigravity 状态数据库文件不存在: {}",
+            state_db.path().display()
+        ));
+    }
+
+    let mut auth_data = state_db
+        .auth_status()?
+        .ok_or_else(|| "查询认证信息失败: 未找到认证信息".to_string())?;
+    auth_data["db_path"] = Value::String(state_db.path().to_string_lossy().to_string());
+    Ok(auth_data)
+}
+
+/// 获取当前登录账户的邮箱（未登录时返回 None）
+pub fn current_account_email() -> Result<Option<String>, String> {
+    StateDb::locate()?.current_email()
+}
+
+/// 备份当前登录账户选定类别的数据
+///
+/// # 返回
+/// - `Ok((backup_name, is_overwrite))`: 备份账户名和该账户此前是否已有备份
+pub fn backup_current_account(categories: &[DataCategory]) -> Result<(String, bool), String> {
+    let state_db = StateDb::locate()?;
+    let email = state_db.current_email()?.ok_or("未检测到已登录用户")?;
+    tracing::info!(user_email = %email, "📧 检测到当前用户");
+    let result = crate::antigravity_backup::smart_backup_from(
+        state_db.path(),
+        &email,
+        BackupTrigger::Manual,
+        categories,
+    )?;
+    refresh_registry();
+    Ok(result)
+}
+
+/// 从指定的状态数据库完整备份账户（用于自动备份和定时备份）
+pub fn backup_account_from(
+    state_db: &StateDb,
+    email: &str,
+    trigger: BackupTrigger,
+) -> Result<(String, bool), String> {
+    let result = crate::antigravity_backup::smart_backup_from(
+        state_db.path(),
+        email,
+        trigger,
+        DataCategory::ALL,
+    )?;
+    refresh_registry();
+    Ok(result)
+}
+
+/// 账户是否已有备份
+pub fn has_backup(email: &str) -> Result<bool, String> {
+    Ok(AccountStore::open_default()?
+        .latest_revision(email)
+        .is_some())
+}
+
+/// 刷新账户注册表（失败只记录警告，不影响账户操作结果）
+fn refresh_registry() {
+    if let Err(e) = account_registry::refresh() {
+        tracing::warn!("⚠️ 更新账户注册表失败: {}", e);
+    }
+}
+
+/// 查找账户要恢复的备份文件（未指定版本时使用最新版本，兼容旧版单文件）
+pub fn backup_file_for(account_name: &str, revision: Option<&str>) -> Result<PathBuf, String> {
+    AccountStore::open_default()?.backup_file(account_name, revision)
+}
+
+/// 将账户备份中选定类别的数据恢复到 Antigravity 数据库（不关闭或启动进程，记录到审计日志）
+pub async fn restore_account(
+    account_name: &str,
+    revision: Option<&str>,
+    categories: &[DataCategory],
+) -> Result<String, RestoreError> {
+    let from_account = current_account_email().ok().flatten();
+    let result = restore_backup(account_name, revision, categories).await;
+
+    let mut details = Vec::new();
+    if let Some(revision) = revision {
+        details.push(format!("版本: {}", revision));
+    }
+    if !data_category::is_all(categories) {
+        details.push(format!("类别: {}", data_category::describe(categories)));
+    }
+
+    let mut entry = AuditEntry::new(AuditAction::Restore)
+        .from_account(from_account.as_deref())
+        .to_account(Some(account_name));
+    if !details.is_empty() {
+        entry = entry.detail(details.join("; "));
+    }
+    audit_log::record(entry.result(&result));
+    result
+}
+
+async fn restore_backup(
+    account_name: &str,
+    revision: Option<&str>,
+    categories: &[DataCategory],
+) -> Result<String, RestoreError> {
+    let backup_file = backup_file_for(account_name, revision).map_err(RestoreError::new)?;
+    crate::antigravity_restore::restore_all_antigravity_data(backup_file, categories).await
+}
+
+/// 切换到指定账户：读取并校验备份 -> 关闭进程并等待退出 -> 保存切换前快照 -> 恢复账户数据 -> 重新启动
+///
+/// 切换结果记录到审计日志
+pub async fn switch_to_account(account_name: &str, restart: bool) -> Result<String, String> {
+    let from_account = current_account_email().ok().flatten();
+    let result = switch_account(account_name, restart).await;
+    audit_log::record(
+        AuditEntry::new(AuditAction::Switch)
+            .from_account(from_account.as_deref())
+            .to_account(Some(account_name))
+            .result(&result),
+    );
+    result
+}
+
+async fn switch_account(account_name: &str, restart: bool) -> Result<String, String> {
+    tracing::info!("🔄 开始执行切换到账户: {}", account_name);
+
+    // 关闭 Antigravity 前完整读取并解密备份，账户名错误、保险库已锁定或备份损坏时不影响正在运行的进程
+    let backup_data = AccountStore::open_default()?
+        .read(account_name, None)
+        .map_err(|e| format!("读取账户 {} 的备份失败，未执行切换: {}", account_name, e))?;
+    let state_db = StateDb::locate()?;
+
+    // 1. 关闭 Antigravity 进程 (如果存在)
+    tracing::info!("🛑 步骤1: 检查并关闭 Antigravity 进程");
+    let kill_result = AntigravityProcess.shutdown().await?;
+
+    // 2-3. 保存快照并恢复数据；此后任何失败都要重新启动 Antigravity，避免停留在关闭状态
+    let restore_result = match write_switch(&state_db, account_name, &backup_data) {
+        Ok(restore_result) => restore_result,
+        Err(e) if restart => {
+            tracing::info!("🚀 切换失败，重新启动 Antigravity");
+            let start_message = AntigravityProcess.restart();
+            return Err(format!("{} -> {}", e, start_message));
+        }
+        Err(e) => return Err(e),
+    };
+    account_registry::record_switch(account_name);
+
+    if !restart {
+        return Ok(format!("{} -> {}", kill_result, restore_result));
+    }
+
+    // 4. 重新启动 Antigravity 进程
+    tracing::info!("🚀 步骤4: 重新启动 Antigravity");
+    let start_message = AntigravityProcess.restart();
+
+    Ok(format!(
+        "{} -> {} -> {}",
+        kill_result, restore_result, start_message
+    ))
+}
+
+/// 切换的写入阶段：保存切换前快照（失败则不写入数据库）后恢复账户数据
+fn write_switch(
+    state_db: &StateDb,
+    account_name: &str,
+    backup_data: &Value,
+) -> Result<String, String> {
+    tracing::info!("📸 步骤2: 保存切换前快照");
+    switch_snapshot::capture_switch_snapshot_from(state_db.path(), account_name, backup_data)
+        .map_err(|e| format!("保存切换前快照失败，已取消切换: {}", e))?;
+
+    tracing::info!("💾 步骤3: 恢复账户数据: {}", account_name);
+    let restore_result = state_db.restore_data(backup_data, DataCategory::ALL)?;
+    tracing::info!("✅ 账户数据恢复完成: {}", restore_result);
+    Ok(restore_result)
+}
+
+/// 撤销上次账户切换：关闭进程 -> 回滚到切换前快照 -> 重新启动（记录到审计日志）
+pub async fn undo_last_switch() -> Result<String, String> {
+    let from_account = current_account_email().ok().flatten();
+    let result = undo_switch().await;
+
+    let to_account = match &result {
+        Ok((_, to_account)) => to_account.clone(),
+        Err(_) => None,
+    };
+    audit_log::record(
+        AuditEntry::new(AuditAction::UndoSwitch)
+            .from_account(from_account.as_deref())
+            .to_account(to_account.as_deref())
+            .result(&result),
+    );
+    result.map(|(message, _)| message)
+}
+
+/// 执行撤销切换，返回结果信息和恢复到的账户
+async fn undo_switch() -> Result<(String, Option<String>), String> {
+    tracing::info!("🛑 步骤1: 检查并关闭 Antigravity 进程");
+    let kill_result = AntigravityProcess.shutdown().await?;
+
+    tracing::info!("⏪ 步骤2: 回滚到切换前快照");
+    let snapshot = switch_snapshot::restore_switch_snapshot(None)?;
+    let restore_result = match &snapshot.from_account {
+        Some(email) => format!("已恢复到切换前的账户: {}", email),
+        None => "已恢复到切换前的状态（未登录）".to_string(),
+    };
+    tracing::info!("✅ {}", restore_result);
+    refresh_registry();
+
+    tracing::info!("🚀 步骤3: 重新启动 Antigravity");
+    let start_message = AntigravityProcess.restart();
+
+    Ok((
+        format!("{} -> {} -> {}", kill_result, restore_result, start_message),
+        snapshot.from_account,
+    ))
+}
+
+/// 保存当前 Antigravity 数据库的完整快照（无需关闭进程）
+pub fn snapshot_databases(label: Option<&str>) -> Result<DbSnapshot, String> {
+    StateDb::locate()?.snapshot(label)
+}
+
+/// 恢复完整数据库快照：关闭进程 -> 用快照覆盖数据库 -> （可选）重新启动
+pub async fn restore_db_snapshot(id: &str, restart: bool) -> Result<String, String> {
+    tracing::info!("🛑 步骤1: 检查并关闭 Antigravity 进程");
+    let kill_result = AntigravityProcess.shutdown().await?;
+
+    tracing::info!("⏪ 步骤2: 恢复完整数据库快照: {}", id);
+    let snapshot = StateDb::locate()?.restore_snapshot(id)?;
+    let restore_result = match &snapshot.account {
+        Some(email) => format!("已恢复快照 {}（账户: {}）", snapshot.id, email),
+        None => format!("已恢复快照 {}（未登录）", snapshot.id),
+    };
+    refresh_registry();
+
+    if !restart {
+        return Ok(format!("{} -> {}", kill_result, restore_result));
+    }
+
+    tracing::info!("🚀 步骤3: 重新启动 Antigravity");
+    let start_message = AntigravityProcess.restart();
+
+    Ok(format!(
+        "{} -> {} -> {}",
+        kill_result, restore_result, start_message
+    ))
+}
+
+/// 注销当前账户：关闭进程 -> 清除选定类别的账户数据 -> （可选）重新启动
+///
+/// 默认清除 [`DataCategory::LOGOUT`]
+pub async fn logout(restart: bool, categories: &[DataCategory]) -> Result<String, String> {
+    tracing::info!("🛑 步骤1: 检查并关闭 Antigravity 进程");
+    let kill_result = AntigravityProcess.shutdown().await?;
+
+    tracing::info!("🗑️ 步骤2: 清除所有 Antigravity 数据");
+    let clear_result = StateDb::locate()?.clear(categories)?;
+    refresh_registry();
+
+    if !restart {
+        return Ok(format!("{} -> {}", kill_result, clear_result));
+    }
+
+    tracing::info!("🚀 步骤3: 重新启动 Antigravity");
+    let start_message = AntigravityProcess.restart();
+
+    Ok(format!(
+        "{} -> {} -> {}",
+        kill_result, clear_result, start_message
+    ))
+}
@@ -0,0 +1,241 @@
+// 账户注册表模块
+// 根据备份文件和当前登录的 antigravityAuthStatus 维护持久化的账户列表（account_registry.json）
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::account_store::AccountStore;
+use crate::constants::{account_registry, database};
+use crate::path_utils::AppPaths;
+use crate::state_db::StateDb;
+
+/// 注册表中的单个账户
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountRecord {
+    /// 账户 ID（与备份目录名一致，即登录邮箱）
+    pub id: String,
+    /// 登录邮箱
+    pub email: String,
+    /// 显示名称（认证信息中没有名称时使用邮箱）
+    pub name: String,
+    /// 头像（antigravity.profileUrl，Base64 编码）
+    pub avatar: Option<String>,
+    /// 首次记录时间（RFC3339）
+    pub created_at: String,
+    /// 最近一次切换到该账户的时间（RFC3339）
+    pub last_switched: Option<String>,
+    /// 最近一次备份的时间（RFC3339，没有备份时为 None）
+    pub last_backed_up: Option<String>,
+}
+
+impl AccountRecord {
+    fn new(id: &str) -> Self {
+        Self {
+            id: id.to_string(),
+            email: id.to_string(),
+            name: id.to_string(),
+            avatar: None,
+            created_at: chrono::Local::now().to_rfc3339(),
+            last_switched: None,
+            last_backed_up: None,
+        }
+    }
+
+    /// 用认证信息（antigravityAuthStatus）更新邮箱和显示名称
+    fn apply_auth_status(&mut self, auth: &Value) {
+        if let Some(email) = auth.get("email").and_then(|v| v.as_str()) {
+            self.email = email.to_string();
+        }
+        self.name = auth
+            .get("name")
+            .and_then(|v| v.as_str())
+            .filter(|name| !name.is_empty())
+            .unwrap_or(&self.email)
+            .to_string();
+    }
+
+    /// 用备份内容更新邮箱、显示名称和头像
+    fn apply_backup(&mut self, backup: &Value) {
+        if let Some(auth) = backup
+            .get(database::AUTH_STATUS)
+            .and_then(|v| v.as_str())
+            .and_then(|auth| serde_json::from_str::<Value>(auth).ok())
+        {
+            self.apply_auth_status(&auth);
+        }
+        if let Some(avatar) = backup.get(database::PROFILE_URL).and_then(|v| v.as_str()) {
+            self.avatar = Some(avatar.to_string());
+        }
+    }
+}
+
+/// 持久化的账户注册表
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AccountRegistry {
+    /// 按账户 ID 索引的账户
+    accounts: BTreeMap<String, AccountRecord>,
+    /// 当前登录的账户 ID
+    current_account_id: Option<String>,
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+impl AccountRegistry {
+    /// 读取指定路径的注册表（文件不存在时返回空注册表）
+    pub fn load(path: impl Into<PathBuf>) -> Result<Self, String> {
+        let path = path.into();
+
+        let mut registry = if path.exists() {
+            let content =
+                fs::read_to_string(&path).map_err(|e| format!("读取账户注册表失败: {}", e))?;
+            serde_json::from_str::<Self>(&content).unwrap_or_else(|e| {
+                tracing::warn!("⚠️ 账户注册表已损坏，将重新生成: {}", e);
+                Self::default()
+            })
+        } else {
+            Self::default()
+        };
+
+        registry.path = path;
+        Ok(registry)
+    }
+
+    /// 读取默认位置的注册表（配置目录下的 account_registry.json）
+    pub fn load_default() -> Result<Self, String> {
+        let path = AppPaths::config_dir()
+            .map(|dir| dir.join(account_registry::FILE_NAME))
+            .ok_or("无法获取配置目录")?;
+        Self::load(path)
+    }
+
+    /// 注册表文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 写入注册表（先写临时文件再替换，避免中途失败留下损坏的文件）
+    pub fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+        }
+
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("序列化账户注册表失败: {}", e))?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        fs::write(&tmp_path, content).map_err(|e| format!("写入账户注册表失败: {}", e))?;
+        fs::rename(&tmp_path, &self.path).map_err(|e| format!("替换账户注册表失败: {}", e))
+    }
+
+    /// 所有账户（当前账户在前，其余按最近切换、最近备份时间排序）
+    pub fn accounts(&self) -> Vec<AccountRecord> {
+        let mut accounts: Vec<AccountRecord> = self.accounts.values().cloned().collect();
+        let current = self.current_account_id.as_deref();
+        accounts.sort_by(|a, b| {
+            (Some(b.id.as_str()) == current)
+                .cmp(&(Some(a.id.as_str()) == current))
+                .then_with(|| b.last_switched.cmp(&a.last_switched))
+                .then_with(|| b.last_backed_up.cmp(&a.last_backed_up))
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        accounts
+    }
+
+    /// 按 ID 或邮箱查找账户
+    pub fn get(&self, id_or_email: &str) -> Option<&AccountRecord> {
+        self.accounts.get(id_or_email).or_else(|| {
+            self.accounts
+                .values()
+                .find(|record| record.email == id_or_email)
+        })
+    }
+
+    /// 当前登录的账户 ID
+    pub fn current_account_id(&self) -> Option<&str> {
+        self.current_account_id.as_deref()
+    }
+
+    /// 根据备份文件和当前登录状态刷新注册表
+    ///
+    /// - 每个有备份的账户都会被记录，名称和头像取自最新备份（保险库锁定时保留原有信息）
+    /// - 备份已被删除且不是当前登录的账户会被移除
+    /// - `state_db` 为 None 或数据库不可读时，保留原有的当前账户
+    pub fn sync(&mut self, store: &AccountStore, state_db: Option<&StateDb>) -> Result<(), String> {
+        let backed_up = store.accounts()?;
+
+        for id in &backed_up {
+            let revisions = store.revisions(id)?;
+            let record = self.accounts.entry(id.clone()).or_insert_with(|| {
+                let mut record = AccountRecord::new(id);
+                // 首次记录时以最早的备份时间作为创建时间
+                if let Some(oldest) = revisions.last() {
+                    record.created_at = oldest.modified_at.clone();
+                }
+                record
+            });
+
+            record.last_backed_up = revisions.first().map(|r| r.modified_at.clone());
+            match store.read(id, None) {
+                Ok(backup) => record.apply_backup(&backup),
+                Err(e) => tracing::debug!("读取账户 {} 的备份失败，保留原有信息: {}", id, e),
+            }
+        }
+
+        if let Some(state_db) = state_db.filter(|db| db.exists()) {
+            match state_db.auth_status() {
+                Ok(Some(auth)) => {
+                    let email = auth.get("email").and_then(|e| e.as_str()).map(String::from);
+                    if let Some(email) = &email {
+                        self.accounts
+                            .entry(email.clone())
+                            .or_insert_with(|| AccountRecord::new(email))
+                            .apply_auth_status(&auth);
+                    }
+                    self.current_account_id = email;
+                }
+                Ok(None) => self.current_account_id = None,
+                Err(e) => tracing::debug!("读取当前登录信息失败，保留原有的当前账户: {}", e),
+            }
+        }
+
+        let current = self.current_account_id.clone();
+        self.accounts
+            .retain(|id, _| backed_up.contains(id) || current.as_deref() == Some(id.as_str()));
+
+        Ok(())
+    }
+
+    /// 记录切换到指定账户
+    pub fn record_switch(&mut self, id: &str) {
+        let record = self
+            .accounts
+            .entry(id.to_string())
+            .or_insert_with(|| AccountRecord::new(id));
+        record.last_switched = Some(chrono::Local::now().to_rfc3339());
+        self.current_account_id = Some(id.to_string());
+    }
+}
+
+/// 读取默认注册表、按当前备份和登录状态刷新并写回
+pub fn refresh() -> Result<AccountRegistry, String> {
+    let mut registry = AccountRegistry::load_default()?;
+    registry.sync(
+        &AccountStore::open_default()?,
+        StateDb::locate().ok().as_ref(),
+    )?;
+    registry.save()?;
+    Ok(registry)
+}
+
+/// 刷新注册表并记录切换到指定账户（失败只记录警告，不影响切换结果）
+pub fn record_switch(id: &str) {
+    let result = refresh().and_then(|mut registry| {
+        registry.record_switch(id);
+        registry.save()
+    });
+    if let Err(e) = result {
+        tracing::warn!("⚠️ 更新账户注册表失败: {}", e);
+    }
+}
@@ -0,0 +1,422 @@
+// 账户备份存储模块
+// 以 AccountStore 封装 antigravity-accounts 目录：账户列表、备份版本、读写（自动经过保险库）以及导入导出
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::account_metadata::{self, AccountFilter, AccountMetadata, AccountWithMetadata};
+use crate::account_vault;
+use crate::antigravity_backup::BackupTrigger;
+use crate::audit_log::{self, AuditAction, AuditEntry};
+use crate::backup_history::{self, BackupRevision, RetentionPolicy};
+use crate::data_category::{self, DataCategory};
+use crate::path_utils::AppPaths;
+
+/// 导出/导入使用的备份数据
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BackupData {
+    pub filename: String,
+    #[serde(rename = "content")]
+    pub content: Value,
+    #[serde(rename = "timestamp")]
+    pub timestamp: u64,
+}
+
+/// 导出文件版本（与前端导出格式一致）
+pub const EXPORT_FORMAT_VERSION: &str = "1.1.0";
+
+/// 导出文件内容
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportBundle {
+    pub version: String,
+    #[serde(rename = "backupCount")]
+    pub backup_count: usize,
+    pub backups: Vec<BackupData>,
+}
+
+impl ExportBundle {
+    pub fn new(backups: Vec<BackupData>) -> Self {
+        Self {
+            version: EXPORT_FORMAT_VERSION.to_string(),
+            backup_count: backups.len(),
+            backups,
+        }
+    }
+}
+
+/// 导入结果
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RestoreResult {
+    #[serde(rename = "restoredCount")]
+    pub restored_count: u32,
+    pub failed: Vec<FailedBackup>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct FailedBackup {
+    pub filename: String,
+    pub error: String,
+}
+
+/// 账户备份存储（`antigravity-accounts/<email>/<revision>.json`）
+#[derive(Debug, Clone)]
+pub struct AccountStore {
+    root: PathBuf,
+}
+
+impl AccountStore {
+    /// 使用指定目录作为备份根目录
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// 使用默认的备份目录（`~/.antigravity-agent/antigravity-accounts`）
+    pub fn open_default() -> Result<Self, String> {
+        AppPaths::backup_dir()
+            .map(Self::new)
+            .ok_or_else(|| "无法获取备份目录".to_string())
+    }
+
+    /// 备份根目录
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 所有有备份的账户名称
+    pub fn accounts(&self) -> Result<Vec<String>, String> {
+        backup_history::list_accounts(&self.root)
+    }
+
+    /// 账户的所有备份版本（最新的在前）
+    pub fn revisions(&self, account: &str) -> Result<Vec<BackupRevision>, String> {
+        backup_history::list_revisions(&self.root, account)
+    }
+
+    /// 账户最新的备份版本
+    pub fn latest_revision(&self, account: &str) -> Option<BackupRevision> {
+        backup_history::latest_revision(&self.root, account)
+    }
+
+    /// 账户最近一次备份的时间
+    pub fn last_modified(&self, account: &str) -> Option<SystemTime> {
+        backup_history::last_modified(&self.root, account)
+    }
+
+    /// 账户要使用的备份文件（未指定版本时使用最新的包含登录凭据的版本，兼容旧版单文件；不检查是否存在）
+    pub fn backup_file(&self, account: &str, revision: Option<&str>) -> Result<PathBuf, String> {
+        match revision {
+            Some(revision) => backup_history::revision_file(&self.root, account, revision),
+            None => self.default_backup_file(account),
+        }
+    }
+
+    /// 未指定版本时使用的备份文件
+    ///
+    /// 只备份了部分类别且不含登录凭据的版本会被跳过，否则切换后仍会保留原账户的登录凭据；
+    /// 无法读取的版本不跳过，由调用方在读取时报告具体错误
+    fn default_backup_file(&self, account: &str) -> Result<PathBuf, String> {
+        let revisions = self.revisions(account)?;
+        if revisions.is_empty() {
+            return backup_history::legacy_file(&self.root, account);
+        }
+
+        revisions
+            .into_iter()
+            .find(|revision| match read_backup_file(&revision.path) {
+                Ok(backup) => data_category::of_backup(&backup).contains(&DataCategory::Auth),
+                Err(_) => true,
+            })
+            .map(|revision| revision.path)
+            .ok_or_else(|| format!("账户 {} 没有包含登录凭据 (auth) 的备份版本", account))
+    }
+
+    /// 读取账户备份内容（保险库启用时自动解密；未指定版本时同 [`Self::backup_file`]）
+    pub fn read(&self, account: &str, revision: Option<&str>) -> Result<Value, String> {
+        read_backup_file(&self.backup_file(account, revision)?)
+    }
+
+    /// 读取账户最新的备份版本（不论包含哪些类别）
+    pub fn read_latest(&self, account: &str) -> Result<Value, String> {
+        let path = match backup_history::latest_backup_file(&self.root, account) {
+            Some(path) => path,
+            None => backup_history::legacy_file(&self.root, account)?,
+        };
+        read_backup_file(&path)
+    }
+
+    /// 将内容写入为账户的新备份版本（旧版单文件先迁移进版本目录）
+    ///
+    /// # 返回
+    /// - `Ok(path)`: 新版本文件路径
+    pub fn save(&self, account: &str, content: &Value) -> Result<PathBuf, String> {
+        self.save_as(account, content, BackupTrigger::Manual)
+    }
+
+    /// 按备份触发方式写入新版本（定时备份的版本会被保留策略清理）
+    pub fn save_as(
+        &self,
+        account: &str,
+        content: &Value,
+        trigger: BackupTrigger,
+    ) -> Result<PathBuf, String> {
+        backup_history::migrate_legacy_backup(&self.root, account)?;
+        fs::create_dir_all(backup_history::account_dir(&self.root, account)?)
+            .map_err(|e| format!("创建版本目录失败: {}", e))?;
+
+        let path = backup_history::revision_file(
+            &self.root,
+            account,
+            &backup_history::new_revision_id_for(trigger),
+        )?;
+        let file_content = serde_json::to_string_pretty(content).map_err(|e| e.to_string())?;
+        account_vault::write_account_file(&path, &file_content)?;
+        Ok(path)
+    }
+
+    /// 删除账户的所有备份，账户没有任何备份时返回 `Ok(false)`
+    pub fn delete(&self, account: &str) -> Result<bool, String> {
+        let result = backup_history::delete_account(&self.root, account);
+        let entry = AuditEntry::new(AuditAction::Delete).to_account(Some(account));
+        audit_log::record(match &result {
+            Ok(false) => entry.result(&Err::<(), _>("账户没有任何备份")),
+            _ => entry.result(&result),
+        });
+        result
+    }
+
+    /// 清理账户的旧版本，只保留最新的 `keep` 个，返回被删除的版本 ID
+    pub fn prune(&self, account: &str, keep: usize) -> Result<Vec<String>, String> {
+        backup_history::prune_revisions(&self.root, account, keep)
+    }
+
+    /// 按保留策略清理账户由定时备份创建的旧版本，返回被删除的版本 ID
+    pub fn apply_retention(
+        &self,
+        account: &str,
+        policy: &RetentionPolicy,
+    ) -> Result<Vec<String>, String> {
+        backup_history::apply_retention(&self.root, account, policy)
+    }
+
+    /// 账户元数据（未设置时返回默认值）
+    pub fn metadata(&self, account: &str) -> AccountMetadata {
+        account_metadata::load(&self.root, account)
+    }
+
+    /// 保存账户元数据（先规范化校验），返回保存后的内容
+    pub fn set_metadata(
+        &self,
+        account: &str,
+        metadata: AccountMetadata,
+    ) -> Result<AccountMetadata, String> {
+        let metadata = metadata.normalized()?;
+        account_metadata::save(&self.root, account, &metadata)?;
+        Ok(metadata)
+    }
+
+    /// 满足筛选条件的账户及其元数据（置顶的在前，其余按显示名称排序）
+    pub fn accounts_with_metadata(
+        &self,
+        filter: &AccountFilter,
+    ) -> Result<Vec<AccountWithMetadata>, String> {
+        let mut accounts: Vec<AccountWithMetadata> = self
+            .accounts()?
+            .into_iter()
+            .map(|account| AccountWithMetadata {
+                metadata: self.metadata(&account),
+                account,
+            })
+            .filter(|entry| filter.matches(&entry.account, &entry.metadata))
+            .collect();
+
+        accounts.sort_by(|a, b| {
+            b.metadata.pinned.cmp(&a.metadata.pinned).then_with(|| {
+                a.metadata
+                    .display_name(&a.account)
+                    .to_lowercase()
+                    .cmp(&b.metadata.display_name(&b.account).to_lowercase())
+            })
+        });
+        Ok(accounts)
+    }
+
+    /// 收集每个账户最新的包含登录凭据的版本（用于导出，文件名保持 `<email>.json`）
+    pub fn export(&self) -> Result<Vec<BackupData>, String> {
+        let mut backups_with_content = Vec::new();
+
+        if !self.root.exists() {
+            return Ok(backups_with_content);
+        }
+
+        for account_name in self.accounts()? {
+            let path = match self.default_backup_file(&account_name) {
+                Ok(path) => path,
+                Err(e) => {
+                    tracing::warn!("⚠️ 跳过账户 {}: {}", account_name, e);
+                    continue;
+                }
+            };
+            let filename = format!("{}.json", account_name);
+
+            match account_vault::read_account_file(&path).map_err(|e| format!("{} {}", filename, e))
+            {
+                Ok(content) => match serde_json::from_str::<Value>(&content) {
+                    Ok(json_value) => {
+                        backups_with_content.push(BackupData {
+                            filename,
+                            content: json_value,
+                            timestamp: SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap_or_default()
+                                .as_secs(),
+                        });
+                    }
+                    Err(e) => {
+                        tracing::warn!("⚠️ 跳过损坏的备份文件 {}: {}", filename, e);
+                    }
+                },
+                Err(e) => {
+                    tracing::warn!("⚠️ 跳过无法读取的文件: {}", e);
+                }
+            }
+        }
+
+        Ok(backups_with_content)
+    }
+
+    /// 将导入的备份作为对应账户的新版本写入
+    pub fn import(&self, backups: Vec<BackupData>) -> Result<RestoreResult, String> {
+        let mut results = RestoreResult {
+            restored_count: 0,
+            failed: Vec::new(),
+        };
+
+        fs::create_dir_all(&self.root).map_err(|e| format!("创建目录失败: {}", e))?;
+
+        for backup in backups {
+            let account_name = Path::new(&backup.filename)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            if account_name.is_empty() {
+                results.failed.push(FailedBackup {
+                    filename: backup.filename,
+                    error: "无效的备份文件名".to_string(),
+                });
+                continue;
+            }
+
+            let result = self.save(&account_name, &backup.content);
+            audit_log::record(
+                AuditEntry::new(AuditAction::Import)
+                    .to_account(Some(&account_name))
+                    .result(&result),
+            );
+
+            match result {
+                Ok(_) => {
+                    results.restored_count += 1;
+                }
+                Err(e) => {
+                    results.failed.push(FailedBackup {
+                        filename: backup.filename,
+                        error: e,
+                    });
+                }
+            }
+        }
+
+        Ok(results)
+    }
+}
+
+/// 读取并解析备份文件（保险库启用时自动解密）
+fn read_backup_file(path: &Path) -> Result<Value, String> {
+    let content = account_vault::read_account_file(path)?;
+    serde_json::from_str(&content).map_err(|e| format!("解析备份文件失败: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use serde_json::json;
+
+    const ACCOUNT: &str = "user@example.com";
+
+    fn write_revision(store: &AccountStore, id: &str, content: &Value) -> PathBuf {
+        let path = backup_history::revision_file(store.root(), ACCOUNT, id).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, content.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn default_revision_skips_backups_without_auth() {
+        let store = AccountStore::new(test_support::temp_dir("store-partial"));
+        let full = write_revision(
+            &store,
+            "20260101-090000-000",
+            &json!({ "account_email": ACCOUNT, "data_categories": ["auth", "settings"] }),
+        );
+        write_revision(
+            &store,
+            "20260102-090000-000",
+            &json!({ "account_email": ACCOUNT, "data_categories": ["settings"] }),
+        );
+
+        assert_eq!(store.backup_file(ACCOUNT, None).unwrap(), full);
+        assert_eq!(
+            store.read(ACCOUNT, None).unwrap()["data_categories"],
+            json!(["auth", "settings"])
+        );
+        assert_eq!(
+            store.read_latest(ACCOUNT).unwrap()["data_categories"],
+            json!(["settings"])
+        );
+
+        let exported = store.export().unwrap();
+        assert_eq!(exported.len(), 1);
+        assert_eq!(
+            exported[0].content["data_categories"],
+            json!(["auth", "settings"])
+        );
+    }
+
+    #[test]
+    fn backups_without_categories_count_as_full() {
+        let store = AccountStore::new(test_support::temp_dir("store-old-format"));
+        let old = write_revision(
+            &store,
+            "20260101-090000-000",
+            &json!({ "account_email": ACCOUNT }),
+        );
+
+        assert_eq!(store.backup_file(ACCOUNT, None).unwrap(), old);
+    }
+
+    #[test]
+    fn refuses_default_revision_when_no_backup_has_auth() {
+        let store = AccountStore::new(test_support::temp_dir("store-no-auth"));
+        let partial = write_revision(
+            &store,
+            "20260102-090000-000",
+            &json!({ "account_email": ACCOUNT, "data_categories": ["chat"] }),
+        );
+
+        let error = store.read(ACCOUNT, None).unwrap_err();
+        assert!(error.contains("auth"), "{}", error);
+        assert!(store.export().unwrap().is_empty());
+        // 显式指定版本时仍可使用部分类别的备份
+        assert_eq!(
+            store
+                .backup_file(ACCOUNT, Some("20260102-090000-000"))
+                .unwrap(),
+            partial
+        );
+    }
+}
@@ -0,0 +1,369 @@
+// 账户保险库模块
+// 可选地对 antigravity-accounts 中的备份文件、切换前快照和完整数据库快照（同样包含登录凭据）进行静态加密
+// 主密码通过 Argon2id 派生密钥，每次会话解锁一次，密钥仅保存在内存中
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use walkdir::WalkDir;
+
+use crate::constants::switch_snapshot;
+use crate::crypto::{self, CryptoError, KdfParams, SecretKey};
+use crate::db_snapshot;
+use crate::path_utils::AppPaths;
+
+/// 保险库配置文件名（位于配置目录，不在账户目录中以免被识别为账户）
+const VAULT_FILE_NAME: &str = "vault.json";
+
+/// 用于校验主密码的固定明文
+const VERIFIER_PLAINTEXT: &[u8] = b"antigravity-agent-vault";
+
+/// 当前会话的保险库密钥
+static SESSION_KEY: Mutex<Option<SecretKey>> = Mutex::new(None);
+
+/// 保险库配置（持久化）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct VaultConfig {
+    version: u8,
+    m_cost: u32,
+    t_cost: u32,
+    p_cost: u32,
+    /// Base64 编码的盐
+    salt: String,
+    /// Base64 编码的校验数据（用派生密钥加密的固定明文）
+    verifier: String,
+    created_at: String,
+}
+
+impl VaultConfig {
+    fn kdf_params(&self) -> KdfParams {
+        KdfParams {
+            m_cost: self.m_cost,
+            t_cost: self.t_cost,
+            p_cost: self.p_cost,
+        }
+    }
+}
+
+/// 保险库状态
+#[derive(Debug, Clone, Serialize)]
+pub struct VaultStatus {
+    /// 是否已启用保险库模式
+    pub enabled: bool,
+    /// 当前会话是否已解锁
+    pub unlocked: bool,
+}
+
+fn vault_file() -> Result<PathBuf, String> {
+    AppPaths::config_dir()
+        .map(|dir| dir.join(VAULT_FILE_NAME))
+        .ok_or_else(|| "无法获取配置目录".to_string())
+}
+
+fn load_config() -> Result<Option<VaultConfig>, String> {
+    let file = vault_file()?;
+    if !file.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&file).map_err(|e| format!("读取保险库配置失败: {}", e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("解析保险库配置失败: {}", e))
+}
+
+fn session_key() -> Option<SecretKey> {
+    *SESSION_KEY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_session_key(key: Option<SecretKey>) {
+    *SESSION_KEY.lock().unwrap_or_else(|e| e.into_inner()) = key;
+}
+
+/// 使用主密码派生密钥并校验
+fn derive_and_verify(config: &VaultConfig, password: &str) -> Result<SecretKey, String> {
+    let salt = STANDARD
+        .decode(&config.salt)
+        .map_err(|e| format!("保险库配置损坏: {}", e))?;
+    let verifier = STANDARD
+        .decode(&config.verifier)
+        .map_err(|e| format!("保险库配置损坏: {}", e))?;
+
+    let key = crypto::derive_key(password, &salt, config.kdf_params())?;
+    match crypto::decrypt_with_key(&verifier, &key) {
+        Ok(plain) if plain == VERIFIER_PLAINTEXT => Ok(key),
+        Ok(_) | Err(CryptoError::WrongPasswordOrTampered) => Err("主密码错误".to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// 获取保险库状态
+pub fn status() -> VaultStatus {
+    VaultStatus {
+        enabled: is_enabled(),
+        unlocked: session_key().is_some(),
+    }
+}
+
+/// 是否已启用保险库模式
+pub fn is_enabled() -> bool {
+    vault_file().map(|file| file.exists()).unwrap_or(false)
+}
+
+/// 使用主密码解锁保险库（本次会话内有效）
+pub fn unlock(password: &str) -> Result<(), String> {
+    let config = load_config()?.ok_or("保险库未启用")?;
+    let key = derive_and_verify(&config, password)?;
+    set_session_key(Some(key));
+    tracing::info!("🔓 账户保险库已解锁");
+
+    let migrated = encrypt_plaintext_files(&key)?;
+    if migrated > 0 {
+        tracing::info!("  🔐 加密了 {} 个遗留的明文备份", migrated);
+    }
+    Ok(())
+}
+
+/// 锁定保险库，清除内存中的密钥
+pub fn lock() {
+    set_session_key(None);
+    tracing::info!("🔒 账户保险库已锁定");
+}
+
+/// 原子写入文件（先写临时文件再重命名，避免迁移中断导致文件损坏）
+fn write_atomic(path: &Path, data: &[u8]) -> Result<(), String> {
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let tmp = PathBuf::from(tmp);
+    fs::write(&tmp, data).map_err(|e| format!("写入临时文件失败: {}", e))?;
+    fs::rename(&tmp, path).map_err(|e| format!("替换文件失败: {}", e))
+}
+
+fn is_json_file(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == "json")
+}
+
+/// 收集目录下所有满足条件的文件（目录不存在时跳过）
+fn collect_files(
+    dir: &Path,
+    is_vault_file: fn(&Path) -> bool,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), String> {
+    if !dir.exists() {
+        return Ok(());
+    }
+    for entry in WalkDir::new(dir) {
+        let entry = entry.map_err(|e| format!("遍历备份目录失败: {}", e))?;
+        let path = entry.path();
+        if path.is_file() && is_vault_file(path) {
+            files.push(path.to_path_buf());
+        }
+    }
+    Ok(())
+}
+
+/// 保险库管理的所有文件：账户目录下的备份文件、切换前快照和完整数据库快照中的数据库文件
+fn vault_files() -> Result<Vec<PathBuf>, String> {
+    let config_dir = AppPaths::config_dir().ok_or("无法获取配置目录")?;
+
+    let mut files = Vec::new();
+    collect_files(
+        &AppPaths::backup_dir().ok_or("无法获取备份目录")?,
+        is_json_file,
+        &mut files,
+    )?;
+    collect_files(
+        &config_dir.join(switch_snapshot::DIR_NAME),
+        is_json_file,
+        &mut files,
+    )?;
+    collect_files(
+        &db_snapshot::snapshot_root()?,
+        db_snapshot::is_database_file,
+        &mut files,
+    )?;
+    Ok(files)
+}
+
+/// 将账户目录中的明文备份原地加密
+fn encrypt_plaintext_files(key: &SecretKey) -> Result<usize, String> {
+    let mut migrated = 0;
+    for file in vault_files()? {
+        let data = fs::read(&file).map_err(|e| format!("读取文件失败: {}", e))?;
+        if crypto::is_key_encrypted(&data) {
+            continue;
+        }
+        let encrypted = crypto::encrypt_with_key(&data, key)?;
+        write_atomic(&file, &encrypted)?;
+        migrated += 1;
+    }
+    Ok(migrated)
+}
+
+/// 启用保险库模式，并将现有明文备份原地加密
+///
+/// # 返回
+/// - `Ok(count)`: 被加密的文件数量
+pub fn enable(password: &str) -> Result<usize, String> {
+    if is_enabled() {
+        return Err("保险库已启用".to_string());
+    }
+
+    let params = KdfParams::default();
+    let salt = crypto::generate_salt();
+    let key = crypto::derive_key(password, &salt, params)?;
+    let verifier = crypto::encrypt_with_key(VERIFIER_PLAINTEXT, &key)?;
+
+    let config = VaultConfig {
+        version: crypto::FORMAT_VERSION,
+        m_cost: params.m_cost,
+        t_cost: params.t_cost,
+        p_cost: params.p_cost,
+        salt: STANDARD.encode(salt),
+        verifier: STANDARD.encode(verifier),
+        created_at: chrono::Local::now().to_rfc3339(),
+    };
+
+    let file = vault_file()?;
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("序列化保险库配置失败: {}", e))?;
+    fs::write(&file, content).map_err(|e| format!("写入保险库配置失败: {}", e))?;
+
+    set_session_key(Some(key));
+
+    // 配置写入后再迁移：中途失败时已加密的文件仍可用主密码解开，
+    // 剩余的明文文件在下次解锁时继续迁移
+    let migrated = encrypt_plaintext_files(&key)?;
+    tracing::info!("🔐 账户保险库已启用，加密了 {} 个文件", migrated);
+    Ok(migrated)
+}
+
+/// 关闭保险库模式，将所有备份解密为明文
+///
+/// # 返回
+/// - `Ok(count)`: 被解密的文件数量
+pub fn disable(password: &str) -> Result<usize, String> {
+    let config = load_config()?.ok_or("保险库未启用")?;
+    let key = derive_and_verify(&config, password)?;
+
+    let mut migrated = 0;
+    for file in vault_files()? {
+        let data = fs::read(&file).map_err(|e| format!("读取文件失败: {}", e))?;
+        if !crypto::is_key_encrypted(&data) {
+            continue;
+        }
+        let plain = crypto::decrypt_with_key(&data, &key)
+            .map_err(|e| format!("解密 {} 失败: {}", file.display(), e))?;
+        write_atomic(&file, &plain)?;
+        migrated += 1;
+    }
+
+    fs::remove_file(vault_file()?).map_err(|e| format!("删除保险库配置失败: {}", e))?;
+    set_session_key(None);
+    tracing::info!("🔓 账户保险库已关闭，解密了 {} 个文件", migrated);
+    Ok(migrated)
+}
+
+/// 读取文件，保险库文件会自动解密
+fn read_file(path: &Path) -> Result<Vec<u8>, String> {
+    let data = fs::read(path).map_err(|e| format!("读取文件失败: {}", e))?;
+
+    if crypto::is_key_encrypted(&data) {
+        let key = session_key().ok_or("账户保险库已锁定，请先解锁")?;
+        Ok(crypto::decrypt_with_key(&data, &key)?)
+    } else {
+        Ok(data)
+    }
+}
+
+/// 读取账户备份文件，保险库文件会自动解密
+pub fn read_account_file(path: &Path) -> Result<String, String> {
+    String::from_utf8(read_file(path)?).map_err(|e| format!("UTF-8解码失败: {}", e))
+}
+
+/// 文件是否已被保险库加密（只读取文件头）
+pub fn is_sealed(path: &Path) -> Result<bool, String> {
+    let mut header = Vec::new();
+    fs::File::open(path)
+        .and_then(|file| file.take(16).read_to_end(&mut header))
+        .map_err(|e| format!("读取文件失败: {}", e))?;
+    Ok(crypto::is_key_encrypted(&header))
+}
+
+/// 保险库启用时将已写入的文件原地加密（用于完整数据库快照等不经过 [`write_account_file`] 写入的文件）
+pub fn seal_file(path: &Path) -> Result<(), String> {
+    if !is_enabled() {
+        return Ok(());
+    }
+    let key = session_key().ok_or("账户保险库已锁定，请先解锁")?;
+    let data = fs::read(path).map_err(|e| format!("读取文件失败: {}", e))?;
+    if crypto::is_key_encrypted(&data) {
+        return Ok(());
+    }
+    write_atomic(path, &crypto::encrypt_with_key(&data, &key)?)
+}
+
+/// 将保险库文件解密后写入 `dest`
+pub fn unseal_file_to(path: &Path, dest: &Path) -> Result<(), String> {
+    fs::write(dest, read_file(path)?).map_err(|e| format!("写入文件失败: {}", e))
+}
+
+/// 写入账户备份文件，保险库启用时自动加密
+pub fn write_account_file(path: &Path, content: &str) -> Result<(), String> {
+    if is_enabled() {
+        let key = session_key().ok_or("账户保险库已锁定，请先解锁")?;
+        let encrypted = crypto::encrypt_with_key(content.as_bytes(), &key)?;
+        fs::write(path, encrypted).map_err(|e| format!("写入文件失败: {}", e))
+    } else {
+        fs::write(path, content).map_err(|e| format!("写入文件失败: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn enable_lock_unlock_and_disable() {
+        let _vault = test_support::lock_vault();
+        test_support::agent_home();
+        // 只统计本测试写入的文件
+        let config_dir = AppPaths::config_dir().unwrap();
+        let _ = fs::remove_dir_all(config_dir.join(switch_snapshot::DIR_NAME));
+        let _ = fs::remove_dir_all(db_snapshot::snapshot_root().unwrap());
+        let account_dir = AppPaths::backup_dir().unwrap().join("vault@example.com");
+        fs::create_dir_all(&account_dir).unwrap();
+        let file = account_dir.join("20250101-120000-000.json");
+        fs::write(&file, "{\"token\":\"abc\"}").unwrap();
+
+        assert_eq!(enable("master").unwrap(), 1);
+        assert!(crypto::is_key_encrypted(&fs::read(&file).unwrap()));
+        assert_eq!(read_account_file(&file).unwrap(), "{\"token\":\"abc\"}");
+
+        lock();
+        assert!(!status().unlocked);
+        assert!(read_account_file(&file).is_err());
+        assert!(write_account_file(&file, "{}").is_err());
+
+        assert_eq!(unlock("wrong").unwrap_err(), "主密码错误");
+        assert!(!status().unlocked);
+
+        unlock("master").unwrap();
+        assert!(status().unlocked);
+        assert_eq!(read_account_file(&file).unwrap(), "{\"token\":\"abc\"}");
+
+        assert_eq!(disable("wrong").unwrap_err(), "主密码错误");
+        assert!(is_enabled());
+
+        assert_eq!(disable("master").unwrap(), 1);
+        assert!(!is_enabled());
+        assert_eq!(fs::read_to_string(&file).unwrap(), "{\"token\":\"abc\"}");
+    }
+}
@@ -0,0 +1,295 @@
+// Antigravity 用户数据备份模块
+// 负责将 Antigravity 应用数据备份到 JSON 文件
+
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::{Map, Value};
+use std::path::Path;
+
+use crate::account_store::AccountStore;
+use crate::audit_log::{self, AuditAction, AuditEntry};
+use crate::constants::database;
+use crate::data_category::{self, DataCategory};
+use crate::extra_keys;
+
+/// 备份文件中记录备份时间的字段
+const BACKUP_TIME_FIELD: &str = "backup_time";
+
+/// 备份的触发方式（记录在审计日志中）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackupTrigger {
+    /// 用户手动备份
+    Manual,
+    /// 数据库监控检测到登录或令牌刷新后自动备份
+    Auto,
+    /// 定时备份
+    Scheduled,
+}
+
+impl BackupTrigger {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            BackupTrigger::Manual => "手动备份",
+            BackupTrigger::Auto => "自动备份",
+            BackupTrigger::Scheduled => "定时备份",
+        }
+    }
+}
+
+/// 从指定 state.vscdb 智能备份账户选定类别的数据（终极版 - 保存完整 Marker，记录到审计日志）
+///
+/// 备份策略：
+/// 1. 保存所有关键字段的原始字符串值
+/// 2. 保存完整的 __$__targetStorageMarker 对象（作为恢复时的参考）
+/// 3. 保存 __$__isNewStorageMarker 状态标记
+/// 4. 每次备份写入 `<email>/<revision>.json`，历史版本不会被覆盖
+/// 5. 自动备份和定时备份的数据与最新版本相同时不新增版本
+///
+/// # 返回
+/// - `Ok((backup_name, is_overwrite))`: 备份账户名和该账户此前是否已有备份
+/// - `Err(message)`: 错误信息
+pub fn smart_backup_from(
+    app_data: &Path,
+    email: &str,
+    trigger: BackupTrigger,
+    categories: &[DataCategory],
+) -> Result<(String, bool), String> {
+    let result = backup_from(app_data, email, trigger, categories);
+
+    let mut detail = trigger.describe().to_string();
+    if !data_category::is_all(categories) {
+        detail.push_str(&format!("（仅 {}）", data_category::describe(categories)));
+    }
+    audit_log::record(
+        AuditEntry::new(AuditAction::Backup)
+            .to_account(Some(email))
+            .detail(detail)
+            .result(&result),
+    );
+    result
+}
+
+fn backup_from(
+    app_data: &Path,
+    email: &str,
+    trigger: BackupTrigger,
+    categories: &[DataCategory],
+) -> Result<(String, bool), String> {
+    if categories.is_empty() {
+        return Err("至少需要选择一个数据类别".to_string());
+    }
+
+    tracing::info!("🔧 执行智能备份（完整 Marker 模式），邮箱: {}", email);
+
+    let store = AccountStore::open_default()?;
+
+    // 每个邮箱一个版本目录，每次备份新增一个版本
+    let backup_name = email.to_string();
+    let is_overwrite = store.latest_revision(&backup_name).is_some();
+
+    if !app_data.exists() {
+        return Err(format!("数据库文件不存在: {}", app_data.display()));
+    }
+
+    let data_map = extract_backup_data(app_data, email, categories)?;
+
+    // 后台备份（自动、定时）内容与最新版本相同时不新增版本，避免堆积相同的版本
+    if trigger != BackupTrigger::Manual && same_as_latest(&store, &backup_name, &data_map) {
+        tracing::info!("ℹ️ 数据与最新备份版本相同，跳过{}", trigger.describe());
+        return Ok((backup_name, is_overwrite));
+    }
+
+    // 写入新的备份版本（旧版单文件先迁移进版本目录）
+    let backup_file = store.save_as(&backup_name, &Value::Object(data_map), trigger)?;
+
+    let action = if is_overwrite {
+        "新增版本"
+    } else {
+        "创建"
+    };
+    tracing::info!("✅ 备份成功 ({}): {}", action, backup_file.display());
+    Ok((backup_name, is_overwrite))
+}
+
+/// 备份数据是否与账户最新版本相同（忽略备份时间；读取失败时视为不同）
+fn same_as_latest(store: &AccountStore, account: &str, data_map: &Map<String, Value>) -> bool {
+    let Ok(Value::Object(mut latest)) = store.read_latest(account) else {
+        return false;
+    };
+    let mut current = data_map.clone();
+    latest.remove(BACKUP_TIME_FIELD);
+    current.remove(BACKUP_TIME_FIELD);
+    latest == current
+}
+
+/// 从指定 state.vscdb 中提取账户选定类别的备份数据
+///
+/// 包含关键字段的原始字符串值、通知字段、解析后的完整 Marker 以及备份元信息
+pub fn extract_backup_data(
+    app_data: &Path,
+    email: &str,
+    categories: &[DataCategory],
+) -> Result<Map<String, Value>, String> {
+    let conn = Connection::open(app_data).map_err(|e| e.to_string())?;
+
+    // 只备份选定类别的字段
+    let keys_to_backup = data_category::keys_of(categories);
+
+    let mut data_map = Map::new();
+
+    // 1. 提取数据（保持原始字符串格式）
+    for key in keys_to_backup {
+        let val: Option<String> = conn
+            .query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+            .unwrap_or(None);
+
+        if let Some(v) = val {
+            tracing::info!("  📦 备份字段: {}", key);
+            data_map.insert(key.to_string(), Value::String(v));
+        } else {
+            tracing::info!("  ℹ️ 字段不存在: {} (跳过)", key);
+        }
+    }
+
+    // 1.5. 提取所有通知相关字段（避免历史通知重复弹窗）
+    let notification_keys: Vec<String> = if data_category::includes_notifications(categories) {
+        tracing::info!("  🔔 检查通知相关字段...");
+        conn.prepare("SELECT key FROM ItemTable WHERE key LIKE ?")
+            .map_err(|e| e.to_string())?
+            .query_map([format!("{}%", database::NOTIFICATION_PREFIX)], |row| {
+                row.get(0)
+            })
+            .map_err(|e| e.to_string())?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(|e| e.to_string())?
+    } else {
+        Vec::new()
+    };
+
+    if !notification_keys.is_empty() {
+        tracing::info!(
+            "  📬 发现 {} 个通知字段，开始备份...",
+            notification_keys.len()
+        );
+        for notification_key in &notification_keys {
+            let val: Option<String> = conn
+                .query_row(
+                    "SELECT value FROM ItemTable WHERE key = ?",
+                    [notification_key],
+                    |row| row.get(0),
+                )
+                .optional()
+                .unwrap_or(None);
+
+            if let Some(v) = val {
+                tracing::info!("  📦 备份通知: {}", notification_key);
+                data_map.insert(notification_key.clone(), Value::String(v));
+            }
+        }
+
+        // 保存通知字段列表到元数据中，方便恢复时使用
+        data_map.insert(
+            "notification_keys".to_string(),
+            Value::Array(notification_keys.into_iter().map(Value::String).collect()),
+        );
+    } else {
+        tracing::info!("  ℹ️ 未发现通知字段");
+    }
+
+    // 1.6. 提取用户自定义的字段，并把匹配结果写入备份（恢复时不依赖当前设置）
+    let extra_settings = extra_keys::load().unwrap_or_else(|e| {
+        tracing::warn!("  ⚠️ 读取自定义字段设置失败，跳过: {}", e);
+        Vec::new()
+    });
+    let extra = extra_keys::resolve(&conn, &extra_settings, categories)?;
+    if !extra.is_empty() {
+        for extra_key in &extra {
+            let val: Option<String> = conn
+                .query_row(
+                    "SELECT value FROM ItemTable WHERE key = ?",
+                    [&extra_key.key],
+                    |row| row.get(0),
+                )
+                .optional()
+                .unwrap_or(None);
+
+            if let Some(v) = val {
+                tracing::info!("  📦 备份自定义字段: {}", extra_key.key);
+                data_map.insert(extra_key.key.clone(), Value::String(v));
+            }
+        }
+
+        data_map.insert(
+            extra_keys::BACKUP_FIELD.to_string(),
+            serde_json::to_value(&extra).map_err(|e| e.to_string())?,
+        );
+    }
+
+    // 2. 提取并解析 Marker（作为恢复时的参考书）
+    let marker_json: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT value FROM ItemTable WHERE key = '{}'",
+                database::TARGET_STORAGE_MARKER
+            ),
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .unwrap_or(None);
+
+    if let Some(m) = marker_json {
+        // 将 Marker 解析为对象存入备份
+        if let Ok(parsed_marker) = serde_json::from_str::<Value>(&m) {
+            tracing::info!("  📋 备份完整 Marker（作为恢复参考）");
+            data_map.insert(database::TARGET_STORAGE_MARKER.to_string(), parsed_marker);
+        }
+    }
+
+    // 3. 添加元信息
+    data_map.insert(
+        "account_email".to_string(),
+        Value::String(email.to_string()),
+    );
+    data_map.insert(
+        BACKUP_TIME_FIELD.to_string(),
+        Value::String(chrono::Local::now().to_rfc3339()),
+    );
+    data_map.insert(
+        data_category::BACKUP_FIELD.to_string(),
+        serde_json::to_value(categories).map_err(|e| e.to_string())?,
+    );
+
+    Ok(data_map)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn partial_backup_records_only_selected_categories() {
+        let app_data = test_support::temp_dir("backup-partial").join("state.vscdb");
+        drop(test_support::create_state_db(
+            &app_data,
+            &[
+                (database::AUTH_STATUS, "auth"),
+                (database::PROFILE_URL, "profile"),
+            ],
+        ));
+
+        let data =
+            extract_backup_data(&app_data, "user@example.com", &[DataCategory::Profile]).unwrap();
+        assert_eq!(data[database::PROFILE_URL], "profile");
+        assert!(!data.contains_key(database::AUTH_STATUS));
+
+        let backup = Value::Object(data);
+        assert_eq!(
+            data_category::of_backup(&backup),
+            vec![DataCategory::Profile]
+        );
+    }
+}
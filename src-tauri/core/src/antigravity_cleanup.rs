@@ -0,0 +1,193 @@
+// Antigravity 用户数据清除模块
+// 负责清除 Antigravity 应用的用户认证和设置信息（可只清除选定的数据类别）
+
+use rusqlite::{Connection, OptionalExtension};
+use serde_json::Value;
+use std::path::Path;
+
+// 导入 platform_utils 模块
+use crate::audit_log::{self, AuditAction, AuditEntry};
+use crate::constants::database;
+use crate::data_category::{self, DataCategory};
+use crate::state_db::StateDb;
+use crate::{db_preflight, platform_utils};
+
+/// 选定类别中需要物理删除的字段（设备指纹等保留字段永远不删除）
+fn keys_to_delete(categories: &[DataCategory]) -> Vec<&'static str> {
+    data_category::keys_of(categories)
+        .into_iter()
+        .filter(|key| !database::PRESERVED_KEYS.contains(key))
+        .collect()
+}
+
+/// 智能更新 Marker：彻底移除指定的 Key（而非设为0）
+pub(crate) fn remove_keys_from_marker(
+    conn: &Connection,
+    keys_to_remove: &[&str],
+) -> Result<(), String> {
+    tracing::info!("  🔧 正在修正校验标记 (Marker)...");
+
+    let current_marker_json: Option<String> = conn
+        .query_row(
+            &format!(
+                "SELECT value FROM ItemTable WHERE key = '{}'",
+                database::TARGET_STORAGE_MARKER
+            ),
+            [],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("读取 Marker 失败: {}", e))?;
+
+    let mut marker_obj: serde_json::Map<String, Value> = match current_marker_json {
+        Some(s) => serde_json::from_str(&s).unwrap_or_default(),
+        None => return Ok(()), // 没有 Marker 就不需要处理
+    };
+
+    let mut changed = false;
+    for key in keys_to_remove {
+        // 关键修正：这里必须是 remove，完全从 JSON 中移除该字段，而不是设为 0
+        if marker_obj.remove(*key).is_some() {
+            changed = true;
+        }
+    }
+
+    if changed {
+        let new_marker_str =
+            serde_json::to_string(&marker_obj).map_err(|e| format!("序列化失败: {}", e))?;
+
+        conn.execute(
+            &format!(
+                "INSERT OR REPLACE INTO ItemTable (key, value) VALUES ('{}', ?)",
+                database::TARGET_STORAGE_MARKER
+            ),
+            [new_marker_str],
+        )
+        .map_err(|e| format!("写入 Marker 失败: {}", e))?;
+
+        tracing::info!("  ✅ 校验标记已清理（完全移除登录相关字段）");
+    } else {
+        tracing::info!("  ℹ️ 校验标记无需变更");
+    }
+    Ok(())
+}
+
+fn clear_database(
+    conn: &Connection,
+    db_name: &str,
+    categories: &[DataCategory],
+) -> Result<usize, String> {
+    tracing::info!("🔄 正在清理数据库: {}", db_name);
+
+    let delete_keys = keys_to_delete(categories);
+    let mut count = 0;
+    // 1. 物理删除数据行
+    for key in &delete_keys {
+        let rows = conn
+            .execute("DELETE FROM ItemTable WHERE key = ?", [key])
+            .unwrap_or(0);
+        if rows > 0 {
+            tracing::info!("  ✅ 已删除: {}", key);
+            count += 1;
+        }
+    }
+
+    // 1.5. 删除通知字段（通知不参与 Marker 同步）
+    if data_category::includes_notifications(categories) {
+        let rows = conn
+            .execute(
+                "DELETE FROM ItemTable WHERE key LIKE ?",
+                [format!("{}%", database::NOTIFICATION_PREFIX)],
+            )
+            .unwrap_or(0);
+        if rows > 0 {
+            tracing::info!("  ✅ 已删除 {} 个通知字段", rows);
+            count += rows;
+        }
+    }
+
+    // 2. 同步修改 Marker 清单（只移除本次清除的字段）
+    if let Err(e) = remove_keys_from_marker(conn, &delete_keys) {
+        tracing::warn!("  ⚠️ Marker 更新警告: {}", e);
+    }
+
+    Ok(count)
+}
+
+/// 清除当前 Antigravity 安装中选定类别的用户数据（保留设备指纹）
+pub async fn clear_all_antigravity_data(categories: &[DataCategory]) -> Result<String, String> {
+    let app_data = platform_utils::resolve_antigravity_db_path()?;
+    clear_state_databases(&app_data, categories)
+}
+
+/// 清除指定 state.vscdb（及同目录下的 state.vscdb.backup）中选定类别的用户数据（记录到审计日志）
+///
+/// 注销时使用 [`DataCategory::LOGOUT`]
+pub fn clear_state_databases(
+    app_data: &Path,
+    categories: &[DataCategory],
+) -> Result<String, String> {
+    let email = StateDb::new(app_data).current_email().ok().flatten();
+    let result = clear_databases(app_data, categories);
+
+    let mut entry = AuditEntry::new(AuditAction::Logout).from_account(email.as_deref());
+    if categories != DataCategory::LOGOUT {
+        entry = entry.detail(format!("清除类别: {}", data_category::describe(categories)));
+    }
+    audit_log::record(entry.result(&result));
+    result
+}
+
+fn clear_databases(app_data: &Path, categories: &[DataCategory]) -> Result<String, String> {
+    if categories.is_empty() {
+        return Err("至少需要选择一个数据类别".to_string());
+    }
+
+    tracing::info!("🗑️ 开始清除 Antigravity 用户认证数据（保留设备指纹）");
+    tracing::info!("🗂️ 清除类别: {}", data_category::describe(categories));
+
+    if !app_data.exists() {
+        return Err(format!(
+            "Antigravity 状态数据库不存在: {}",
+            app_data.display()
+        ));
+    }
+
+    // 写入前检查两个数据库，任意一个被占用都不做任何修改
+    let main_conn = db_preflight::open_for_write(app_data)?;
+    let backup_db = app_data.with_extension("vscdb.backup");
+    let backup_conn = if backup_db.exists() {
+        Some(db_preflight::open_for_write(&backup_db)?)
+    } else {
+        None
+    };
+
+    let mut msg = String::new();
+
+    // 清理主库
+    tracing::info!("📊 步骤1: 清除 state.vscdb 数据库");
+    match clear_database(&main_conn, "state.vscdb", categories) {
+        Ok(c) => {
+            tracing::info!("  ✅ 主数据库已清除 {} 项", c);
+            msg.push_str(&format!("主库清理 {} 项", c));
+        }
+        Err(e) => return Err(e),
+    }
+
+    // 清理备份库
+    tracing::info!("💾 步骤2: 清除 state.vscdb.backup");
+    if let Some(backup_conn) = &backup_conn {
+        if let Ok(c) = clear_database(backup_conn, "state.vscdb.backup", categories) {
+            tracing::info!("  ✅ 备份数据库已清除 {} 项", c);
+            msg.push_str(&format!("; 备份库清理 {} 项", c));
+        }
+    } else {
+        tracing::info!("  ℹ️ 备份数据库不存在，跳过");
+    }
+
+    // 添加设备指纹保护说明
+    tracing::info!("🔒 设备指纹保护: google.antigravity 已保留，避免风控触发");
+    msg.push_str(" (设备指纹已保留)");
+
+    Ok(format!("✅ 登出成功: {}", msg))
+}
@@ -5,8 +5,10 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
+use crate::path_utils::AppPaths;
+
 /// Antigravity 路径配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AntigravityPathConfig {
     /// 用户自定义的 Antigravity 数据目录路径（state.vscdb 所在目录）
     pub custom_data_path: Option<String>,
@@ -14,20 +16,9 @@ pub struct AntigravityPathConfig {
     pub custom_executable_path: Option<String>,
 }
 
-impl Default for AntigravityPathConfig {
-    fn default() -> Self {
-        Self { 
-            custom_data_path: None,
-            custom_executable_path: None,
-        }
-    }
-}
-
 /// 获取配置文件路径
 fn get_config_file_path() -> Result<PathBuf, String> {
-    let config_dir = dirs::config_dir()
-        .ok_or("无法获取配置目录")?
-        .join(".antigravity-agent");
+    let config_dir = AppPaths::config_dir().ok_or("无法获取配置目录")?;
 
     // 确保配置目录存在
     fs::create_dir_all(&config_dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
@@ -39,11 +30,11 @@ fn get_config_file_path() -> Result<PathBuf, String> {
 pub fn save_custom_data_path(path: String) -> Result<(), String> {
     let config_file = get_config_file_path()?;
     let mut config = read_config().unwrap_or_default();
-    
+
     config.custom_data_path = Some(path);
     write_config(&config_file, &config)?;
 
-  tracing::info!("✅ 已保存自定义 Antigravity 数据路径");
+    tracing::info!("✅ 已保存自定义 Antigravity 数据路径");
     Ok(())
 }
 
@@ -51,18 +42,21 @@ pub fn save_custom_data_path(path: String) -> Result<(), String> {
 pub fn save_custom_executable_path(path: String) -> Result<(), String> {
     let config_file = get_config_file_path()?;
     let mut config = read_config().unwrap_or_default();
-    
+
     config.custom_executable_path = Some(path);
     write_config(&config_file, &config)?;
 
-  tracing::info!("✅ 已保存自定义 Antigravity 可执行文件路径");
+    tracing::info!("✅ 已保存自定义 Antigravity 可执行文件路径");
     Ok(())
 }
 
 /// 写入配置到文件
-fn write_config(config_file: &std::path::Path, config: &AntigravityPathConfig) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(config)
-        .map_err(|e| format!("序列化配置失败: {}", e))?;
+fn write_config(
+    config_file: &std::path::Path,
+    config: &AntigravityPathConfig,
+) -> Result<(), String> {
+    let json =
+        serde_json::to_string_pretty(config).map_err(|e| format!("序列化配置失败: {}", e))?;
     fs::write(config_file, json).map_err(|e| format!("写入配置文件失败: {}", e))?;
     Ok(())
 }
@@ -75,11 +69,11 @@ fn read_config() -> Result<AntigravityPathConfig, String> {
         return Ok(AntigravityPathConfig::default());
     }
 
-    let content = fs::read_to_string(&config_file)
-        .map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let content =
+        fs::read_to_string(&config_file).map_err(|e| format!("读取配置文件失败: {}", e))?;
 
-    let config: AntigravityPathConfig = serde_json::from_str(&content)
-        .map_err(|e| format!("解析配置文件失败: {}", e))?;
+    let config: AntigravityPathConfig =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置文件失败: {}", e))?;
 
     Ok(config)
 }
@@ -103,7 +97,7 @@ pub fn clear_custom_path() -> Result<(), String> {
 
     if config_file.exists() {
         fs::remove_file(&config_file).map_err(|e| format!("删除配置文件失败: {}", e))?;
-      tracing::info!("✅ 已清除自定义 Antigravity 路径");
+        tracing::info!("✅ 已清除自定义 Antigravity 路径");
     }
 
     Ok(())
@@ -113,7 +107,7 @@ pub fn clear_custom_path() -> Result<(), String> {
 pub fn validate_data_path(path: &str) -> bool {
     let path_buf = PathBuf::from(path);
     let db_path = path_buf.join("state.vscdb");
-    
+
     db_path.exists() && db_path.is_file()
 }
 
@@ -0,0 +1,967 @@
+// Antigravity 用户数据恢复模块
+// 负责将备份数据恢复到 Antigravity 应用数据库
+
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Transaction};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// 导入相关模块
+use crate::account_vault;
+use crate::constants::database;
+use crate::data_category::{self, DataCategory};
+use crate::db_preflight;
+use crate::extra_keys;
+use crate::platform_utils;
+use crate::state_diff::{self, ChangeKind};
+
+/// 附加备份数据库时使用的 schema 名称
+pub const BACKUP_DB_SCHEMA: &str = "backup_db";
+
+/// 恢复失败的结构化错误
+///
+/// 数据库写入在事务中执行，事务内出错时所有更改都会回滚（`rolled_back` 为 true）；
+/// 打开、附加数据库和写入前检查等发生在事务之前的错误不涉及回滚
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreError {
+    /// 出错的数据库名称（与数据库无关的错误为 None）
+    pub database: Option<String>,
+    /// 写入失败的字段（与字段无关的错误为 None）
+    pub key: Option<String>,
+    /// 错误信息
+    pub message: String,
+    /// 数据库更改是否已回滚
+    pub rolled_back: bool,
+}
+
+impl RestoreError {
+    /// 与具体数据库无关的错误（读取备份文件、定位数据库等）
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            database: None,
+            key: None,
+            message: message.into(),
+            rolled_back: false,
+        }
+    }
+
+    /// 写入指定数据库字段时发生的错误
+    pub fn at_key(db_name: &str, key: &str, message: impl Into<String>) -> Self {
+        Self {
+            database: Some(db_name.to_string()),
+            key: Some(key.to_string()),
+            message: message.into(),
+            rolled_back: false,
+        }
+    }
+
+    /// 数据库级别的错误（打开、附加、开启或提交事务等）
+    pub fn at_database(db_name: &str, message: impl Into<String>) -> Self {
+        Self {
+            database: Some(db_name.to_string()),
+            key: None,
+            message: message.into(),
+            rolled_back: false,
+        }
+    }
+
+    /// 标记为事务内发生的错误（事务被丢弃，所有更改已回滚）
+    pub fn rolled_back(mut self) -> Self {
+        self.rolled_back = true;
+        self
+    }
+}
+
+impl fmt::Display for RestoreError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.database, &self.key) {
+            (Some(db), Some(key)) => {
+                write!(f, "恢复 {} 的字段 {} 失败: {}", db, key, self.message)?
+            }
+            (Some(db), None) => write!(f, "恢复 {} 失败: {}", db, self.message)?,
+            _ => write!(f, "{}", self.message)?,
+        }
+        if self.rolled_back {
+            write!(f, "（所有数据库更改已回滚）")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for RestoreError {}
+
+impl From<RestoreError> for String {
+    fn from(error: RestoreError) -> Self {
+        error.to_string()
+    }
+}
+
+/// 读取并解析备份 JSON 文件
+fn load_backup_data(backup_file_path: &Path) -> Result<Value, RestoreError> {
+    if !backup_file_path.exists() {
+        return Err(RestoreError::new(format!(
+            "备份文件不存在: {}",
+            backup_file_path.display()
+        )));
+    }
+
+    let content = account_vault::read_account_file(backup_file_path).map_err(RestoreError::new)?;
+    serde_json::from_str(&content).map_err(|e| RestoreError::new(e.to_string()))
+}
+
+/// 打开主数据库，并在 state.vscdb.backup 存在时将其附加为 `BACKUP_DB_SCHEMA`
+///
+/// 两个库共享同一个连接，因此可以在同一个事务中写入
+///
+/// # 返回
+/// - `Ok((conn, has_backup_db))`: 数据库连接和是否附加了备份库
+pub fn open_state_databases(app_data: &Path) -> Result<(Connection, bool), RestoreError> {
+    let conn = Connection::open(app_data)
+        .map_err(|e| RestoreError::at_database("state.vscdb", format!("打开数据库失败: {}", e)))?;
+
+    let backup_db = app_data.with_extension("vscdb.backup");
+    let has_backup_db = backup_db.exists();
+    if has_backup_db {
+        conn.execute(
+            &format!("ATTACH DATABASE ? AS {}", BACKUP_DB_SCHEMA),
+            [backup_db.to_string_lossy()],
+        )
+        .map_err(|e| {
+            RestoreError::at_database("state.vscdb.backup", format!("附加数据库失败: {}", e))
+        })?;
+    }
+
+    Ok((conn, has_backup_db))
+}
+
+/// 写入前检查主库和备份库（见 [`db_preflight::check`]），通过后再打开
+///
+/// 所有写入 state.vscdb 的恢复操作都应使用此函数
+pub fn open_state_databases_for_write(app_data: &Path) -> Result<(Connection, bool), RestoreError> {
+    db_preflight::check(app_data).map_err(|e| RestoreError::at_database("state.vscdb", e))?;
+
+    let backup_db = app_data.with_extension("vscdb.backup");
+    if backup_db.exists() {
+        db_preflight::check(&backup_db)
+            .map_err(|e| RestoreError::at_database("state.vscdb.backup", e))?;
+    }
+
+    let (conn, has_backup_db) = open_state_databases(app_data)?;
+    conn.busy_timeout(db_preflight::BUSY_TIMEOUT).map_err(|e| {
+        RestoreError::at_database("state.vscdb", format!("设置数据库忙等待超时失败: {}", e))
+    })?;
+    Ok((conn, has_backup_db))
+}
+
+/// 是否为支持跨附加数据库原子提交的回滚日志模式（WAL、MEMORY、OFF 不支持）
+fn supports_atomic_commit(journal_mode: &str) -> bool {
+    ["delete", "truncate", "persist"]
+        .iter()
+        .any(|mode| journal_mode.eq_ignore_ascii_case(mode))
+}
+
+/// 读取（`mode` 为 None）或设置数据库的日志模式，返回设置后的模式
+fn journal_mode(conn: &Connection, schema: &str, mode: Option<&str>) -> Result<String, String> {
+    let sql = match mode {
+        Some(mode) => format!("PRAGMA {}.journal_mode = {}", schema, mode),
+        None => format!("PRAGMA {}.journal_mode", schema),
+    };
+    conn.query_row(&sql, [], |row| row.get(0))
+        .map_err(|e| format!("设置日志模式失败: {}", e))
+}
+
+/// 在同一个事务中写入主库和附加的备份库，全部成功才提交
+///
+/// WAL 模式下 SQLite 不保证跨附加数据库的原子提交，写入前将两个库临时切换为 DELETE 日志模式，
+/// 结束后恢复原来的模式。`write` 返回的错误和提交失败都会标记为已回滚
+pub fn write_state_databases<T>(
+    conn: &mut Connection,
+    has_backup_db: bool,
+    write: impl FnOnce(&Transaction) -> Result<T, RestoreError>,
+) -> Result<T, RestoreError> {
+    let mut databases = vec![("main", "state.vscdb")];
+    if has_backup_db {
+        databases.push((BACKUP_DB_SCHEMA, "state.vscdb.backup"));
+    }
+
+    // 被切换了日志模式的数据库及其原来的模式
+    let mut switched: Vec<(&str, &str, String)> = Vec::new();
+    let run = || -> Result<T, RestoreError> {
+        for (schema, db_name) in &databases {
+            let mode = journal_mode(conn, schema, None)
+                .map_err(|e| RestoreError::at_database(db_name, e))?;
+            if supports_atomic_commit(&mode) {
+                continue;
+            }
+
+            let new_mode = journal_mode(conn, schema, Some("DELETE"))
+                .map_err(|e| RestoreError::at_database(db_name, e))?;
+            if !supports_atomic_commit(&new_mode) {
+                return Err(RestoreError::at_database(
+                    db_name,
+                    format!("无法将日志模式从 {} 切换为 DELETE", mode),
+                ));
+            }
+            tracing::info!("  🔁 {} 日志模式临时切换: {} -> DELETE", db_name, mode);
+            switched.push((schema, db_name, mode));
+        }
+
+        let tx = conn.transaction().map_err(|e| {
+            RestoreError::at_database("state.vscdb", format!("开启事务失败: {}", e))
+        })?;
+        // 出错时 tx 被丢弃，两个库的更改一起回滚
+        let value = write(&tx).map_err(RestoreError::rolled_back)?;
+        tx.commit().map_err(|e| {
+            RestoreError::at_database("state.vscdb", format!("提交事务失败: {}", e)).rolled_back()
+        })?;
+        Ok(value)
+    };
+    let result = run();
+
+    for (schema, db_name, mode) in switched {
+        if let Err(e) = journal_mode(conn, schema, Some(&mode)) {
+            tracing::warn!("  ⚠️ 恢复 {} 的日志模式 {} 失败: {}", db_name, mode, e);
+        }
+    }
+
+    result
+}
+
+/// 从备份的 Marker 中获取 Key 对应的 flag (0 或 1)
+/// 如果找不到，回退到安全默认值
+fn get_marker_flag_from_backup(backup_marker: &Option<&Value>, key: &str) -> i32 {
+    if let Some(marker_val) = backup_marker {
+        if let Some(marker_obj) = marker_val.as_object() {
+            if let Some(flag) = marker_obj.get(key) {
+                if let Some(i) = flag.as_i64() {
+                    tracing::info!("  📖 从备份 Marker 读取 {} = {}", key, i);
+                    return i as i32;
+                }
+            }
+        }
+    }
+
+    // 只有在备份文件损坏或是旧版本时才使用此回退逻辑
+    let default = default_marker_flag(key);
+    tracing::warn!(
+        "  ⚠️ 备份中没有 {} 的 Marker 信息，使用默认值: {}",
+        key,
+        default
+    );
+    default
+}
+
+/// 没有 Marker 信息时字段的默认 flag
+pub(crate) fn default_marker_flag(key: &str) -> i32 {
+    match key {
+        database::AUTH_STATUS
+        | database::PROFILE_URL
+        | database::ONBOARDING
+        | database::COMMAND_CONFIGS => 0,
+        _ => 1,
+    }
+}
+
+/// 选定类别中要恢复的字段及其是否参与 Marker 合并
+///
+/// 包括内置字段和备份文件中记录的自定义字段（以备份时的设置为准，不读取当前配置）
+fn restore_keys(backup_data: &Value, categories: &[DataCategory]) -> Vec<(String, bool)> {
+    let builtin = data_category::keys_of(categories)
+        .into_iter()
+        // 只有非特殊字段才需要在 Marker 中注册
+        .map(|key| (key.to_string(), key != database::NEW_STORAGE_MARKER));
+
+    let extra = extra_keys::from_backup(backup_data)
+        .into_iter()
+        .filter(|extra| categories.contains(&extra.category))
+        .filter(|extra| !database::ALL_KEYS.contains(&extra.key.as_str()))
+        .map(|extra| (extra.key, extra.marker));
+
+    builtin.chain(extra).collect()
+}
+
+/// 选定类别包含通知时，备份中记录的通知字段及其值（只包含字符串值）
+fn restore_notifications<'a>(
+    backup_data: &'a Value,
+    categories: &[DataCategory],
+) -> Vec<(&'a str, &'a str)> {
+    if !data_category::includes_notifications(categories) {
+        return Vec::new();
+    }
+
+    backup_data
+        .get("notification_keys")
+        .and_then(|v| v.as_array())
+        .map(|keys| {
+            keys.iter()
+                .filter_map(|k| k.as_str())
+                .filter_map(|k| backup_data.get(k).and_then(|v| v.as_str()).map(|v| (k, v)))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 恢复时可能写入的全部字段（__$__targetStorageMarker 除外）
+///
+/// 包括选定类别的字段、通知字段和分析数据上传时间戳。切换前快照按此保存原始值，撤销切换时才能完整还原
+pub fn written_keys(backup_data: &Value, categories: &[DataCategory]) -> Vec<String> {
+    let mut keys: Vec<String> = restore_keys(backup_data, categories)
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    keys.extend(
+        restore_notifications(backup_data, categories)
+            .into_iter()
+            .map(|(key, _)| key.to_string()),
+    );
+    keys.push(database::ANALYTICS_LAST_UPLOAD_TIME.to_string());
+    keys
+}
+
+/// 通用数据库恢复方法（终极版 - 从备份 Marker 读取值）
+///
+/// 在调用方提供的事务中执行精确的数据库恢复操作（只处理选定类别的字段）：
+/// 1. 从备份中读取字段的原始值
+/// 2. 插入到数据库（使用 INSERT OR REPLACE）
+/// 3. 从备份的 Marker 中读取每个字段应该是 0 还是 1
+/// 4. 智能合并 Marker（保留现有配置）
+///
+/// 任何一步写入失败都会立即返回错误，由调用方丢弃事务以回滚所有更改
+///
+/// # 参数
+/// - `tx`: 当前事务
+/// - `schema`: 目标数据库的 schema 名称（`main` 或附加的备份库）
+/// - `db_name`: 数据库名称（用于日志显示和错误报告）
+/// - `backup_data`: 备份数据的 JSON 对象
+/// - `categories`: 要恢复的数据类别
+///
+/// # 返回
+/// - `Ok(restored_count)`: 成功恢复的项目数量
+/// - `Err(error)`: 写入失败的数据库和字段
+fn restore_database(
+    tx: &Transaction,
+    schema: &str,
+    db_name: &str,
+    backup_data: &Value,
+    categories: &[DataCategory],
+) -> Result<usize, RestoreError> {
+    tracing::info!("🔄 恢复数据库: {}", db_name);
+
+    let insert_sql = format!(
+        "INSERT OR REPLACE INTO {}.ItemTable (key, value) VALUES (?, ?)",
+        schema
+    );
+
+    // 只恢复选定类别的字段（含备份中记录的自定义字段），其余字段保持数据库当前的值
+    let keys_to_restore = restore_keys(backup_data, categories);
+
+    let mut restored_count = 0;
+    let mut restored_keys = Vec::new();
+
+    // 1. 插入数据（Value 直接使用备份中的原始字符串）
+    for (key, in_marker) in &keys_to_restore {
+        if let Some(val) = backup_data.get(key) {
+            if let Some(val_str) = val.as_str() {
+                tx.execute(&insert_sql, params![key, val_str])
+                    .map_err(|e| RestoreError::at_key(db_name, key, e.to_string()))?;
+                tracing::info!("  ✅ 注入数据: {}", key);
+                restored_count += 1;
+                if *in_marker {
+                    restored_keys.push(key);
+                }
+            } else {
+                tracing::warn!("  ⚠️ 字段 {} 不是字符串类型，跳过", key);
+            }
+        } else {
+            tracing::info!("  ℹ️ 备份中未找到: {} (跳过)", key);
+        }
+    }
+
+    // 2. 恢复通知字段（避免历史通知重复弹窗）
+    let notifications = restore_notifications(backup_data, categories);
+    if !notifications.is_empty() {
+        tracing::info!("  📬 开始恢复 {} 个通知字段...", notifications.len());
+
+        for (notification_key, notification_str) in &notifications {
+            tx.execute(&insert_sql, params![notification_key, notification_str])
+                .map_err(|e| RestoreError::at_key(db_name, notification_key, e.to_string()))?;
+            tracing::info!("  ✅ 恢复通知: {}", notification_key);
+            // 通知字段不添加到 restored_keys 中，因为它们通常不需要参与 Marker 同步
+        }
+
+        tracing::info!("  ✅ 成功恢复 {} 个通知字段", notifications.len());
+    }
+
+    // 3. 智能合并 Marker
+    if !restored_keys.is_empty() {
+        tracing::info!("  🔧 开始智能合并 Marker...");
+
+        // A. 读取当前数据库的 Marker
+        let current_marker_str: Option<String> = tx
+            .query_row(
+                &format!("SELECT value FROM {}.ItemTable WHERE key = ?", schema),
+                [database::TARGET_STORAGE_MARKER],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| {
+                RestoreError::at_key(
+                    db_name,
+                    database::TARGET_STORAGE_MARKER,
+                    format!("读取 Marker 失败: {}", e),
+                )
+            })?;
+
+        let mut current_marker_obj = match current_marker_str {
+            Some(s) => {
+                tracing::info!("  📋 读取到现有 Marker");
+                serde_json::from_str::<serde_json::Map<String, Value>>(&s).unwrap_or_default()
+            }
+            None => {
+                tracing::info!("  ℹ️ 未找到现有 Marker，创建新的");
+                serde_json::Map::new()
+            }
+        };
+
+        tracing::info!(
+            "  📊 合并前 Marker 包含 {} 个字段",
+            current_marker_obj.len()
+        );
+
+        // B. 获取备份文件中的 Marker（作为参考源）
+        let backup_marker = backup_data.get("__$__targetStorageMarker");
+        if backup_marker.is_some() {
+            tracing::info!("  📖 从备份文件中读取到完整 Marker，将使用其中的值作为参考");
+        } else {
+            tracing::warn!("  ⚠️ 备份文件中没有 Marker，将使用默认值");
+        }
+
+        // C. 只将本次实际写入的 Key 的 Marker 状态合并进去，其余 Key 保持现状
+        for key in &restored_keys {
+            // 关键：从备份里读取它是 0 还是 1，而不是瞎猜
+            let flag = get_marker_flag_from_backup(&backup_marker, key);
+            current_marker_obj.insert(key.to_string(), json!(flag));
+        }
+
+        tracing::info!(
+            "  📊 合并后 Marker 包含 {} 个字段",
+            current_marker_obj.len()
+        );
+
+        // D. 写回 Marker
+        let new_marker_str = serde_json::to_string(&current_marker_obj).map_err(|e| {
+            RestoreError::at_key(
+                db_name,
+                database::TARGET_STORAGE_MARKER,
+                format!("序列化 Marker 失败: {}", e),
+            )
+        })?;
+
+        tx.execute(
+            &insert_sql,
+            params![database::TARGET_STORAGE_MARKER, new_marker_str],
+        )
+        .map_err(|e| {
+            RestoreError::at_key(
+                db_name,
+                database::TARGET_STORAGE_MARKER,
+                format!("更新 Marker 失败: {}", e),
+            )
+        })?;
+
+        tracing::info!("  ✅ Marker 已智能合并（使用备份中的精确值）");
+
+        // E. 重置上传时间戳（防止 Sync 冲突）
+        tx.execute(
+            &insert_sql,
+            params![database::ANALYTICS_LAST_UPLOAD_TIME, "0"],
+        )
+        .map_err(|e| {
+            RestoreError::at_key(db_name, database::ANALYTICS_LAST_UPLOAD_TIME, e.to_string())
+        })?;
+        tracing::info!("  ✅ 已重置分析时间戳");
+    } else {
+        tracing::warn!("  ⚠️ 未恢复任何数据，跳过 Marker 更新");
+    }
+
+    Ok(restored_count)
+}
+
+/// 恢复 Antigravity 的用户认证数据（终极版）
+///
+/// 从备份文件恢复用户数据到数据库：
+/// - 恢复所有字段的原始值
+/// - 从备份的 Marker 中读取每个字段的同步状态（0 或 1）
+/// - 恢复 __$__isNewStorageMarker 状态标记
+/// - 同时处理主数据库和备份数据库
+///
+/// 备份数据库通过 ATTACH 附加到主库连接上，两个库的写入在同一个事务中完成：
+/// 只有两者都成功才会提交，任意一个失败都会整体回滚
+///
+/// # 参数
+/// - `backup_file_path`: 备份 JSON 文件的完整路径
+/// - `categories`: 要恢复的数据类别（[`DataCategory::ALL`] 为完整恢复）
+///
+/// # 返回
+/// - `Ok(message)`: 成功消息
+/// - `Err(error)`: 结构化错误，包含失败的数据库和字段
+pub async fn restore_all_antigravity_data(
+    backup_file_path: PathBuf,
+    categories: &[DataCategory],
+) -> Result<String, RestoreError> {
+    let app_data = platform_utils::resolve_antigravity_db_path().map_err(RestoreError::new)?;
+    restore_backup_to(&app_data, &backup_file_path, categories)
+}
+
+/// 将备份文件中选定类别的数据恢复到指定的 state.vscdb（及同目录下的 state.vscdb.backup）
+pub fn restore_backup_to(
+    app_data: &Path,
+    backup_file_path: &Path,
+    categories: &[DataCategory],
+) -> Result<String, RestoreError> {
+    tracing::info!("📂 备份文件: {}", backup_file_path.display());
+    let backup_data = load_backup_data(backup_file_path)?;
+    tracing::info!("✅ 备份文件读取成功");
+
+    restore_backup_data_to(app_data, &backup_data, categories)
+}
+
+/// 将已读取的备份内容中选定类别的数据恢复到指定的 state.vscdb（及同目录下的 state.vscdb.backup）
+///
+/// 供需要在关闭进程前先读取并校验备份的流程使用（如账户切换）
+pub fn restore_backup_data_to(
+    app_data: &Path,
+    backup_data: &Value,
+    categories: &[DataCategory],
+) -> Result<String, RestoreError> {
+    if categories.is_empty() {
+        return Err(RestoreError::new("至少需要选择一个数据类别"));
+    }
+
+    tracing::info!("🚀 开始执行智能恢复（从备份 Marker 读取精确值）...");
+    tracing::info!("🗂️ 恢复类别: {}", data_category::describe(categories));
+
+    // 确保数据库目录存在
+    if let Some(parent) = app_data.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| RestoreError::new(format!("创建数据库目录失败: {}", e)))?;
+    }
+
+    let (mut conn, has_backup_db) = open_state_databases_for_write(app_data)?;
+
+    // 两个库在同一个事务中恢复，都成功后统一提交
+    let mut msg = write_state_databases(&mut conn, has_backup_db, |tx| {
+        tracing::info!("📊 步骤1: 恢复 state.vscdb 数据库");
+        let count = restore_database(tx, "main", "state.vscdb", backup_data, categories)?;
+        let mut msg = format!("主库恢复 {} 项", count);
+        tracing::info!("  ✅ {}", msg);
+
+        tracing::info!("💾 步骤2: 恢复 state.vscdb.backup");
+        if has_backup_db {
+            let count = restore_database(
+                tx,
+                BACKUP_DB_SCHEMA,
+                "state.vscdb.backup",
+                backup_data,
+                categories,
+            )?;
+            let status = format!("; 备份库恢复 {} 项", count);
+            tracing::info!("  ✅ {}", status);
+            msg.push_str(&status);
+        } else {
+            tracing::info!("  ℹ️ 备份数据库不存在，跳过");
+        }
+        Ok(msg)
+    })?;
+    tracing::info!("✅ 事务已提交");
+
+    if !data_category::is_all(categories) {
+        msg.push_str(&format!(" (仅 {})", data_category::describe(categories)));
+    }
+
+    Ok(format!("✅ 恢复成功! {}", msg))
+}
+
+/// 预览中单个字段的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyAction {
+    /// 数据库中不存在，将被插入
+    Insert,
+    /// 数据库中存在且值不同，将被替换
+    Replace,
+    /// 数据库中的值与备份相同
+    Unchanged,
+    /// 备份中没有该字段，保持原样
+    Skip,
+}
+
+/// 预览中单个字段的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct KeyPreview {
+    pub key: String,
+    pub action: KeyAction,
+}
+
+/// 预览中 Marker 标记的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkerFlagChange {
+    pub key: String,
+    /// 当前 Marker 中的值（不存在时为 None）
+    pub from: Option<i64>,
+    /// 恢复后的值
+    pub to: i32,
+}
+
+/// 单个数据库的恢复预览
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseRestorePreview {
+    pub database: String,
+    pub keys: Vec<KeyPreview>,
+    pub marker_changes: Vec<MarkerFlagChange>,
+    /// 将写入的通知字段数量
+    pub notification_count: usize,
+}
+
+/// 恢复预览（dry-run 结果）
+#[derive(Debug, Clone, Serialize)]
+pub struct RestorePreview {
+    pub backup_file: String,
+    /// 备份所属账户
+    pub account_email: Option<String>,
+    /// 预览的数据类别
+    pub categories: Vec<DataCategory>,
+    pub databases: Vec<DatabaseRestorePreview>,
+}
+
+/// 读取数据库当前的值，计算恢复会带来的变化（只读）
+///
+/// 字段比较复用 `state_diff::diff_fields`：新增对应插入，修改对应替换
+fn preview_database(
+    db_path: &Path,
+    db_name: &str,
+    backup_data: &Value,
+    categories: &[DataCategory],
+) -> Result<DatabaseRestorePreview, RestoreError> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| RestoreError::at_database(db_name, format!("打开数据库失败: {}", e)))?;
+
+    let read_value = |key: &str| -> Result<Option<String>, RestoreError> {
+        conn.query_row("SELECT value FROM ItemTable WHERE key = ?", [key], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(|e| RestoreError::at_key(db_name, key, e.to_string()))
+    };
+
+    // 1. 构建当前值与备份值的对照表（只包含选定类别中备份存在的字符串字段）
+    let keys_to_restore = restore_keys(backup_data, categories);
+    let mut current_values = serde_json::Map::new();
+    let mut backup_values = serde_json::Map::new();
+    for (key, _) in &keys_to_restore {
+        if let Some(backup_str) = backup_data.get(key).and_then(|v| v.as_str()) {
+            backup_values.insert(key.clone(), Value::String(backup_str.to_string()));
+            if let Some(current) = read_value(key)? {
+                current_values.insert(key.clone(), Value::String(current));
+            }
+        }
+    }
+
+    let changes = state_diff::diff_fields(&current_values, &backup_values);
+    let keys = keys_to_restore
+        .iter()
+        .map(|(key, _)| {
+            let action = if !backup_values.contains_key(key) {
+                KeyAction::Skip
+            } else {
+                match changes.iter().find(|c| &c.key == key).map(|c| c.kind) {
+                    Some(ChangeKind::Added) => KeyAction::Insert,
+                    Some(_) => KeyAction::Replace,
+                    None => KeyAction::Unchanged,
+                }
+            };
+            KeyPreview {
+                key: key.clone(),
+                action,
+            }
+        })
+        .collect();
+
+    // 2. 计算 Marker 变化（与 restore_database 的合并规则一致）
+    let current_marker: serde_json::Map<String, Value> =
+        read_value(database::TARGET_STORAGE_MARKER)?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default();
+    let backup_marker = backup_data.get(database::TARGET_STORAGE_MARKER);
+
+    let marker_changes = keys_to_restore
+        .iter()
+        .filter(|(key, in_marker)| *in_marker && backup_values.contains_key(key))
+        .filter_map(|(key, _)| {
+            let from = current_marker.get(key).and_then(|v| v.as_i64());
+            let to = get_marker_flag_from_backup(&backup_marker, key);
+            (from != Some(to as i64)).then(|| MarkerFlagChange {
+                key: key.clone(),
+                from,
+                to,
+            })
+        })
+        .collect();
+
+    // 3. 统计将写入的通知字段
+    let notification_count = restore_notifications(backup_data, categories).len();
+
+    Ok(DatabaseRestorePreview {
+        database: db_name.to_string(),
+        keys,
+        marker_changes,
+        notification_count,
+    })
+}
+
+/// 预览恢复操作（dry-run），不写入任何数据
+///
+/// 返回每个数据库中哪些字段会被插入、替换或保持不变，
+/// Marker 标记如何变化，以及会写入多少个通知字段
+///
+/// # 参数
+/// - `backup_file_path`: 备份 JSON 文件的完整路径
+/// - `categories`: 要恢复的数据类别
+pub fn preview_restore(
+    backup_file_path: &Path,
+    categories: &[DataCategory],
+) -> Result<RestorePreview, RestoreError> {
+    let app_data = platform_utils::resolve_antigravity_db_path().map_err(RestoreError::new)?;
+    preview_restore_to(&app_data, backup_file_path, categories)
+}
+
+/// 预览将备份文件中选定类别的数据恢复到指定 state.vscdb 会带来的变化（只读）
+pub fn preview_restore_to(
+    app_data: &Path,
+    backup_file_path: &Path,
+    categories: &[DataCategory],
+) -> Result<RestorePreview, RestoreError> {
+    let backup_data = load_backup_data(backup_file_path)?;
+
+    let mut databases = Vec::new();
+
+    if app_data.exists() {
+        databases.push(preview_database(
+            app_data,
+            "state.vscdb",
+            &backup_data,
+            categories,
+        )?);
+    }
+
+    let backup_db = app_data.with_extension("vscdb.backup");
+    if backup_db.exists() {
+        databases.push(preview_database(
+            &backup_db,
+            "state.vscdb.backup",
+            &backup_data,
+            categories,
+        )?);
+    }
+
+    Ok(RestorePreview {
+        backup_file: backup_file_path.display().to_string(),
+        account_email: backup_data
+            .get("account_email")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        categories: categories.to_vec(),
+        databases,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    /// 创建 WAL 模式的主库和备份库，以及包含新登录信息的备份文件
+    fn setup(name: &str) -> (PathBuf, PathBuf) {
+        let dir = test_support::temp_dir(name);
+        let app_data = dir.join("state.vscdb");
+        for path in [app_data.clone(), app_data.with_extension("vscdb.backup")] {
+            let conn = test_support::create_state_db(
+                &path,
+                &[
+                    (database::AUTH_STATUS, "old-auth"),
+                    (database::PROFILE_URL, "old-profile"),
+                ],
+            );
+            let mode: String = conn
+                .query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))
+                .unwrap();
+            assert_eq!(mode, "wal");
+        }
+
+        let backup_file = dir.join("backup.json");
+        fs::write(
+            &backup_file,
+            json!({
+                database::AUTH_STATUS: "new-auth",
+                database::PROFILE_URL: "new-profile",
+            })
+            .to_string(),
+        )
+        .unwrap();
+        (app_data, backup_file)
+    }
+
+    fn value_of(path: &Path, key: &str) -> Option<String> {
+        test_support::item_rows(path)
+            .into_iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    fn journal_mode_of(path: &Path) -> String {
+        Connection::open(path)
+            .unwrap()
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn restore_writes_both_databases_and_keeps_wal_mode() {
+        let (app_data, backup_file) = setup("restore-both");
+        let backup_db = app_data.with_extension("vscdb.backup");
+
+        restore_backup_to(&app_data, &backup_file, DataCategory::ALL).unwrap();
+
+        for path in [&app_data, &backup_db] {
+            assert_eq!(
+                value_of(path, database::AUTH_STATUS).as_deref(),
+                Some("new-auth")
+            );
+            assert_eq!(journal_mode_of(path), "wal");
+        }
+    }
+
+    #[test]
+    fn failed_backup_db_write_leaves_main_db_unchanged() {
+        let (app_data, backup_file) = setup("restore-rollback");
+        let backup_db = app_data.with_extension("vscdb.backup");
+        Connection::open(&backup_db)
+            .unwrap()
+            .execute_batch(
+                "CREATE TRIGGER reject BEFORE INSERT ON ItemTable
+                 BEGIN SELECT RAISE(ABORT, 'rejected'); END;",
+            )
+            .unwrap();
+        let main_before = test_support::item_rows(&app_data);
+        let backup_before = test_support::item_rows(&backup_db);
+
+        let error = restore_backup_to(&app_data, &backup_file, DataCategory::ALL).unwrap_err();
+        assert_eq!(error.database.as_deref(), Some("state.vscdb.backup"));
+        assert!(error.rolled_back);
+
+        assert_eq!(test_support::item_rows(&app_data), main_before);
+        assert_eq!(test_support::item_rows(&backup_db), backup_before);
+        assert_eq!(journal_mode_of(&app_data), "wal");
+    }
+
+    #[test]
+    fn only_transaction_errors_are_rolled_back() {
+        let open_error = RestoreError::at_database("state.vscdb", "数据库被占用");
+        assert!(!open_error.rolled_back);
+        assert_eq!(
+            open_error.to_string(),
+            "恢复 state.vscdb 失败: 数据库被占用"
+        );
+
+        let write_error = RestoreError::at_key("state.vscdb", "key", "写入失败").rolled_back();
+        assert!(write_error.rolled_back);
+        assert_eq!(
+            write_error.to_string(),
+            "恢复 state.vscdb 的字段 key 失败: 写入失败（所有数据库更改已回滚）"
+        );
+    }
+
+    fn action_of(preview: &DatabaseRestorePreview, key: &str) -> Option<KeyAction> {
+        preview.keys.iter().find(|k| k.key == key).map(|k| k.action)
+    }
+
+    #[test]
+    fn preview_reports_changes_without_writing() {
+        let (app_data, backup_file) = setup("restore-preview");
+        let notification = format!("{}welcome", database::NOTIFICATION_PREFIX);
+        fs::write(
+            &backup_file,
+            json!({
+                "account_email": "user@example.com",
+                database::AUTH_STATUS: "new-auth",
+                database::GOOGLE_DATA: "google",
+                database::PROFILE_URL: "old-profile",
+                &notification: "seen",
+                "notification_keys": [&notification],
+            })
+            .to_string(),
+        )
+        .unwrap();
+        let main_before = test_support::item_rows(&app_data);
+
+        let preview = preview_restore_to(&app_data, &backup_file, DataCategory::ALL).unwrap();
+        assert_eq!(preview.account_email.as_deref(), Some("user@example.com"));
+        assert_eq!(preview.databases.len(), 2);
+
+        let main = &preview.databases[0];
+        assert_eq!(main.database, "state.vscdb");
+        assert_eq!(
+            action_of(main, database::AUTH_STATUS),
+            Some(KeyAction::Replace)
+        );
+        assert_eq!(
+            action_of(main, database::GOOGLE_DATA),
+            Some(KeyAction::Insert)
+        );
+        assert_eq!(
+            action_of(main, database::PROFILE_URL),
+            Some(KeyAction::Unchanged)
+        );
+        assert_eq!(
+            action_of(main, database::USER_SETTINGS),
+            Some(KeyAction::Skip)
+        );
+        assert_eq!(main.notification_count, 1);
+        assert!(main
+            .marker_changes
+            .iter()
+            .any(|change| change.key == database::AUTH_STATUS && change.from.is_none()));
+
+        // 只预览登录凭据时不包含其他类别的字段和通知
+        let auth_only = preview_restore_to(&app_data, &backup_file, &[DataCategory::Auth]).unwrap();
+        assert_eq!(
+            action_of(&auth_only.databases[0], database::PROFILE_URL),
+            None
+        );
+        assert_eq!(auth_only.databases[0].notification_count, 0);
+
+        assert_eq!(test_support::item_rows(&app_data), main_before);
+    }
+
+    #[test]
+    fn category_restore_leaves_other_categories_untouched() {
+        let (app_data, backup_file) = setup("restore-categories");
+
+        let message = restore_backup_to(&app_data, &backup_file, &[DataCategory::Auth]).unwrap();
+        assert!(message.contains("仅 auth"), "{}", message);
+
+        for path in [&app_data, &app_data.with_extension("vscdb.backup")] {
+            assert_eq!(
+                value_of(path, database::AUTH_STATUS).as_deref(),
+                Some("new-auth")
+            );
+            assert_eq!(
+                value_of(path, database::PROFILE_URL).as_deref(),
+                Some("old-profile")
+            );
+        }
+        assert!(restore_backup_to(&app_data, &backup_file, &[]).is_err());
+    }
+}
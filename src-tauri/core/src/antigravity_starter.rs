@@ -2,9 +2,29 @@
 ///
 /// 提供跨平台的 Antigravity 应用程序启动功能
 /// 支持 Windows、macOS 和 Linux 系统
+use std::collections::BTreeSet;
 use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::process::{Child, Command, Stdio};
+use std::sync::Mutex;
 
+/// 由 Antigravity Agent 启动的进程 ID
+///
+/// 这些进程虽然是 Agent 的子进程，但属于 Antigravity 应用本身，关闭 Antigravity 时不应被当作 Agent 自身排除
+static LAUNCHED_PIDS: Mutex<BTreeSet<u32>> = Mutex::new(BTreeSet::new());
+
+fn remember_launched(child: &Child) {
+    if let Ok(mut pids) = LAUNCHED_PIDS.lock() {
+        pids.insert(child.id());
+    }
+}
+
+/// 判断进程是否由 Antigravity Agent 启动
+pub fn is_launched_by_agent(pid: u32) -> bool {
+    LAUNCHED_PIDS
+        .lock()
+        .map(|pids| pids.contains(&pid))
+        .unwrap_or(false)
+}
 
 /// 启动 Antigravity 应用程序（主入口函数）
 ///
@@ -15,10 +35,12 @@ use std::process::{Command, Stdio};
 ///
 /// # 示例
 ///
-/// ```rust
+/// ```no_run
+/// use antigravity_agent_core::antigravity_starter;
+///
 /// match antigravity_starter::start_antigravity() {
-///     Ok(msg) => println!("启动成功: {}", msg),
-///     Err(e) => println!("启动失败: {}", e),
+///     Ok(msg) => tracing::info!("启动成功: {}", msg),
+///     Err(e) => tracing::info!("启动失败: {}", e),
 /// }
 /// ```
 pub fn start_antigravity() -> Result<String, String> {
@@ -33,7 +55,7 @@ pub fn start_antigravity() -> Result<String, String> {
             tracing::warn!("⚠️ 自定义可执行文件路径无效: {}", custom_exec);
         }
     }
-    
+
     // 回退到自动检测
     match std::env::consts::OS {
         "windows" => start_antigravity_windows(),
@@ -157,9 +179,6 @@ fn start_antigravity_linux() -> Result<String, String> {
     }
 }
 
-
-
-
 /// 尝试从指定路径启动应用程序
 fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
     // macOS 需要特殊处理：使用 open 命令启动 .app 应用
@@ -174,13 +193,14 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
 
         // 方法1: 尝试不带 -n 参数的 open 命令（更兼容）
         match Command::new("open")
-            .arg("-g")  // 在后台启动应用
+            .arg("-g") // 在后台启动应用
             .arg(&app_bundle_path)
             .stdout(std::process::Stdio::null())
             .stderr(std::process::Stdio::null())
             .spawn()
         {
-            Ok(_) => {
+            Ok(child) => {
+                remember_launched(&child);
                 return Ok("Antigravity 已启动".to_string());
             }
             Err(e1) => {
@@ -194,7 +214,8 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
                             .stderr(std::process::Stdio::null())
                             .spawn()
                         {
-                            Ok(_) => {
+                            Ok(child) => {
+                                remember_launched(&child);
                                 return Ok("Antigravity 已启动".to_string());
                             }
                             Err(_) => {
@@ -211,7 +232,8 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
                     .stderr(std::process::Stdio::null())
                     .spawn()
                 {
-                    Ok(_) => {
+                    Ok(child) => {
+                        remember_launched(&child);
                         return Ok("Antigravity 已启动".to_string());
                     }
                     Err(e3) => {
@@ -232,6 +254,7 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
                 .stdout(Stdio::null())
                 .stderr(Stdio::null())
                 .spawn()
+                .map(|child| remember_launched(&child))
                 .map_err(|e| format!("启动失败: {}", e))?;
         }
 
@@ -242,6 +265,7 @@ fn try_start_from_path(path: &PathBuf) -> Result<String, String> {
                 .stdout(Stdio::null())
                 .stderr(Stdio::null())
                 .spawn()
+                .map(|child| remember_launched(&child))
                 .map_err(|e| format!("启动失败: {}", e))?;
         }
 
@@ -259,7 +283,8 @@ fn try_start_from_commands(commands: Vec<&str>) -> Result<String, String> {
             .stderr(std::process::Stdio::null())
             .spawn()
         {
-            Ok(_) => {
+            Ok(child) => {
+                remember_launched(&child);
                 return Ok("Antigravity 已启动".to_string());
             }
             Err(e) => {
@@ -271,7 +296,6 @@ fn try_start_from_commands(commands: Vec<&str>) -> Result<String, String> {
     Err(format!("所有命令尝试失败: {}", errors.join(", ")))
 }
 
-
 /// 检测 Antigravity 可执行文件路径（不启动，只检测）
 pub fn detect_antigravity_executable() -> Option<PathBuf> {
     tracing::info!("🔍 开始自动检测 Antigravity 可执行文件...");
@@ -0,0 +1,439 @@
+// 审计日志模块
+// 以 JSON Lines 追加写入 audit_log.jsonl（位于配置目录下），每次备份、恢复、切换、注销、导入、删除、完整快照
+// 以及监控检测到的登录变化都记录一条；支持按账户和时间范围查询，并导出为 CSV 或 JSON
+
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::constants::audit_log;
+use crate::path_utils::AppPaths;
+
+/// 操作类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditAction {
+    /// 备份账户
+    Backup,
+    /// 将账户备份恢复到数据库
+    Restore,
+    /// 切换账户
+    Switch,
+    /// 撤销上次切换
+    UndoSwitch,
+    /// 注销（清除账户数据）
+    Logout,
+    /// 从导出文件导入账户备份
+    Import,
+    /// 删除账户备份
+    Delete,
+    /// 数据库监控检测到登录状态变化（登录、登出或在 Antigravity 中切换账户）
+    LoginDetected,
+    /// 保存完整数据库快照
+    Snapshot,
+    /// 恢复完整数据库快照
+    RestoreSnapshot,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::Backup => "backup",
+            AuditAction::Restore => "restore",
+            AuditAction::Switch => "switch",
+            AuditAction::UndoSwitch => "undo_switch",
+            AuditAction::Logout => "logout",
+            AuditAction::Import => "import",
+            AuditAction::Delete => "delete",
+            AuditAction::LoginDetected => "login_detected",
+            AuditAction::Snapshot => "snapshot",
+            AuditAction::RestoreSnapshot => "restore_snapshot",
+        }
+    }
+}
+
+/// 操作结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+}
+
+impl AuditOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditOutcome::Success => "success",
+            AuditOutcome::Failure => "failure",
+        }
+    }
+}
+
+/// 一条审计记录
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// 记录时间（RFC3339）
+    pub timestamp: String,
+    pub action: AuditAction,
+    /// 操作前的账户（如切换前登录的账户）
+    pub from_account: Option<String>,
+    /// 操作的目标账户
+    pub to_account: Option<String>,
+    pub outcome: AuditOutcome,
+    /// 补充说明（失败时为错误信息）
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    /// 以当前时间创建一条成功的记录
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            timestamp: Local::now().to_rfc3339(),
+            action,
+            from_account: None,
+            to_account: None,
+            outcome: AuditOutcome::Success,
+            detail: None,
+        }
+    }
+
+    pub fn from_account(mut self, account: Option<&str>) -> Self {
+        self.from_account = account.map(String::from);
+        self
+    }
+
+    pub fn to_account(mut self, account: Option<&str>) -> Self {
+        self.to_account = account.map(String::from);
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// 根据操作结果设置 outcome，失败时把错误信息写入 detail
+    pub fn result<T, E: std::fmt::Display>(mut self, result: &Result<T, E>) -> Self {
+        if let Err(e) = result {
+            self.outcome = AuditOutcome::Failure;
+            self.detail = Some(e.to_string());
+        }
+        self
+    }
+
+    /// 记录时间（无法解析时为 None）
+    pub fn time(&self) -> Option<DateTime<Local>> {
+        DateTime::parse_from_rfc3339(&self.timestamp)
+            .ok()
+            .map(|time| time.with_timezone(&Local))
+    }
+
+    /// 是否涉及指定账户（作为来源或目标）
+    pub fn involves(&self, account: &str) -> bool {
+        self.from_account.as_deref() == Some(account) || self.to_account.as_deref() == Some(account)
+    }
+}
+
+/// 默认审计日志路径
+pub fn log_path() -> Option<PathBuf> {
+    AppPaths::config_dir().map(|dir| dir.join(audit_log::FILE_NAME))
+}
+
+/// 追加一条记录到默认审计日志（失败只记录警告，不影响操作结果）
+pub fn record(entry: AuditEntry) {
+    let result = log_path()
+        .ok_or_else(|| "无法获取配置目录".to_string())
+        .and_then(|path| append(&path, &entry));
+
+    if let Err(e) = result {
+        tracing::warn!("⚠️ 写入审计日志失败: {}", e);
+    }
+}
+
+/// 追加一条记录到指定的审计日志文件
+pub fn append(path: &Path, entry: &AuditEntry) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建审计日志目录失败: {}", e))?;
+    }
+
+    let mut line =
+        serde_json::to_string(entry).map_err(|e| format!("序列化审计记录失败: {}", e))?;
+    line.push('\n');
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("写入审计日志失败 ({}): {}", path.display(), e))
+}
+
+/// 读取审计日志中的所有记录（按写入顺序，跳过损坏的行）
+pub fn read_entries(path: &Path) -> Result<Vec<AuditEntry>, String> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("读取审计日志失败 ({}): {}", path.display(), e))?;
+
+    Ok(content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter_map(|(index, line)| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                tracing::warn!("⚠️ 跳过损坏的审计记录（第 {} 行）: {}", index + 1, e);
+                None
+            }
+        })
+        .collect())
+}
+
+/// 查询条件（所有条件都是可选的）
+#[derive(Debug, Clone, Default)]
+pub struct AuditQuery {
+    /// 只返回涉及该账户的记录
+    pub account: Option<String>,
+    /// 起始时间（包含）
+    pub since: Option<DateTime<Local>>,
+    /// 结束时间（包含）
+    pub until: Option<DateTime<Local>>,
+}
+
+impl AuditQuery {
+    /// 从字符串条件构建查询，时间支持 RFC3339 或 `YYYY-MM-DD`（`until` 为日期时包含当天）
+    pub fn parse(
+        account: Option<String>,
+        since: Option<&str>,
+        until: Option<&str>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            account: account.filter(|account| !account.is_empty()),
+            since: since.map(|s| parse_time_bound(s, false)).transpose()?,
+            until: until.map(|s| parse_time_bound(s, true)).transpose()?,
+        })
+    }
+
+    pub fn matches(&self, entry: &AuditEntry) -> bool {
+        if let Some(account) = &self.account {
+            if !entry.involves(account) {
+                return false;
+            }
+        }
+
+        if self.since.is_none() && self.until.is_none() {
+            return true;
+        }
+
+        let Some(time) = entry.time() else {
+            return false;
+        };
+        self.since.is_none_or(|since| time >= since) && self.until.is_none_or(|until| time <= until)
+    }
+}
+
+/// 解析时间条件（日期作为结束时间时取当天最后一刻）
+fn parse_time_bound(value: &str, end_of_day: bool) -> Result<DateTime<Local>, String> {
+    let value = value.trim();
+
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.with_timezone(&Local));
+    }
+
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("无效的时间: {}（支持 RFC3339 或 YYYY-MM-DD）", value))?;
+    let time = if end_of_day {
+        date.and_hms_milli_opt(23, 59, 59, 999)
+    } else {
+        date.and_hms_opt(0, 0, 0)
+    }
+    .ok_or_else(|| format!("无效的时间: {}", value))?;
+
+    Local
+        .from_local_datetime(&time)
+        .earliest()
+        .ok_or_else(|| format!("无效的本地时间: {}", value))
+}
+
+/// 按条件查询默认审计日志（按时间顺序）
+pub fn query(query: &AuditQuery) -> Result<Vec<AuditEntry>, String> {
+    let path = log_path().ok_or("无法获取配置目录")?;
+    Ok(read_entries(&path)?
+        .into_iter()
+        .filter(|entry| query.matches(entry))
+        .collect())
+}
+
+/// 导出格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "json" => Ok(ExportFormat::Json),
+            _ => Err(format!("不支持的导出格式: {}（支持 csv 或 json）", s)),
+        }
+    }
+}
+
+/// 将记录导出为 CSV 或 JSON 文本
+pub fn export(entries: &[AuditEntry], format: ExportFormat) -> Result<String, String> {
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(entries).map_err(|e| format!("序列化审计记录失败: {}", e))
+        }
+        ExportFormat::Csv => {
+            let mut csv = String::from("timestamp,action,from_account,to_account,outcome,detail\n");
+            for entry in entries {
+                let fields = [
+                    entry.timestamp.as_str(),
+                    entry.action.as_str(),
+                    entry.from_account.as_deref().unwrap_or(""),
+                    entry.to_account.as_deref().unwrap_or(""),
+                    entry.outcome.as_str(),
+                    entry.detail.as_deref().unwrap_or(""),
+                ];
+                let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                csv.push_str(&row.join(","));
+                csv.push('\n');
+            }
+            Ok(csv)
+        }
+    }
+}
+
+/// CSV 字段转义（包含逗号、引号或换行时加引号）
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn entry_at(timestamp: &str, action: AuditAction, to: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: timestamp.to_string(),
+            ..AuditEntry::new(action).to_account(Some(to))
+        }
+    }
+
+    #[test]
+    fn appends_and_skips_corrupt_lines() {
+        let path = test_support::temp_dir("audit-log").join("audit.jsonl");
+        let ok = AuditEntry::new(AuditAction::Switch)
+            .from_account(Some("a@example.com"))
+            .to_account(Some("b@example.com"));
+        let failed = AuditEntry::new(AuditAction::Restore).result(&Err::<(), _>("数据库被占用"));
+
+        append(&path, &ok).unwrap();
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| file.write_all(b"{not json\n"))
+            .unwrap();
+        append(&path, &failed).unwrap();
+
+        let entries = read_entries(&path).unwrap();
+        assert_eq!(entries, vec![ok, failed]);
+        assert_eq!(entries[1].outcome, AuditOutcome::Failure);
+        assert_eq!(entries[1].detail.as_deref(), Some("数据库被占用"));
+    }
+
+    #[test]
+    fn query_filters_by_account_and_inclusive_dates() {
+        let entries = [
+            entry_at(
+                "2026-01-01T10:00:00+00:00",
+                AuditAction::Switch,
+                "a@example.com",
+            ),
+            entry_at(
+                "2026-01-02T10:00:00+00:00",
+                AuditAction::Switch,
+                "b@example.com",
+            ),
+            entry_at(
+                "2026-01-03T10:00:00+00:00",
+                AuditAction::Restore,
+                "a@example.com",
+            ),
+        ];
+        let select = |query: AuditQuery| -> Vec<&str> {
+            entries
+                .iter()
+                .filter(|entry| query.matches(entry))
+                .map(|entry| entry.timestamp.as_str())
+                .collect()
+        };
+
+        let by_account = AuditQuery::parse(Some("a@example.com".to_string()), None, None).unwrap();
+        assert_eq!(
+            select(by_account),
+            vec!["2026-01-01T10:00:00+00:00", "2026-01-03T10:00:00+00:00"]
+        );
+
+        let range = AuditQuery::parse(
+            None,
+            Some("2026-01-02T00:00:00+00:00"),
+            Some("2026-01-02T23:59:59+00:00"),
+        )
+        .unwrap();
+        assert_eq!(select(range), vec!["2026-01-02T10:00:00+00:00"]);
+
+        assert!(AuditQuery::parse(None, Some("yesterday"), None).is_err());
+    }
+
+    #[test]
+    fn until_date_includes_the_whole_day() {
+        let until = parse_time_bound("2026-01-02", true).unwrap();
+        assert_eq!(
+            until.date_naive(),
+            NaiveDate::from_ymd_opt(2026, 1, 2).unwrap()
+        );
+        assert_eq!(until.time().to_string(), "23:59:59.999");
+        assert_eq!(
+            parse_time_bound("2026-01-02", false)
+                .unwrap()
+                .time()
+                .to_string(),
+            "00:00:00"
+        );
+    }
+
+    #[test]
+    fn csv_export_escapes_fields() {
+        let entry = entry_at(
+            "2026-01-01T10:00:00+00:00",
+            AuditAction::Switch,
+            "a@example.com",
+        )
+        .result(&Err::<(), _>("失败, \"原因\"\n第二行"));
+
+        let csv = export(&[entry], ExportFormat::Csv).unwrap();
+        assert_eq!(
+            csv,
+            "timestamp,action,from_account,to_account,outcome,detail\n\
+             2026-01-01T10:00:00+00:00,switch,,a@example.com,failure,\"失败, \"\"原因\"\"\n第二行\"\n"
+        );
+        assert!("xml".parse::<ExportFormat>().is_err());
+    }
+}
@@ -0,0 +1,547 @@
+// 账户备份历史模块
+// 负责管理每个账户的多版本备份（antigravity-accounts/<email>/<revision>.json）
+// 同时兼容旧版单文件布局（antigravity-accounts/<email>.json）
+// 定时备份创建的版本 ID 带有 "-scheduled" 后缀，保留策略只清理这些版本
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::antigravity_backup::BackupTrigger;
+
+/// 旧版单文件备份对应的版本 ID
+pub const LEGACY_REVISION_ID: &str = "legacy";
+
+/// 版本 ID 的时间格式（按字典序排序即为时间顺序）
+const REVISION_ID_FORMAT: &str = "%Y%m%d-%H%M%S-%3f";
+
+/// 定时备份版本 ID 的后缀
+const SCHEDULED_SUFFIX: &str = "-scheduled";
+
+/// 单个备份版本信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRevision {
+    /// 版本 ID（时间戳或 "legacy"）
+    pub id: String,
+    /// 所属账户
+    pub account: String,
+    /// 文件修改时间（RFC3339）
+    pub modified_at: String,
+    /// 文件大小（字节）
+    pub size_bytes: u64,
+    /// 是否为旧版单文件备份
+    pub is_legacy: bool,
+    /// 是否由定时备份创建（只有这些版本会被保留策略清理）
+    #[serde(default)]
+    pub scheduled: bool,
+    /// 备份文件路径
+    #[serde(skip)]
+    pub path: PathBuf,
+    #[serde(skip)]
+    modified: Option<SystemTime>,
+}
+
+/// 生成新的版本 ID
+pub fn new_revision_id() -> String {
+    chrono::Local::now().format(REVISION_ID_FORMAT).to_string()
+}
+
+/// 按备份触发方式生成新的版本 ID（定时备份带有后缀）
+pub fn new_revision_id_for(trigger: BackupTrigger) -> String {
+    match trigger {
+        BackupTrigger::Scheduled => format!("{}{}", new_revision_id(), SCHEDULED_SUFFIX),
+        BackupTrigger::Manual | BackupTrigger::Auto => new_revision_id(),
+    }
+}
+
+/// 检查账户名或版本 ID 能否安全地作为路径的一部分
+///
+/// 账户名和版本 ID 来自 Tauri 命令和命令行参数，拒绝空值、`.`、`..` 以及包含路径分隔符或 NUL 的值，
+/// 避免拼接出备份目录以外的路径
+fn validate_path_component(kind: &str, value: &str) -> Result<(), String> {
+    if value.is_empty() || value == "." || value == ".." || value.contains(['/', '\\', '\0']) {
+        return Err(format!("无效的{}: {:?}", kind, value));
+    }
+    Ok(())
+}
+
+/// 账户的版本目录
+pub fn account_dir(accounts_dir: &Path, account: &str) -> Result<PathBuf, String> {
+    validate_path_component("账户名", account)?;
+    Ok(accounts_dir.join(account))
+}
+
+/// 账户的旧版单文件路径
+pub fn legacy_file(accounts_dir: &Path, account: &str) -> Result<PathBuf, String> {
+    validate_path_component("账户名", account)?;
+    Ok(accounts_dir.join(format!("{}.json", account)))
+}
+
+/// 指定版本的文件路径（不检查是否存在）
+pub fn revision_file(
+    accounts_dir: &Path,
+    account: &str,
+    revision: &str,
+) -> Result<PathBuf, String> {
+    if revision == LEGACY_REVISION_ID {
+        legacy_file(accounts_dir, account)
+    } else {
+        validate_path_component("版本 ID", revision)?;
+        Ok(account_dir(accounts_dir, account)?.join(format!("{}.json", revision)))
+    }
+}
+
+fn build_revision(
+    account: &str,
+    id: String,
+    path: PathBuf,
+    is_legacy: bool,
+) -> Option<BackupRevision> {
+    let metadata = fs::metadata(&path).ok()?;
+    let modified = metadata.modified().ok();
+    let modified_at = modified
+        .map(|t| chrono::DateTime::<chrono::Local>::from(t).to_rfc3339())
+        .unwrap_or_default();
+
+    Some(BackupRevision {
+        scheduled: id.ends_with(SCHEDULED_SUFFIX),
+        id,
+        account: account.to_string(),
+        modified_at,
+        size_bytes: metadata.len(),
+        is_legacy,
+        path,
+        modified,
+    })
+}
+
+/// 列出账户的所有备份版本（最新的在前）
+///
+/// 旧版单文件备份排在所有时间戳版本之后
+pub fn list_revisions(accounts_dir: &Path, account: &str) -> Result<Vec<BackupRevision>, String> {
+    let mut revisions = Vec::new();
+
+    let dir = account_dir(accounts_dir, account)?;
+    if dir.is_dir() {
+        for entry in fs::read_dir(&dir).map_err(|e| format!("读取版本目录失败: {}", e))? {
+            let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
+            let path = entry.path();
+
+            if path.extension().is_some_and(|ext| ext == "json") {
+                if let Some(stem) = path.file_stem() {
+                    let id = stem.to_string_lossy().to_string();
+                    if let Some(revision) = build_revision(account, id, path, false) {
+                        revisions.push(revision);
+                    }
+                }
+            }
+        }
+    }
+
+    revisions.sort_by(|a, b| b.id.cmp(&a.id));
+
+    let legacy = legacy_file(accounts_dir, account)?;
+    if legacy.is_file() {
+        if let Some(revision) =
+            build_revision(account, LEGACY_REVISION_ID.to_string(), legacy, true)
+        {
+            revisions.push(revision);
+        }
+    }
+
+    Ok(revisions)
+}
+
+/// 获取账户最新的备份版本
+pub fn latest_revision(accounts_dir: &Path, account: &str) -> Option<BackupRevision> {
+    list_revisions(accounts_dir, account)
+        .ok()
+        .and_then(|revisions| revisions.into_iter().next())
+}
+
+/// 获取账户最新备份文件路径（兼容旧版单文件）
+pub fn latest_backup_file(accounts_dir: &Path, account: &str) -> Option<PathBuf> {
+    latest_revision(accounts_dir, account).map(|revision| revision.path)
+}
+
+/// 列出所有有备份的账户名称（同时识别版本目录和旧版单文件）
+pub fn list_accounts(accounts_dir: &Path) -> Result<Vec<String>, String> {
+    let mut accounts = Vec::new();
+
+    if !accounts_dir.exists() {
+        return Ok(accounts);
+    }
+
+    for entry in fs::read_dir(accounts_dir).map_err(|e| format!("读取用户目录失败: {}", e))?
+    {
+        let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
+        let path = entry.path();
+
+        let name = if path.is_dir() {
+            let has_revision = fs::read_dir(&path)
+                .map(|mut entries| {
+                    entries.any(|e| {
+                        e.map(|e| e.path().extension().is_some_and(|ext| ext == "json"))
+                            .unwrap_or(false)
+                    })
+                })
+                .unwrap_or(false);
+            if !has_revision {
+                continue;
+            }
+            path.file_name().map(|n| n.to_string_lossy().to_string())
+        } else if path.extension().is_some_and(|ext| ext == "json") {
+            path.file_stem().map(|n| n.to_string_lossy().to_string())
+        } else {
+            None
+        };
+
+        if let Some(name) = name {
+            if !accounts.contains(&name) {
+                accounts.push(name);
+            }
+        }
+    }
+
+    Ok(accounts)
+}
+
+/// 获取账户最近一次备份的时间
+pub fn last_modified(accounts_dir: &Path, account: &str) -> Option<SystemTime> {
+    list_revisions(accounts_dir, account)
+        .ok()?
+        .into_iter()
+        .filter_map(|revision| revision.modified)
+        .max()
+}
+
+/// 将旧版单文件备份迁移为版本目录中的一个版本
+///
+/// 使用文件修改时间作为版本 ID，迁移后旧文件被移除
+pub fn migrate_legacy_backup(accounts_dir: &Path, account: &str) -> Result<Option<String>, String> {
+    let legacy = legacy_file(accounts_dir, account)?;
+    if !legacy.is_file() {
+        return Ok(None);
+    }
+
+    let modified = fs::metadata(&legacy)
+        .and_then(|m| m.modified())
+        .unwrap_or_else(|_| SystemTime::now());
+    let revision_id = chrono::DateTime::<chrono::Local>::from(modified)
+        .format(REVISION_ID_FORMAT)
+        .to_string();
+
+    let dir = account_dir(accounts_dir, account)?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建版本目录失败: {}", e))?;
+
+    let target = dir.join(format!("{}.json", revision_id));
+    if target.exists() {
+        // 同名版本已存在，直接丢弃旧文件会丢数据，保持原样
+        return Ok(None);
+    }
+
+    fs::rename(&legacy, &target).map_err(|e| format!("迁移旧版备份失败: {}", e))?;
+    tracing::info!("  📦 旧版备份已迁移为版本: {}", revision_id);
+    Ok(Some(revision_id))
+}
+
+/// 删除账户的所有备份（版本目录和旧版单文件）
+///
+/// # 返回
+/// - `Ok(true)`: 删除了至少一个文件
+/// - `Ok(false)`: 账户没有任何备份
+pub fn delete_account(accounts_dir: &Path, account: &str) -> Result<bool, String> {
+    let mut deleted = false;
+
+    let dir = account_dir(accounts_dir, account)?;
+    if dir.is_dir() {
+        fs::remove_dir_all(&dir).map_err(|e| format!("删除版本目录失败: {}", e))?;
+        deleted = true;
+    }
+
+    let legacy = legacy_file(accounts_dir, account)?;
+    if legacy.is_file() {
+        fs::remove_file(&legacy).map_err(|e| format!("删除用户文件失败: {}", e))?;
+        deleted = true;
+    }
+
+    Ok(deleted)
+}
+
+/// 清理旧版本，只保留最新的 `keep` 个
+///
+/// # 返回
+/// - `Ok(removed)`: 被删除的版本 ID 列表
+pub fn prune_revisions(
+    accounts_dir: &Path,
+    account: &str,
+    keep: usize,
+) -> Result<Vec<String>, String> {
+    let revisions = list_revisions(accounts_dir, account)?;
+    let mut removed = Vec::new();
+
+    for revision in revisions.into_iter().skip(keep) {
+        fs::remove_file(&revision.path)
+            .map_err(|e| format!("删除版本 {} 失败: {}", revision.id, e))?;
+        removed.push(revision.id);
+    }
+
+    if !removed.is_empty() {
+        tracing::info!("  🧹 账户 {} 清理了 {} 个旧版本", account, removed.len());
+    }
+
+    Ok(removed)
+}
+
+/// 备份保留策略：每小时、每天、每周各保留最新的一个版本，分别保留最近的若干个时间段
+///
+/// 最新的版本和旧版单文件备份总是保留
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionPolicy {
+    pub hourly: usize,
+    pub daily: usize,
+    pub weekly: usize,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            hourly: 24,
+            daily: 7,
+            weekly: 4,
+        }
+    }
+}
+
+impl RetentionPolicy {
+    /// 按策略挑选要保留的版本 ID（`revisions` 需按最新在前排序）
+    fn retained(&self, revisions: &[BackupRevision]) -> HashSet<String> {
+        let times: Vec<(&str, chrono::NaiveDateTime)> = revisions
+            .iter()
+            .filter(|revision| !revision.is_legacy)
+            .filter_map(|revision| revision_time(revision).map(|time| (revision.id.as_str(), time)))
+            .collect();
+
+        self.retained_ids(&times)
+    }
+
+    /// 按策略挑选要保留的 ID（`items` 为 ID 和时间，需按最新在前排序）
+    pub fn retained_ids(&self, items: &[(&str, chrono::NaiveDateTime)]) -> HashSet<String> {
+        let mut keep = HashSet::new();
+        if let Some((newest, _)) = items.first() {
+            keep.insert(newest.to_string());
+        }
+
+        for (count, bucket_format) in [
+            (self.hourly, "%Y%m%d%H"),
+            (self.daily, "%Y%m%d"),
+            (self.weekly, "%G%V"),
+        ] {
+            let mut buckets = HashSet::new();
+            for (id, time) in items {
+                if buckets.len() >= count {
+                    break;
+                }
+                // 每个时间段只保留最新的一个版本
+                if buckets.insert(time.format(bucket_format).to_string()) {
+                    keep.insert(id.to_string());
+                }
+            }
+        }
+
+        keep
+    }
+}
+
+/// 解析版本 ID 中的时间戳（旧版备份等非时间戳 ID 返回 None）
+pub fn parse_revision_id(id: &str) -> Option<chrono::NaiveDateTime> {
+    let timestamp = id.strip_suffix(SCHEDULED_SUFFIX).unwrap_or(id);
+    chrono::NaiveDateTime::parse_from_str(timestamp, REVISION_ID_FORMAT).ok()
+}
+
+/// 版本的备份时间（优先使用版本 ID 中的时间戳）
+fn revision_time(revision: &BackupRevision) -> Option<chrono::NaiveDateTime> {
+    parse_revision_id(&revision.id).or_else(|| {
+        revision
+            .modified
+            .map(|t| chrono::DateTime::<chrono::Local>::from(t).naive_local())
+    })
+}
+
+/// 按保留策略清理账户由定时备份创建的旧版本
+///
+/// 手动备份、自动备份、导入和旧版单文件备份都不受影响
+///
+/// # 返回
+/// - `Ok(removed)`: 被删除的版本 ID 列表
+pub fn apply_retention(
+    accounts_dir: &Path,
+    account: &str,
+    policy: &RetentionPolicy,
+) -> Result<Vec<String>, String> {
+    let revisions: Vec<BackupRevision> = list_revisions(accounts_dir, account)?
+        .into_iter()
+        .filter(|revision| revision.scheduled)
+        .collect();
+    let keep = policy.retained(&revisions);
+    let mut removed = Vec::new();
+
+    for revision in revisions {
+        if keep.contains(&revision.id) {
+            continue;
+        }
+        fs::remove_file(&revision.path)
+            .map_err(|e| format!("删除版本 {} 失败: {}", revision.id, e))?;
+        removed.push(revision.id);
+    }
+
+    if !removed.is_empty() {
+        tracing::info!(
+            "  🧹 账户 {} 按保留策略清理了 {} 个旧版本",
+            account,
+            removed.len()
+        );
+    }
+
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use chrono::NaiveDateTime;
+
+    fn time(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S").unwrap()
+    }
+
+    fn retained(policy: RetentionPolicy, items: &[(&str, &str)]) -> Vec<String> {
+        let items: Vec<(&str, NaiveDateTime)> =
+            items.iter().map(|(id, t)| (*id, time(t))).collect();
+        let mut keep: Vec<String> = policy.retained_ids(&items).into_iter().collect();
+        keep.sort();
+        keep
+    }
+
+    fn policy(hourly: usize, daily: usize, weekly: usize) -> RetentionPolicy {
+        RetentionPolicy {
+            hourly,
+            daily,
+            weekly,
+        }
+    }
+
+    #[test]
+    fn retained_ids_keeps_newest_per_hour() {
+        let keep = retained(
+            policy(2, 0, 0),
+            &[
+                ("a", "2025-01-01 10:59:59"),
+                ("b", "2025-01-01 10:00:00"),
+                ("c", "2025-01-01 09:59:59"),
+                ("d", "2025-01-01 09:00:00"),
+                ("e", "2025-01-01 08:30:00"),
+            ],
+        );
+        assert_eq!(keep, ["a", "c"]);
+    }
+
+    #[test]
+    fn retained_ids_keeps_newest_per_day() {
+        let keep = retained(
+            policy(0, 2, 0),
+            &[
+                ("a", "2025-01-02 00:00:01"),
+                ("b", "2025-01-01 23:59:59"),
+                ("c", "2025-01-01 00:00:00"),
+                ("d", "2024-12-31 23:00:00"),
+            ],
+        );
+        assert_eq!(keep, ["a", "b"]);
+    }
+
+    #[test]
+    fn retained_ids_uses_iso_weeks_across_years() {
+        // 2024-12-30（周一）属于 2025 年第 1 周，2024-12-29（周日）属于 2024 年第 52 周
+        let keep = retained(
+            policy(0, 0, 2),
+            &[
+                ("a", "2025-01-01 12:00:00"),
+                ("b", "2024-12-30 00:00:00"),
+                ("c", "2024-12-29 23:59:59"),
+                ("d", "2024-12-23 00:00:00"),
+                ("e", "2024-12-16 00:00:00"),
+            ],
+        );
+        assert_eq!(keep, ["a", "c"]);
+    }
+
+    #[test]
+    fn retained_ids_always_keeps_newest() {
+        let keep = retained(
+            policy(0, 0, 0),
+            &[("a", "2025-01-01 00:00:00"), ("b", "2024-01-01 00:00:00")],
+        );
+        assert_eq!(keep, ["a"]);
+        assert!(retained(policy(0, 0, 0), &[]).is_empty());
+    }
+
+    #[test]
+    fn parses_scheduled_revision_ids() {
+        assert_eq!(
+            parse_revision_id("20250101-120000-000-scheduled"),
+            parse_revision_id("20250101-120000-000")
+        );
+        assert!(parse_revision_id("20250101-120000-000").is_some());
+        assert!(parse_revision_id(LEGACY_REVISION_ID).is_none());
+    }
+
+    #[test]
+    fn rejects_unsafe_path_components() {
+        let root = Path::new("accounts");
+        for account in ["", ".", "..", "a/b", "a\\b", "a\0b"] {
+            assert!(account_dir(root, account).is_err(), "{:?}", account);
+        }
+        assert!(revision_file(root, "user@example.com", "../x").is_err());
+        assert!(account_dir(root, "user@example.com").is_ok());
+    }
+
+    #[test]
+    fn apply_retention_only_prunes_scheduled_revisions() {
+        let accounts_dir = test_support::agent_home().join("retention-accounts");
+        let account = "user@example.com";
+        let dir = accounts_dir.join(account);
+        fs::create_dir_all(&dir).unwrap();
+        for id in [
+            "20250101-100000-000",
+            "20250101-110000-000-scheduled",
+            "20250101-120000-000-scheduled",
+            "20250101-130000-000",
+        ] {
+            fs::write(dir.join(format!("{}.json", id)), "{}").unwrap();
+        }
+
+        let mut removed = apply_retention(&accounts_dir, account, &policy(0, 0, 0)).unwrap();
+        removed.sort();
+        assert_eq!(removed, ["20250101-110000-000-scheduled"]);
+
+        let mut remaining: Vec<String> = list_revisions(&accounts_dir, account)
+            .unwrap()
+            .into_iter()
+            .map(|revision| revision.id)
+            .collect();
+        remaining.sort();
+        assert_eq!(
+            remaining,
+            [
+                "20250101-100000-000",
+                "20250101-120000-000-scheduled",
+                "20250101-130000-000"
+            ]
+        );
+    }
+}
@@ -0,0 +1,93 @@
+// 定时备份计划模块
+// 定义定时备份的设置（每隔若干小时或每天固定时间）并计算下一次运行时间
+// 由桌面应用在后台按计划备份当前登录的账户，备份后按保留策略清理定时备份创建的旧版本
+
+use chrono::{DateTime, Duration, Local, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::backup_history::RetentionPolicy;
+
+/// 时间格式（每天备份的时间）
+const TIME_FORMAT: &str = "%H:%M";
+
+/// 备份间隔的上限（一年）
+const MAX_INTERVAL_HOURS: u32 = 24 * 365;
+
+/// 备份计划
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum BackupSchedule {
+    /// 每隔 `hours` 小时备份一次
+    Interval { hours: u32 },
+    /// 每天在 `time`（本地时间，HH:MM）备份一次
+    Daily { time: String },
+}
+
+impl Default for BackupSchedule {
+    fn default() -> Self {
+        BackupSchedule::Interval { hours: 1 }
+    }
+}
+
+impl BackupSchedule {
+    /// 检查计划是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            BackupSchedule::Interval { hours } if *hours == 0 => {
+                Err("备份间隔必须大于 0 小时".to_string())
+            }
+            BackupSchedule::Interval { hours } if *hours > MAX_INTERVAL_HOURS => {
+                Err(format!("备份间隔不能超过 {} 小时", MAX_INTERVAL_HOURS))
+            }
+            BackupSchedule::Interval { .. } => Ok(()),
+            BackupSchedule::Daily { time } => parse_time(time).map(|_| ()),
+        }
+    }
+
+    /// `after` 之后的下一次运行时间
+    pub fn next_run(&self, after: DateTime<Local>) -> Result<DateTime<Local>, String> {
+        match self {
+            BackupSchedule::Interval { hours } => {
+                self.validate()?;
+                Ok(after + Duration::hours(i64::from(*hours)))
+            }
+            BackupSchedule::Daily { time } => {
+                let time = parse_time(time)?;
+                let mut date = after.date_naive();
+                loop {
+                    // 夏令时切换导致当天不存在该时间时顺延到下一天
+                    if let Some(run) = Local.from_local_datetime(&date.and_time(time)).earliest() {
+                        if run > after {
+                            return Ok(run);
+                        }
+                    }
+                    date = date
+                        .succ_opt()
+                        .ok_or_else(|| "无法计算下一次备份时间".to_string())?;
+                }
+            }
+        }
+    }
+}
+
+fn parse_time(time: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(time.trim(), TIME_FORMAT)
+        .map_err(|_| format!("无效的备份时间: {}（格式为 HH:MM）", time))
+}
+
+/// 定时备份设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ScheduledBackupSettings {
+    /// 是否启用定时备份
+    pub enabled: bool,
+    pub schedule: BackupSchedule,
+    /// 每次定时备份后应用的保留策略
+    pub retention: RetentionPolicy,
+}
+
+impl ScheduledBackupSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        self.schedule.validate()
+    }
+}
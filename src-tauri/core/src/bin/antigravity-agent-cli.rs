@@ -0,0 +1,839 @@
+//! Antigravity Agent 命令行工具
+//! 无需图形界面即可完成账户的备份、切换、恢复、导入导出等操作
+//! 与桌面应用共用同一套备份、恢复、清理和启动模块
+
+use antigravity_agent_core::account_ops::{self, ExportBundle};
+use antigravity_agent_core::audit_log::{self, AuditQuery, ExportFormat};
+use antigravity_agent_core::backup_history::RetentionPolicy;
+use antigravity_agent_core::db_health::MarkerReport;
+use antigravity_agent_core::db_snapshot::{self, DbSnapshot};
+use antigravity_agent_core::extra_keys::{self, ExtraKey, KeyMatch};
+use antigravity_agent_core::path_utils::AppPaths;
+use antigravity_agent_core::process::{self, MatchStatus};
+use antigravity_agent_core::{
+    account_registry, account_vault, antigravity_path_config, crypto, platform_utils,
+};
+use antigravity_agent_core::{AccountStore, AntigravityProcess, DataCategory, StateDb};
+use clap::{Parser, Subcommand};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(
+    name = "antigravity-agent-cli",
+    version,
+    about = "Antigravity 账户管理命令行工具"
+)]
+struct Cli {
+    /// 以 JSON 格式输出结果
+    #[arg(long, global = true)]
+    json: bool,
+
+    /// 输出详细日志（写入标准错误）
+    #[arg(short, long, global = true)]
+    verbose: bool,
+
+    /// 账户保险库主密码（保险库启用时用于解锁）
+    #[arg(
+        long,
+        global = true,
+        env = "ANTIGRAVITY_VAULT_PASSWORD",
+        hide_env_values = true
+    )]
+    vault_password: Option<String>,
+
+    /// 关闭 Antigravity 时等待其正常退出的秒数，超时后强制终止
+    #[arg(long, global = true, value_name = "SECS")]
+    graceful_timeout: Option<u64>,
+
+    /// 强制终止 Antigravity 后等待其退出的秒数
+    #[arg(long, global = true, value_name = "SECS")]
+    kill_timeout: Option<u64>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// 列出所有已备份的账户
+    List,
+    /// 显示当前登录的账户
+    Current,
+    /// 备份当前登录的账户
+    Backup {
+        /// 只备份指定的数据类别（逗号分隔，默认全部）
+        #[arg(long, value_delimiter = ',', value_name = "CATEGORIES")]
+        only: Vec<DataCategory>,
+    },
+    /// 切换到指定账户（关闭 Antigravity -> 恢复 -> 重新启动）
+    Switch {
+        /// 账户邮箱
+        account: String,
+        /// 切换后不重新启动 Antigravity
+        #[arg(long)]
+        no_restart: bool,
+    },
+    /// 将账户备份恢复到数据库（不关闭或启动 Antigravity）
+    Restore {
+        /// 账户邮箱
+        account: String,
+        /// 备份版本 ID（默认最新版本）
+        #[arg(long)]
+        revision: Option<String>,
+        /// 只预览变化，不写入数据库
+        #[arg(long)]
+        dry_run: bool,
+        /// 只恢复指定的数据类别（逗号分隔，默认全部）：
+        /// auth, profile, settings, onboarding, agent_state, chat, notifications
+        #[arg(long, value_delimiter = ',', value_name = "CATEGORIES")]
+        only: Vec<DataCategory>,
+    },
+    /// 注销当前账户（关闭 Antigravity 并清除账户数据）
+    Logout {
+        /// 注销后重新启动 Antigravity
+        #[arg(long)]
+        restart: bool,
+        /// 只清除指定的数据类别（逗号分隔，默认 auth,profile,settings,onboarding）
+        #[arg(long, value_delimiter = ',', value_name = "CATEGORIES")]
+        only: Vec<DataCategory>,
+    },
+    /// 导出所有账户的最新备份
+    Export {
+        /// 导出文件路径
+        file: PathBuf,
+        /// 导出密码（不提供时导出明文 JSON）
+        #[arg(long, env = "ANTIGRAVITY_EXPORT_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// 从导出文件导入账户备份
+    Import {
+        /// 导出文件路径
+        file: PathBuf,
+        /// 导出密码（明文 JSON 文件无需提供）
+        #[arg(long, env = "ANTIGRAVITY_EXPORT_PASSWORD", hide_env_values = true)]
+        password: Option<String>,
+    },
+    /// 列出 Antigravity 相关进程及匹配原因
+    Processes,
+    /// 关闭 Antigravity 进程（先请求正常退出，超时后强制终止）
+    Kill,
+    /// 启动 Antigravity
+    Start,
+    /// 显示配置、备份和数据库路径
+    Paths,
+    /// 查看或导出账户操作审计日志
+    Audit {
+        /// 只显示涉及该账户的记录
+        #[arg(long)]
+        account: Option<String>,
+        /// 起始时间（RFC3339 或 YYYY-MM-DD）
+        #[arg(long)]
+        since: Option<String>,
+        /// 结束时间（RFC3339 或 YYYY-MM-DD，日期包含当天）
+        #[arg(long)]
+        until: Option<String>,
+        /// 导出格式（csv 或 json），需要同时指定 --output
+        #[arg(long, default_value = "json", requires = "output")]
+        format: ExportFormat,
+        /// 导出文件路径
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// 查看或修改自定义备份字段（内置字段之外需要备份和恢复的字段）
+    ExtraKeys {
+        /// 添加字段名或匹配模式
+        #[arg(long, value_name = "PATTERN", conflicts_with = "remove")]
+        add: Option<String>,
+        /// 匹配方式（exact、glob 或 like）
+        #[arg(long, default_value = "exact", requires = "add")]
+        kind: KeyMatch,
+        /// 恢复时在 Marker 中登记该字段
+        #[arg(long, requires = "add")]
+        marker: bool,
+        /// 所属数据类别
+        #[arg(long, default_value = "auth", requires = "add")]
+        category: DataCategory,
+        /// 删除指定的字段名或匹配模式
+        #[arg(long, value_name = "PATTERN")]
+        remove: Option<String>,
+    },
+    /// 检查和维护 state.vscdb（默认执行完整性检查）
+    Db {
+        #[command(subcommand)]
+        action: Option<DbCommand>,
+    },
+    /// 管理完整数据库快照（完整复制 state.vscdb 和 state.vscdb.backup，默认列出快照）
+    Snapshot {
+        #[command(subcommand)]
+        action: Option<SnapshotCommand>,
+    },
+}
+
+#[derive(Subcommand)]
+enum DbCommand {
+    /// 完整性检查，显示文件大小和空闲页
+    Check,
+    /// 执行 VACUUM 回收空闲页（需要先关闭 Antigravity）
+    Vacuum,
+    /// 检查 __$__targetStorageMarker 与实际数据是否一致
+    Marker {
+        /// 修复不一致的 Marker（需要先关闭 Antigravity）
+        #[arg(long)]
+        repair: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotCommand {
+    /// 列出所有快照
+    List,
+    /// 保存完整数据库快照（Antigravity 运行时也可以执行）
+    Create {
+        /// 快照备注
+        #[arg(long)]
+        label: Option<String>,
+    },
+    /// 用快照覆盖数据库（关闭 Antigravity -> 保存当前状态 -> 恢复 -> 重新启动）
+    Restore {
+        /// 快照 ID
+        id: String,
+        /// 恢复后不重新启动 Antigravity
+        #[arg(long)]
+        no_restart: bool,
+    },
+    /// 删除指定快照
+    Delete {
+        /// 快照 ID
+        id: String,
+    },
+    /// 清理旧快照（默认按与账户备份相同的保留策略）
+    Prune {
+        /// 只保留最新的 N 个快照
+        #[arg(long, value_name = "N", conflicts_with_all = ["hourly", "daily", "weekly"])]
+        keep: Option<usize>,
+        /// 保留最近多少个小时各一个快照
+        #[arg(long)]
+        hourly: Option<usize>,
+        /// 保留最近多少天各一个快照
+        #[arg(long)]
+        daily: Option<usize>,
+        /// 保留最近多少周各一个快照
+        #[arg(long)]
+        weekly: Option<usize>,
+    },
+}
+
+/// 单条命令的输出：人类可读文本和 JSON 数据
+struct Output {
+    text: String,
+    data: Value,
+}
+
+impl Output {
+    fn message(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            data: json!({ "message": text }),
+            text,
+        }
+    }
+
+    fn with_data(text: impl Into<String>, data: impl Serialize) -> Self {
+        Self {
+            text: text.into(),
+            data: serde_json::to_value(data).unwrap_or(Value::Null),
+        }
+    }
+}
+
+fn init_logging(verbose: bool) {
+    let level = if verbose { "info" } else { "warn" };
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new(level));
+
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_target(false)
+        .init();
+}
+
+fn unlock_vault(password: Option<&str>) -> Result<(), String> {
+    if !account_vault::is_enabled() {
+        return Ok(());
+    }
+    match password {
+        Some(password) => account_vault::unlock(password),
+        // 未提供密码时只允许不读取备份内容的操作，读取时会返回“已锁定”错误
+        None => Ok(()),
+    }
+}
+
+fn apply_shutdown_options(cli: &Cli) {
+    let mut options = process::shutdown_options();
+    if let Some(secs) = cli.graceful_timeout {
+        options.graceful_timeout = Duration::from_secs(secs);
+    }
+    if let Some(secs) = cli.kill_timeout {
+        options.kill_timeout = Duration::from_secs(secs);
+    }
+    process::set_shutdown_options(options);
+}
+
+fn cmd_list() -> Result<Output, String> {
+    let store = AccountStore::open_default()?;
+    let current = account_ops::current_account_email().unwrap_or(None);
+    let registry = account_registry::refresh()
+        .map_err(|e| tracing::warn!("⚠️ 更新账户注册表失败: {}", e))
+        .ok();
+
+    let mut rows = Vec::new();
+    for account in store.accounts()? {
+        let revisions = store.revisions(&account)?;
+        let record = registry.as_ref().and_then(|r| r.get(&account));
+        let metadata = store.metadata(&account);
+        rows.push(json!({
+            "account": account,
+            "name": record.map(|r| r.name.clone()),
+            "label": metadata.label,
+            "tags": metadata.tags,
+            "pinned": metadata.pinned,
+            "current": current.as_deref() == Some(account.as_str()),
+            "revisions": revisions.len(),
+            "last_backup": revisions.first().map(|r| r.modified_at.clone()),
+            "last_switched": record.and_then(|r| r.last_switched.clone()),
+        }));
+    }
+
+    let text = if rows.is_empty() {
+        "没有已备份的账户".to_string()
+    } else {
+        rows.iter()
+            .map(|row| {
+                format!(
+                    "{} {}  ({} 个版本, 最近备份: {}, 最近切换: {})",
+                    if row["current"].as_bool().unwrap_or(false) {
+                        "*"
+                    } else {
+                        " "
+                    },
+                    row["account"].as_str().unwrap_or_default(),
+                    row["revisions"],
+                    row["last_backup"].as_str().unwrap_or("-"),
+                    row["last_switched"].as_str().unwrap_or("-"),
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    Ok(Output::with_data(text, rows))
+}
+
+fn cmd_current() -> Result<Output, String> {
+    let info = account_ops::current_account_info()?;
+    let email = info.get("email").and_then(|e| e.as_str()).unwrap_or("-");
+    let db_path = info.get("db_path").and_then(|p| p.as_str()).unwrap_or("-");
+
+    Ok(Output::with_data(
+        format!("当前账户: {}\n数据库: {}", email, db_path),
+        json!({ "email": email, "db_path": db_path }),
+    ))
+}
+
+/// 未指定 `--only` 时使用默认的数据类别
+fn categories_or<'a>(only: &'a [DataCategory], default: &'a [DataCategory]) -> &'a [DataCategory] {
+    if only.is_empty() {
+        default
+    } else {
+        only
+    }
+}
+
+fn cmd_backup(categories: &[DataCategory]) -> Result<Output, String> {
+    let (backup_name, is_overwrite) = account_ops::backup_current_account(categories)?;
+    let action = if is_overwrite { "更新" } else { "备份" };
+    Ok(Output::with_data(
+        format!("Antigravity 账户 '{}'{}成功", backup_name, action),
+        json!({ "account": backup_name, "is_overwrite": is_overwrite }),
+    ))
+}
+
+async fn cmd_restore(
+    account: &str,
+    revision: Option<&str>,
+    dry_run: bool,
+    categories: &[DataCategory],
+) -> Result<Output, String> {
+    if dry_run {
+        let backup_file = account_ops::backup_file_for(account, revision)?;
+        let preview = StateDb::locate()?.preview_restore(&backup_file, categories)?;
+
+        let mut lines = vec![format!("备份文件: {}", preview.backup_file)];
+        for db in &preview.databases {
+            lines.push(format!("[{}]", db.database));
+            for key in &db.keys {
+                lines.push(format!("  {:?} {}", key.action, key.key));
+            }
+        }
+        return Ok(Output::with_data(lines.join("\n"), preview));
+    }
+
+    let message = account_ops::restore_account(account, revision, categories).await?;
+    Ok(Output::message(message))
+}
+
+fn cmd_export(file: &PathBuf, password: Option<&str>) -> Result<Output, String> {
+    let bundle = ExportBundle::new(AccountStore::open_default()?.export()?);
+    if bundle.backups.is_empty() {
+        return Err("没有找到任何用户信息，无法导出配置文件".to_string());
+    }
+
+    let json_data =
+        serde_json::to_string_pretty(&bundle).map_err(|e| format!("序列化导出数据失败: {}", e))?;
+    let (content, encrypted) = match password {
+        Some(password) => (crypto::encrypt_to_base64(&json_data, password)?, true),
+        None => (json_data, false),
+    };
+
+    fs::write(file, content).map_err(|e| format!("写入文件失败: {}", e))?;
+
+    Ok(Output::with_data(
+        format!(
+            "已导出 {} 个账户到 {}{}",
+            bundle.backup_count,
+            file.display(),
+            if encrypted { "" } else { "（未加密）" }
+        ),
+        json!({
+            "file": file,
+            "count": bundle.backup_count,
+            "encrypted": encrypted,
+        }),
+    ))
+}
+
+fn cmd_import(file: &PathBuf, password: Option<&str>) -> Result<Output, String> {
+    let content = fs::read_to_string(file).map_err(|e| format!("读取文件失败: {}", e))?;
+
+    // 明文 JSON 以 '{' 开头，否则按加密格式处理
+    let json_data = if content.trim_start().starts_with('{') {
+        content
+    } else {
+        let password = password.ok_or("导入加密文件需要提供 --password")?;
+        crypto::decrypt_from_base64(&content, password)?.0
+    };
+
+    let bundle: ExportBundle =
+        serde_json::from_str(&json_data).map_err(|e| format!("配置文件格式无效: {}", e))?;
+
+    let result = AccountStore::open_default()?.import(bundle.backups)?;
+
+    let mut text = format!("已导入 {} 个账户", result.restored_count);
+    for failed in &result.failed {
+        text.push_str(&format!("\n  ⚠️ {}: {}", failed.filename, failed.error));
+    }
+    Ok(Output::with_data(text, result))
+}
+
+fn cmd_paths() -> Result<Output, String> {
+    let home = AppPaths::agent_home();
+    let data = json!({
+        "config_dir": home.as_ref().map(|home| &home.dir),
+        "config_source": home.as_ref().map(|home| home.source),
+        "backup_dir": AppPaths::backup_dir(),
+        "state_db": StateDb::locate().ok().map(|db| db.path().to_path_buf()),
+        "detected_state_dbs": platform_utils::get_all_antigravity_db_paths(),
+        "custom_data_path": antigravity_path_config::get_custom_data_path().unwrap_or(None),
+        "custom_executable_path": antigravity_path_config::get_custom_executable_path().unwrap_or(None),
+        "executable": AntigravityProcess.detect_executable(),
+    });
+
+    let text = data
+        .as_object()
+        .map(|fields| {
+            fields
+                .iter()
+                .map(|(name, value)| {
+                    let value = match value {
+                        Value::Null => "-".to_string(),
+                        Value::String(s) => s.clone(),
+                        Value::Array(items) if items.is_empty() => "-".to_string(),
+                        Value::Array(items) => items
+                            .iter()
+                            .filter_map(|item| item.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", "),
+                        other => other.to_string(),
+                    };
+                    format!("{:<24}{}", name, value)
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        })
+        .unwrap_or_default();
+
+    Ok(Output::with_data(text, data))
+}
+
+fn cmd_extra_keys(add: Option<ExtraKey>, remove: Option<&str>) -> Result<Output, String> {
+    let mut keys = extra_keys::load()?;
+
+    if let Some(extra) = add {
+        keys.push(extra);
+        extra_keys::save(&keys)?;
+        keys = extra_keys::load()?;
+    } else if let Some(pattern) = remove {
+        let count = keys.len();
+        keys.retain(|extra| extra.pattern != pattern);
+        if keys.len() == count {
+            return Err(format!("未找到自定义字段: {}", pattern));
+        }
+        extra_keys::save(&keys)?;
+    }
+
+    let text = if keys.is_empty() {
+        "没有自定义备份字段".to_string()
+    } else {
+        keys.iter()
+            .map(|extra| {
+                format!(
+                    "{:<6} {:<14} {}{}",
+                    extra.kind.as_str(),
+                    extra.category.as_str(),
+                    extra.pattern,
+                    if extra.marker { "  (marker)" } else { "" }
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    Ok(Output::with_data(text, keys))
+}
+
+fn marker_lines(report: &MarkerReport) -> Vec<String> {
+    let mut lines = vec![format!(
+        "[{}] Marker: {}",
+        report.database,
+        if !report.marker_present {
+            "不存在".to_string()
+        } else if !report.marker_valid {
+            "不是有效的 JSON".to_string()
+        } else {
+            format!("{} 个字段", report.marker_keys)
+        }
+    )];
+    lines.extend(
+        report
+            .dangling
+            .iter()
+            .map(|key| format!("  没有对应行: {}", key)),
+    );
+    lines.extend(
+        report
+            .unregistered
+            .iter()
+            .map(|key| format!("  未登记: {}", key)),
+    );
+    if report.is_consistent() {
+        lines.push("  ✅ 一致".to_string());
+    }
+    lines
+}
+
+fn cmd_db(action: Option<&DbCommand>) -> Result<Output, String> {
+    let state_db = StateDb::locate()?;
+
+    match action.unwrap_or(&DbCommand::Check) {
+        DbCommand::Check => {
+            let reports = state_db.health()?;
+            let mut lines = Vec::new();
+            for health in &reports {
+                lines.push(format!(
+                    "[{}] {} 字节, {} 页 x {} 字节, 空闲 {} 页 ({} 字节)",
+                    health.database,
+                    health.size_bytes,
+                    health.page_count,
+                    health.page_size,
+                    health.freelist_count,
+                    health.free_bytes
+                ));
+                if health.is_ok() {
+                    lines.push("  ✅ 完整性检查通过".to_string());
+                } else {
+                    lines.extend(health.integrity.iter().map(|line| format!("  ⚠️ {}", line)));
+                }
+            }
+            Ok(Output::with_data(lines.join("\n"), reports))
+        }
+        DbCommand::Vacuum => {
+            let results = state_db.vacuum()?;
+            let text = results
+                .iter()
+                .map(|result| {
+                    format!(
+                        "[{}] {} 字节 -> {} 字节",
+                        result.database, result.before_bytes, result.after_bytes
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            Ok(Output::with_data(text, results))
+        }
+        DbCommand::Marker { repair } => {
+            let reports = if *repair {
+                state_db.repair_marker()?
+            } else {
+                state_db.check_marker()?
+            };
+            let mut text = reports
+                .iter()
+                .flat_map(marker_lines)
+                .collect::<Vec<_>>()
+                .join("\n");
+            if *repair
+                && reports
+                    .iter()
+                    .any(|report| report.marker_valid && !report.is_consistent())
+            {
+                text.push_str("\n已修复以上问题");
+            }
+            Ok(Output::with_data(text, reports))
+        }
+    }
+}
+
+fn snapshot_line(snapshot: &DbSnapshot) -> String {
+    format!(
+        "{}  {:<24} {:>10} 字节{}{}",
+        snapshot.id,
+        snapshot.account.as_deref().unwrap_or("-"),
+        snapshot.size_bytes,
+        if snapshot.has_backup_db {
+            ""
+        } else {
+            "  (无 state.vscdb.backup)"
+        },
+        snapshot
+            .label
+            .as_deref()
+            .map(|label| format!("  {}", label))
+            .unwrap_or_default()
+    )
+}
+
+async fn cmd_snapshot(action: Option<&SnapshotCommand>) -> Result<Output, String> {
+    match action.unwrap_or(&SnapshotCommand::List) {
+        SnapshotCommand::List => {
+            let snapshots = db_snapshot::list_snapshots()?;
+            let text = if snapshots.is_empty() {
+                "没有完整数据库快照".to_string()
+            } else {
+                snapshots
+                    .iter()
+                    .map(snapshot_line)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            };
+            Ok(Output::with_data(text, snapshots))
+        }
+        SnapshotCommand::Create { label } => {
+            let snapshot = account_ops::snapshot_databases(label.as_deref())?;
+            Ok(Output::with_data(
+                format!("已保存完整数据库快照: {}", snapshot_line(&snapshot)),
+                snapshot,
+            ))
+        }
+        SnapshotCommand::Restore { id, no_restart } => {
+            account_ops::restore_db_snapshot(id, !no_restart)
+                .await
+                .map(Output::message)
+        }
+        SnapshotCommand::Delete { id } => {
+            db_snapshot::delete_snapshot(id)?;
+            Ok(Output::message(format!("已删除快照: {}", id)))
+        }
+        SnapshotCommand::Prune {
+            keep,
+            hourly,
+            daily,
+            weekly,
+        } => {
+            let removed = match keep {
+                Some(keep) => db_snapshot::prune_snapshots(*keep)?,
+                None => {
+                    let default = RetentionPolicy::default();
+                    db_snapshot::apply_retention(&RetentionPolicy {
+                        hourly: hourly.unwrap_or(default.hourly),
+                        daily: daily.unwrap_or(default.daily),
+                        weekly: weekly.unwrap_or(default.weekly),
+                    })?
+                }
+            };
+            Ok(Output::with_data(
+                format!("已清理 {} 个旧快照", removed.len()),
+                json!({ "removed": removed }),
+            ))
+        }
+    }
+}
+
+fn cmd_audit(
+    query: AuditQuery,
+    format: ExportFormat,
+    output: Option<&PathBuf>,
+) -> Result<Output, String> {
+    let entries = audit_log::query(&query)?;
+
+    if let Some(file) = output {
+        fs::write(file, audit_log::export(&entries, format)?)
+            .map_err(|e| format!("写入文件失败: {}", e))?;
+        return Ok(Output::with_data(
+            format!("已导出 {} 条审计记录到 {}", entries.len(), file.display()),
+            json!({ "file": file, "count": entries.len() }),
+        ));
+    }
+
+    let text = if entries.is_empty() {
+        "没有审计记录".to_string()
+    } else {
+        entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}  {:<14} {} -> {}  {}{}",
+                    entry.timestamp,
+                    entry.action.as_str(),
+                    entry.from_account.as_deref().unwrap_or("-"),
+                    entry.to_account.as_deref().unwrap_or("-"),
+                    entry.outcome.as_str(),
+                    entry
+                        .detail
+                        .as_deref()
+                        .map(|detail| format!("  ({})", detail))
+                        .unwrap_or_default()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+    Ok(Output::with_data(text, entries))
+}
+
+fn cmd_processes() -> Result<Output, String> {
+    let processes = AntigravityProcess.scan();
+
+    let text = if processes.is_empty() {
+        "没有与 Antigravity 相关的进程".to_string()
+    } else {
+        processes
+            .iter()
+            .map(|m| {
+                let status = match m.status {
+                    MatchStatus::Matched => "匹配",
+                    MatchStatus::Excluded => "排除",
+                    MatchStatus::Ignored => "忽略",
+                };
+                format!("{:<8}{:<6}{:<24}{}", m.pid, status, m.name, m.reason)
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    Ok(Output::with_data(text, processes))
+}
+
+async fn run(cli: &Cli) -> Result<Output, String> {
+    unlock_vault(cli.vault_password.as_deref())?;
+    apply_shutdown_options(cli);
+
+    match &cli.command {
+        Command::List => cmd_list(),
+        Command::Current => cmd_current(),
+        Command::Backup { only } => cmd_backup(categories_or(only, DataCategory::ALL)),
+        Command::Switch {
+            account,
+            no_restart,
+        } => account_ops::switch_to_account(account, !no_restart)
+            .await
+            .map(Output::message),
+        Command::Restore {
+            account,
+            revision,
+            dry_run,
+            only,
+        } => {
+            let categories = categories_or(only, DataCategory::ALL);
+            cmd_restore(account, revision.as_deref(), *dry_run, categories).await
+        }
+        Command::Logout { restart, only } => {
+            account_ops::logout(*restart, categories_or(only, DataCategory::LOGOUT))
+                .await
+                .map(Output::message)
+        }
+        Command::Export { file, password } => cmd_export(file, password.as_deref()),
+        Command::Import { file, password } => cmd_import(file, password.as_deref()),
+        Command::Processes => cmd_processes(),
+        Command::Kill => AntigravityProcess.shutdown().await.map(Output::message),
+        Command::Start => AntigravityProcess.start().map(Output::message),
+        Command::Paths => cmd_paths(),
+        Command::Audit {
+            account,
+            since,
+            until,
+            format,
+            output,
+        } => cmd_audit(
+            AuditQuery::parse(account.clone(), since.as_deref(), until.as_deref())?,
+            *format,
+            output.as_ref(),
+        ),
+        Command::ExtraKeys {
+            add,
+            kind,
+            marker,
+            category,
+            remove,
+        } => cmd_extra_keys(
+            add.clone().map(|pattern| ExtraKey {
+                pattern,
+                kind: *kind,
+                marker: *marker,
+                category: *category,
+            }),
+            remove.as_deref(),
+        ),
+        Command::Db { action } => cmd_db(action.as_ref()),
+        Command::Snapshot { action } => cmd_snapshot(action.as_ref()).await,
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let cli = Cli::parse();
+    init_logging(cli.verbose);
+
+    match run(&cli).await {
+        Ok(output) => {
+            if cli.json {
+                println!("{}", json!({ "ok": true, "data": output.data }));
+            } else {
+                println!("{}", output.text);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if cli.json {
+                println!("{}", json!({ "ok": false, "error": e }));
+            } else {
+                eprintln!("❌ {}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,140 @@
+/// 数据库字段常量
+pub mod database {
+    /// 认证状态
+    pub const AUTH_STATUS: &str = "antigravityAuthStatus";
+
+    /// 用户头像 URL
+    pub const PROFILE_URL: &str = "antigravity.profileUrl";
+
+    /// 用户设置
+    pub const USER_SETTINGS: &str = "antigravityUserSettings.allUserSettings";
+
+    /// 新手引导
+    pub const ONBOARDING: &str = "antigravityOnboarding";
+
+    /// Google 相关数据
+    pub const GOOGLE_DATA: &str = "google.antigravity";
+
+    /// 命令模型配置
+    pub const COMMAND_CONFIGS: &str = "antigravity_allowed_command_model_configs";
+
+    /// Agent 状态同步
+    pub const AGENT_STATE: &str = "jetskiStateSync.agentManagerInitState";
+
+    /// 聊天会话存储
+    pub const CHAT_SESSION: &str = "chat.ChatSessionStore.index";
+
+    /// 新存储标记
+    pub const NEW_STORAGE_MARKER: &str = "__$__isNewStorageMarker";
+
+    /// 目标存储标记
+    pub const TARGET_STORAGE_MARKER: &str = "__$__targetStorageMarker";
+
+    /// 分析数据上传时间戳（恢复后重置，防止 Sync 冲突）
+    pub const ANALYTICS_LAST_UPLOAD_TIME: &str = "antigravityAnalytics.lastUploadTime";
+
+    /// 通知字段前缀（antigravity.notification.*）
+    pub const NOTIFICATION_PREFIX: &str = "antigravity.notification.";
+
+    // ---- 按数据类别分组的字段（见 data_category::DataCategory） ----
+
+    /// 认证类别：登录凭据、设备指纹和存储状态标记
+    pub const AUTH_KEYS: &[&str] = &[AUTH_STATUS, GOOGLE_DATA, NEW_STORAGE_MARKER];
+
+    /// 个人资料类别
+    pub const PROFILE_KEYS: &[&str] = &[PROFILE_URL];
+
+    /// 设置类别：用户设置和命令模型配置（变化时产生“设置已变化”事件）
+    pub const SETTINGS_KEYS: &[&str] = &[USER_SETTINGS, COMMAND_CONFIGS];
+
+    /// 新手引导类别
+    pub const ONBOARDING_KEYS: &[&str] = &[ONBOARDING];
+
+    /// Agent 状态类别
+    pub const AGENT_STATE_KEYS: &[&str] = &[AGENT_STATE];
+
+    /// 聊天类别
+    pub const CHAT_KEYS: &[&str] = &[CHAT_SESSION];
+
+    /// 清除时始终保留的字段（设备指纹和存储状态标记，删除可能触发风控）
+    pub const PRESERVED_KEYS: &[&str] = &[GOOGLE_DATA, NEW_STORAGE_MARKER];
+
+    /// 所有需要备份的字段列表（包含设备指纹，即以上所有类别的字段）
+    pub const ALL_KEYS: &[&str] = &[
+        AUTH_STATUS,
+        PROFILE_URL,
+        USER_SETTINGS,
+        ONBOARDING,
+        GOOGLE_DATA,
+        COMMAND_CONFIGS,
+        AGENT_STATE,
+        CHAT_SESSION,
+        NEW_STORAGE_MARKER,
+    ];
+}
+
+/// 路径常量
+pub mod paths {
+    /// 配置目录名称
+    pub const CONFIG_DIR_NAME: &str = ".antigravity-agent";
+
+    /// 指定 agent 主目录的环境变量（优先级最高）
+    pub const HOME_ENV: &str = "ANTIGRAVITY_AGENT_HOME";
+
+    /// 便携模式标记文件：与可执行文件放在同一目录时，数据保存在可执行文件旁边的配置目录中
+    pub const PORTABLE_MARKER: &str = "antigravity-agent.portable";
+
+    /// 日志目录名称（位于配置目录下）
+    pub const LOGS_DIR_NAME: &str = "logs";
+
+    /// 日志文件名前缀（每日一个文件：antigravity-agent.2025-11-23.log）
+    pub const LOG_FILE_PREFIX: &str = "antigravity-agent";
+
+    /// 应用设置文件
+    pub const APP_SETTINGS_FILE: &str = "app_settings.json";
+
+    /// 窗口状态文件
+    pub const WINDOW_STATE_FILE: &str = "window_state.json";
+}
+
+/// 账户注册表常量
+pub mod account_registry {
+    /// 注册表文件名（位于配置目录下）
+    pub const FILE_NAME: &str = "account_registry.json";
+}
+
+/// 审计日志常量
+pub mod audit_log {
+    /// 审计日志文件名（位于配置目录下，JSON Lines 格式）
+    pub const FILE_NAME: &str = "audit_log.jsonl";
+}
+
+/// 切换前快照常量
+pub mod switch_snapshot {
+    /// 快照目录名称（位于配置目录下）
+    pub const DIR_NAME: &str = "switch-snapshots";
+
+    /// 最多保留的快照数量
+    pub const MAX_SNAPSHOTS: usize = 10;
+}
+
+/// 完整数据库快照常量
+pub mod db_snapshot {
+    /// 快照目录名称（位于配置目录下）
+    pub const DIR_NAME: &str = "db-snapshots";
+
+    /// 快照元数据文件名（位于每个快照目录中）
+    pub const META_FILE: &str = "snapshot.json";
+
+    /// 在线备份每一步复制的页数
+    pub const PAGES_PER_STEP: i32 = 256;
+
+    /// 在线备份每一步之间的暂停时间（毫秒），期间 Antigravity 可以继续写入
+    pub const STEP_PAUSE_MS: u64 = 10;
+}
+
+/// 窗口状态限制
+pub mod window_limits {}
+
+/// 进程管理常量
+pub mod process {}
@@ -0,0 +1,450 @@
+// 加密模块
+// 导出文件使用基于密码的认证加密：Argon2id 派生密钥 + AES-256-GCM
+// 同时兼容旧版 XOR 导出格式的解密
+// 账户保险库使用会话内缓存的密钥直接加密（不在每个文件上重复派生密钥）
+//
+// 加密格式（整体 Base64 编码）：
+//   magic(5) | version(1) | m_cost(4, LE) | t_cost(4, LE) | p_cost(4, LE) | salt(16) | nonce(12) | 密文+tag
+// 头部（nonce 之前的全部字节及 nonce）作为 AAD 参与认证，任何篡改都会导致解密失败
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use std::fmt;
+
+/// 加密文件标识
+const MAGIC: &[u8; 5] = b"AGENC";
+
+/// 保险库文件标识（使用已派生的密钥加密）
+const KEYED_MAGIC: &[u8; 5] = b"AGVLT";
+
+/// 当前格式版本
+pub const FORMAT_VERSION: u8 = 1;
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const KEY_LEN: usize = 32;
+
+/// 256 位对称密钥
+pub type SecretKey = [u8; KEY_LEN];
+
+/// 头部长度：magic + version + 3 个 KDF 参数 + salt + nonce
+const HEADER_LEN: usize = MAGIC.len() + 1 + 4 * 3 + SALT_LEN + NONCE_LEN;
+
+/// 保险库文件头部长度：magic + version + nonce
+const KEYED_HEADER_LEN: usize = KEYED_MAGIC.len() + 1 + NONCE_LEN;
+
+/// 加解密错误
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    /// 密码为空
+    EmptyPassword,
+    /// 数据格式无效（Base64 或头部损坏）
+    InvalidFormat(String),
+    /// 不支持的格式版本
+    UnsupportedVersion(u8),
+    /// 认证失败：密码错误或文件已被篡改
+    WrongPasswordOrTampered,
+    /// 密钥派生或加密失败
+    Internal(String),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::EmptyPassword => write!(f, "密码不能为空"),
+            CryptoError::InvalidFormat(msg) => write!(f, "加密数据格式无效: {}", msg),
+            CryptoError::UnsupportedVersion(v) => {
+                write!(f, "不支持的加密格式版本: {}，请升级应用后重试", v)
+            }
+            CryptoError::WrongPasswordOrTampered => write!(f, "密码错误或文件已被篡改"),
+            CryptoError::Internal(msg) => write!(f, "加密失败: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
+
+impl From<CryptoError> for String {
+    fn from(err: CryptoError) -> Self {
+        err.to_string()
+    }
+}
+
+/// Argon2id 参数（随文件保存，便于以后调整强度而不影响旧文件）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// 内存开销（KiB）
+    pub m_cost: u32,
+    /// 迭代次数
+    pub t_cost: u32,
+    /// 并行度
+    pub p_cost: u32,
+}
+
+/// 参数上限：参数来自文件头部（不可信），在认证之前就会用于派生密钥，
+/// 不加限制时构造的文件可以耗尽内存或长时间占用 CPU
+/// 上限为默认参数的几倍（256 MiB、6 次迭代、4 线程），
+/// 派生一次密钥的开销在可接受范围内
+const MAX_M_COST: u32 = 256 * 1024;
+const MAX_T_COST: u32 = 6;
+const MAX_P_COST: u32 = 4;
+
+impl KdfParams {
+    /// 检查参数是否在允许范围内
+    pub fn validate(&self) -> Result<(), CryptoError> {
+        if self.m_cost > MAX_M_COST || self.t_cost > MAX_T_COST || self.p_cost > MAX_P_COST {
+            return Err(CryptoError::InvalidFormat(format!(
+                "KDF 参数超出允许范围 (m_cost={}, t_cost={}, p_cost={})",
+                self.m_cost, self.t_cost, self.p_cost
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            m_cost: 64 * 1024,
+            t_cost: 3,
+            p_cost: 1,
+        }
+    }
+}
+
+/// 使用 Argon2id 从密码派生 256 位密钥（参数超出上限时拒绝派生）
+pub fn derive_key(
+    password: &str,
+    salt: &[u8],
+    params: KdfParams,
+) -> Result<SecretKey, CryptoError> {
+    if password.is_empty() {
+        return Err(CryptoError::EmptyPassword);
+    }
+    params.validate()?;
+
+    let argon_params = Params::new(params.m_cost, params.t_cost, params.p_cost, Some(KEY_LEN))
+        .map_err(|e| CryptoError::InvalidFormat(format!("KDF 参数无效: {}", e)))?;
+
+    let mut key = [0u8; KEY_LEN];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, argon_params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| CryptoError::Internal(format!("密钥派生失败: {}", e)))?;
+
+    Ok(key)
+}
+
+/// 生成随机盐
+pub fn generate_salt() -> [u8; SALT_LEN] {
+    let mut salt = [0u8; SALT_LEN];
+    OsRng.fill_bytes(&mut salt);
+    salt
+}
+
+/// 判断数据是否为新版加密格式（已 Base64 解码）
+pub fn is_encrypted(data: &[u8]) -> bool {
+    data.starts_with(MAGIC)
+}
+
+/// 使用密码加密数据，返回带版本头的二进制数据
+pub fn encrypt(plaintext: &[u8], password: &str) -> Result<Vec<u8>, CryptoError> {
+    let params = KdfParams::default();
+
+    let salt = generate_salt();
+    let key = derive_key(password, &salt, params)?;
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let mut output = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+    output.extend_from_slice(MAGIC);
+    output.push(FORMAT_VERSION);
+    output.extend_from_slice(&params.m_cost.to_le_bytes());
+    output.extend_from_slice(&params.t_cost.to_le_bytes());
+    output.extend_from_slice(&params.p_cost.to_le_bytes());
+    output.extend_from_slice(&salt);
+    output.extend_from_slice(&nonce);
+
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &output,
+            },
+        )
+        .map_err(|e| CryptoError::Internal(e.to_string()))?;
+
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+/// 使用密码解密新版加密格式
+pub fn decrypt(data: &[u8], password: &str) -> Result<Vec<u8>, CryptoError> {
+    if !is_encrypted(data) {
+        return Err(CryptoError::InvalidFormat("缺少文件标识".to_string()));
+    }
+    if data.len() < HEADER_LEN {
+        return Err(CryptoError::InvalidFormat("数据长度不足".to_string()));
+    }
+
+    let version = data[MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(CryptoError::UnsupportedVersion(version));
+    }
+
+    let read_u32 = |offset: usize| {
+        u32::from_le_bytes([
+            data[offset],
+            data[offset + 1],
+            data[offset + 2],
+            data[offset + 3],
+        ])
+    };
+    let params_offset = MAGIC.len() + 1;
+    let params = KdfParams {
+        m_cost: read_u32(params_offset),
+        t_cost: read_u32(params_offset + 4),
+        p_cost: read_u32(params_offset + 8),
+    };
+
+    let salt_offset = params_offset + 12;
+    let nonce_offset = salt_offset + SALT_LEN;
+    let salt = &data[salt_offset..nonce_offset];
+    let nonce = Nonce::from_slice(&data[nonce_offset..HEADER_LEN]);
+
+    let key = derive_key(password, salt, params)?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key));
+
+    cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: &data[HEADER_LEN..],
+                aad: &data[..HEADER_LEN],
+            },
+        )
+        .map_err(|_| CryptoError::WrongPasswordOrTampered)
+}
+
+/// 判断数据是否为保险库加密格式
+pub fn is_key_encrypted(data: &[u8]) -> bool {
+    data.starts_with(KEYED_MAGIC)
+}
+
+/// 使用已派生的密钥加密数据（保险库文件格式）
+pub fn encrypt_with_key(plaintext: &[u8], key: &SecretKey) -> Result<Vec<u8>, CryptoError> {
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let mut output = Vec::with_capacity(KEYED_HEADER_LEN + plaintext.len() + 16);
+    output.extend_from_slice(KEYED_MAGIC);
+    output.push(FORMAT_VERSION);
+    output.extend_from_slice(&nonce);
+
+    let ciphertext = cipher
+        .encrypt(
+            &nonce,
+            Payload {
+                msg: plaintext,
+                aad: &output,
+            },
+        )
+        .map_err(|e| CryptoError::Internal(e.to_string()))?;
+
+    output.extend_from_slice(&ciphertext);
+    Ok(output)
+}
+
+/// 使用已派生的密钥解密保险库文件
+pub fn decrypt_with_key(data: &[u8], key: &SecretKey) -> Result<Vec<u8>, CryptoError> {
+    if !is_key_encrypted(data) {
+        return Err(CryptoError::InvalidFormat("缺少文件标识".to_string()));
+    }
+    if data.len() < KEYED_HEADER_LEN {
+        return Err(CryptoError::InvalidFormat("数据长度不足".to_string()));
+    }
+
+    let version = data[KEYED_MAGIC.len()];
+    if version != FORMAT_VERSION {
+        return Err(CryptoError::UnsupportedVersion(version));
+    }
+
+    let nonce = Nonce::from_slice(&data[KEYED_MAGIC.len() + 1..KEYED_HEADER_LEN]);
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+
+    cipher
+        .decrypt(
+            nonce,
+            Payload {
+                msg: &data[KEYED_HEADER_LEN..],
+                aad: &data[..KEYED_HEADER_LEN],
+            },
+        )
+        .map_err(|_| CryptoError::WrongPasswordOrTampered)
+}
+
+/// 解密旧版 XOR 导出格式（仅用于兼容已有导出文件）
+pub fn decrypt_legacy_xor(data: &[u8], password: &str) -> Result<Vec<u8>, CryptoError> {
+    if password.is_empty() {
+        return Err(CryptoError::EmptyPassword);
+    }
+
+    let key_bytes = password.as_bytes();
+    Ok(data
+        .iter()
+        .enumerate()
+        .map(|(i, &byte)| byte ^ key_bytes[i % key_bytes.len()])
+        .collect())
+}
+
+/// 加密文本并返回 Base64 字符串（导出文件内容）
+pub fn encrypt_to_base64(plaintext: &str, password: &str) -> Result<String, CryptoError> {
+    encrypt(plaintext.as_bytes(), password).map(|data| STANDARD.encode(data))
+}
+
+/// 解密 Base64 导出内容，自动识别新版格式和旧版 XOR 格式
+///
+/// # 返回
+/// - `Ok((text, is_legacy))`: 解密后的文本，以及是否来自旧版 XOR 格式
+pub fn decrypt_from_base64(encoded: &str, password: &str) -> Result<(String, bool), CryptoError> {
+    let data = STANDARD
+        .decode(encoded.trim())
+        .map_err(|e| CryptoError::InvalidFormat(format!("Base64解码失败: {}", e)))?;
+
+    let (plaintext, is_legacy) = if is_encrypted(&data) {
+        (decrypt(&data, password)?, false)
+    } else {
+        (decrypt_legacy_xor(&data, password)?, true)
+    };
+
+    // 旧版格式没有完整性校验，密码错误时只会得到乱码
+    let text = String::from_utf8(plaintext).map_err(|_| CryptoError::WrongPasswordOrTampered)?;
+    Ok((text, is_legacy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PASSWORD: &str = "correct horse battery staple";
+
+    #[test]
+    fn encrypt_decrypt_round_trip() {
+        let encoded = encrypt_to_base64("账户数据", PASSWORD).unwrap();
+        let (text, is_legacy) = decrypt_from_base64(&encoded, PASSWORD).unwrap();
+        assert_eq!(text, "账户数据");
+        assert!(!is_legacy);
+    }
+
+    #[test]
+    fn decrypt_rejects_wrong_password() {
+        let data = encrypt(b"secret", PASSWORD).unwrap();
+        assert_eq!(
+            decrypt(&data, "wrong password"),
+            Err(CryptoError::WrongPasswordOrTampered)
+        );
+    }
+
+    #[test]
+    fn decrypt_rejects_tampered_header_and_ciphertext() {
+        let data = encrypt(b"secret", PASSWORD).unwrap();
+
+        // 头部作为 AAD 参与认证：修改盐会导致认证失败
+        let mut tampered_header = data.clone();
+        tampered_header[MAGIC.len() + 1 + 12] ^= 0x01;
+        assert_eq!(
+            decrypt(&tampered_header, PASSWORD),
+            Err(CryptoError::WrongPasswordOrTampered)
+        );
+
+        let mut tampered_ciphertext = data.clone();
+        *tampered_ciphertext.last_mut().unwrap() ^= 0x01;
+        assert_eq!(
+            decrypt(&tampered_ciphertext, PASSWORD),
+            Err(CryptoError::WrongPasswordOrTampered)
+        );
+
+        let mut bad_version = data;
+        bad_version[MAGIC.len()] = FORMAT_VERSION + 1;
+        assert_eq!(
+            decrypt(&bad_version, PASSWORD),
+            Err(CryptoError::UnsupportedVersion(FORMAT_VERSION + 1))
+        );
+    }
+
+    #[test]
+    fn decrypt_rejects_oversized_kdf_params() {
+        let data = encrypt(b"secret", PASSWORD).unwrap();
+        let params_offset = MAGIC.len() + 1;
+
+        // 依次把 m_cost、t_cost、p_cost 改为刚超过上限的值，都应在派生密钥前被拒绝
+        for (index, value) in [
+            (0, MAX_M_COST + 1),
+            (1, MAX_T_COST + 1),
+            (2, MAX_P_COST + 1),
+        ] {
+            let mut tampered = data.clone();
+            let offset = params_offset + index * 4;
+            tampered[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+            assert!(matches!(
+                decrypt(&tampered, PASSWORD),
+                Err(CryptoError::InvalidFormat(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn kdf_params_limits() {
+        assert!(KdfParams::default().validate().is_ok());
+        let max = KdfParams {
+            m_cost: MAX_M_COST,
+            t_cost: MAX_T_COST,
+            p_cost: MAX_P_COST,
+        };
+        assert!(max.validate().is_ok());
+        for oversized in [
+            KdfParams {
+                m_cost: MAX_M_COST + 1,
+                ..max
+            },
+            KdfParams {
+                t_cost: MAX_T_COST + 1,
+                ..max
+            },
+            KdfParams {
+                p_cost: MAX_P_COST + 1,
+                ..max
+            },
+        ] {
+            assert!(oversized.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn decrypts_legacy_xor_exports() {
+        let legacy: Vec<u8> = b"{\"accounts\":[]}"
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| byte ^ b"pw"[i % 2])
+            .collect();
+        let (text, is_legacy) = decrypt_from_base64(&STANDARD.encode(legacy), "pw").unwrap();
+        assert_eq!(text, "{\"accounts\":[]}");
+        assert!(is_legacy);
+    }
+
+    #[test]
+    fn keyed_round_trip_and_wrong_key() {
+        let key = [7u8; KEY_LEN];
+        let data = encrypt_with_key(b"vault", &key).unwrap();
+        assert!(is_key_encrypted(&data));
+        assert_eq!(decrypt_with_key(&data, &key).unwrap(), b"vault");
+        assert_eq!(
+            decrypt_with_key(&data, &[8u8; KEY_LEN]),
+            Err(CryptoError::WrongPasswordOrTampered)
+        );
+    }
+}
@@ -0,0 +1,144 @@
+// 数据类别模块
+// 将 state.vscdb 中的备份字段按用途分组（认证、个人资料、设置、新手引导、Agent 状态、聊天、通知），
+// 备份、恢复和清除可以只处理选定的类别，例如只切换登录凭据而保留当前的设置
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::constants::database;
+
+/// 备份文件中记录备份类别的字段
+pub const BACKUP_FIELD: &str = "data_categories";
+
+/// 数据类别
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataCategory {
+    /// 登录凭据（认证状态、设备指纹、存储状态标记）
+    Auth,
+    /// 个人资料（头像）
+    Profile,
+    /// 用户设置和命令模型配置
+    Settings,
+    /// 新手引导状态
+    Onboarding,
+    /// Agent 状态同步
+    AgentState,
+    /// 聊天会话
+    Chat,
+    /// 通知（antigravity.notification.*）
+    Notifications,
+}
+
+impl DataCategory {
+    /// 所有类别（备份和恢复的默认范围）
+    pub const ALL: &'static [DataCategory] = &[
+        DataCategory::Auth,
+        DataCategory::Profile,
+        DataCategory::Settings,
+        DataCategory::Onboarding,
+        DataCategory::AgentState,
+        DataCategory::Chat,
+        DataCategory::Notifications,
+    ];
+
+    /// 注销时默认清除的类别（保留会话数据和通知）
+    pub const LOGOUT: &'static [DataCategory] = &[
+        DataCategory::Auth,
+        DataCategory::Profile,
+        DataCategory::Settings,
+        DataCategory::Onboarding,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataCategory::Auth => "auth",
+            DataCategory::Profile => "profile",
+            DataCategory::Settings => "settings",
+            DataCategory::Onboarding => "onboarding",
+            DataCategory::AgentState => "agent_state",
+            DataCategory::Chat => "chat",
+            DataCategory::Notifications => "notifications",
+        }
+    }
+
+    /// 该类别包含的固定字段（通知字段按前缀匹配，不在此列出）
+    pub fn keys(&self) -> &'static [&'static str] {
+        match self {
+            DataCategory::Auth => database::AUTH_KEYS,
+            DataCategory::Profile => database::PROFILE_KEYS,
+            DataCategory::Settings => database::SETTINGS_KEYS,
+            DataCategory::Onboarding => database::ONBOARDING_KEYS,
+            DataCategory::AgentState => database::AGENT_STATE_KEYS,
+            DataCategory::Chat => database::CHAT_KEYS,
+            DataCategory::Notifications => &[],
+        }
+    }
+
+    /// 字段所属的类别
+    pub fn of_key(key: &str) -> Option<DataCategory> {
+        if key.starts_with(database::NOTIFICATION_PREFIX) {
+            return Some(DataCategory::Notifications);
+        }
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|category| category.keys().contains(&key))
+    }
+}
+
+impl fmt::Display for DataCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for DataCategory {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|category| category.as_str() == s.trim().to_ascii_lowercase())
+            .ok_or_else(|| format!("未知的数据类别: {}（支持 {}）", s, describe(Self::ALL)))
+    }
+}
+
+/// 选定类别包含的固定字段（按 `database::ALL_KEYS` 的顺序）
+pub fn keys_of(categories: &[DataCategory]) -> Vec<&'static str> {
+    database::ALL_KEYS
+        .iter()
+        .copied()
+        .filter(|key| DataCategory::of_key(key).is_some_and(|c| categories.contains(&c)))
+        .collect()
+}
+
+/// 备份包含的类别（旧版备份没有记录类别，视为完整备份）
+pub fn of_backup(backup: &Value) -> Vec<DataCategory> {
+    backup
+        .get(BACKUP_FIELD)
+        .and_then(|value| serde_json::from_value(value.clone()).ok())
+        .unwrap_or_else(|| DataCategory::ALL.to_vec())
+}
+
+/// 是否选中了通知类别
+pub fn includes_notifications(categories: &[DataCategory]) -> bool {
+    categories.contains(&DataCategory::Notifications)
+}
+
+/// 是否选中了所有类别
+pub fn is_all(categories: &[DataCategory]) -> bool {
+    DataCategory::ALL.iter().all(|c| categories.contains(c))
+}
+
+/// 类别列表的文字描述（如 `auth, settings`），用于日志和审计记录
+pub fn describe(categories: &[DataCategory]) -> String {
+    categories
+        .iter()
+        .map(DataCategory::as_str)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
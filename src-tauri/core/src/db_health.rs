@@ -0,0 +1,413 @@
+// 数据库健康检查模块
+// 对 state.vscdb 和 state.vscdb.backup 执行完整性检查、统计大小和空闲页、按需 VACUUM，
+// 并检查 __$__targetStorageMarker 与实际数据是否一致（Marker 中有但数据库没有的字段、
+// 数据库中有但 Marker 没有登记的字段），可按恢复和清除时的同一套规则修复
+
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::antigravity_cleanup::remove_keys_from_marker;
+use crate::antigravity_restore::default_marker_flag;
+use crate::constants::database;
+use crate::data_category::DataCategory;
+use crate::{db_preflight, extra_keys};
+
+/// 单个数据库的健康状况
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseHealth {
+    /// 数据库名称（state.vscdb 或 state.vscdb.backup）
+    pub database: String,
+    pub path: PathBuf,
+    /// 文件大小（字节）
+    pub size_bytes: u64,
+    pub page_size: i64,
+    pub page_count: i64,
+    /// 空闲页数量（VACUUM 后可回收）
+    pub freelist_count: i64,
+    /// 空闲页占用的字节数
+    pub free_bytes: i64,
+    /// PRAGMA integrity_check 的结果（正常时只有一行 "ok"）
+    pub integrity: Vec<String>,
+}
+
+impl DatabaseHealth {
+    /// 完整性检查是否通过
+    pub fn is_ok(&self) -> bool {
+        self.integrity.len() == 1 && self.integrity[0] == "ok"
+    }
+}
+
+/// VACUUM 前后的文件大小
+#[derive(Debug, Clone, Serialize)]
+pub struct VacuumResult {
+    pub database: String,
+    pub before_bytes: u64,
+    pub after_bytes: u64,
+}
+
+/// 单个数据库的 Marker 一致性检查结果
+#[derive(Debug, Clone, Serialize)]
+pub struct MarkerReport {
+    pub database: String,
+    /// 数据库中是否存在 Marker
+    pub marker_present: bool,
+    /// Marker 是否为有效的 JSON 对象
+    pub marker_valid: bool,
+    /// Marker 中登记的字段数量
+    pub marker_keys: usize,
+    /// Marker 中登记了但数据库中没有对应行的字段
+    pub dangling: Vec<String>,
+    /// 数据库中存在但 Marker 中没有登记的字段（只检查需要登记的内置字段和自定义字段）
+    pub unregistered: Vec<String>,
+}
+
+impl MarkerReport {
+    /// Marker 与数据是否一致
+    pub fn is_consistent(&self) -> bool {
+        self.marker_valid && self.dangling.is_empty() && self.unregistered.is_empty()
+    }
+}
+
+/// 存在的数据库文件（名称和路径），主库不存在时返回错误
+fn state_databases(app_data: &Path) -> Result<Vec<(&'static str, PathBuf)>, String> {
+    if !app_data.exists() {
+        return Err(format!(
+            "Antigravity 状态数据库不存在: {}",
+            app_data.display()
+        ));
+    }
+
+    let mut databases = vec![("state.vscdb", app_data.to_path_buf())];
+    let backup_db = app_data.with_extension("vscdb.backup");
+    if backup_db.exists() {
+        databases.push(("state.vscdb.backup", backup_db));
+    }
+    Ok(databases)
+}
+
+fn file_size(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
+}
+
+fn pragma_i64(conn: &Connection, pragma: &str) -> Result<i64, String> {
+    conn.query_row(&format!("PRAGMA {}", pragma), [], |row| row.get(0))
+        .map_err(|e| format!("读取 {} 失败: {}", pragma, e))
+}
+
+fn database_health(name: &str, path: &Path) -> Result<DatabaseHealth, String> {
+    let conn = db_preflight::open_read_only(path)?;
+
+    let integrity = conn
+        .prepare("PRAGMA integrity_check")
+        .and_then(|mut stmt| {
+            stmt.query_map([], |row| row.get::<_, String>(0))?
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(|e| format!("{} 完整性检查失败: {}", name, e))?;
+
+    let page_size = pragma_i64(&conn, "page_size")?;
+    let freelist_count = pragma_i64(&conn, "freelist_count")?;
+
+    Ok(DatabaseHealth {
+        database: name.to_string(),
+        path: path.to_path_buf(),
+        size_bytes: file_size(path),
+        page_size,
+        page_count: pragma_i64(&conn, "page_count")?,
+        freelist_count,
+        free_bytes: page_size * freelist_count,
+        integrity,
+    })
+}
+
+/// 对 state.vscdb 和 state.vscdb.backup 执行完整性检查并统计大小（只读，Antigravity 运行时也可以调用）
+pub fn check_health(app_data: &Path) -> Result<Vec<DatabaseHealth>, String> {
+    let mut reports = Vec::new();
+
+    for (name, path) in state_databases(app_data)? {
+        tracing::info!("🩺 检查数据库: {}", name);
+        let health = database_health(name, &path)?;
+        if health.is_ok() {
+            tracing::info!("  ✅ 完整性检查通过");
+        } else {
+            tracing::warn!("  ⚠️ 完整性检查发现 {} 个问题", health.integrity.len());
+        }
+        reports.push(health);
+    }
+
+    Ok(reports)
+}
+
+/// 对 state.vscdb 和 state.vscdb.backup 执行 VACUUM，回收空闲页
+///
+/// 写入前检查两个数据库，任意一个被占用都不做任何修改
+pub fn vacuum(app_data: &Path) -> Result<Vec<VacuumResult>, String> {
+    let databases = state_databases(app_data)?;
+    let connections = databases
+        .iter()
+        .map(|(_, path)| db_preflight::open_for_write(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut results = Vec::new();
+    for ((name, path), conn) in databases.iter().zip(connections) {
+        let before_bytes = file_size(path);
+        tracing::info!("🧹 VACUUM: {}", name);
+        conn.execute_batch("VACUUM")
+            .map_err(|e| format!("{} VACUUM 失败: {}", name, e))?;
+        drop(conn);
+
+        let after_bytes = file_size(path);
+        tracing::info!("  ✅ {} 字节 -> {} 字节", before_bytes, after_bytes);
+        results.push(VacuumResult {
+            database: name.to_string(),
+            before_bytes,
+            after_bytes,
+        });
+    }
+
+    Ok(results)
+}
+
+/// 需要在 Marker 中登记的字段（与恢复时的规则一致：内置字段除 __$__isNewStorageMarker 外都登记，
+/// 自定义字段按设置中的 marker 选项）
+fn marker_managed_keys(conn: &Connection) -> Result<Vec<String>, String> {
+    let mut keys: Vec<String> = database::ALL_KEYS
+        .iter()
+        .filter(|key| **key != database::NEW_STORAGE_MARKER)
+        .map(|key| key.to_string())
+        .collect();
+
+    let extra = extra_keys::load()?;
+    keys.extend(
+        extra_keys::resolve(conn, &extra, DataCategory::ALL)?
+            .into_iter()
+            .filter(|extra| extra.marker)
+            .map(|extra| extra.key),
+    );
+
+    Ok(keys)
+}
+
+fn row_exists(conn: &Connection, key: &str) -> Result<bool, String> {
+    conn.query_row("SELECT 1 FROM ItemTable WHERE key = ?", [key], |_| Ok(()))
+        .optional()
+        .map(|row| row.is_some())
+        .map_err(|e| format!("查询字段 {} 失败: {}", key, e))
+}
+
+fn marker_report(name: &str, conn: &Connection) -> Result<MarkerReport, String> {
+    let marker_str: Option<String> = conn
+        .query_row(
+            "SELECT value FROM ItemTable WHERE key = ?",
+            [database::TARGET_STORAGE_MARKER],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| format!("读取 Marker 失败: {}", e))?;
+
+    let marker: Option<Map<String, Value>> = marker_str
+        .as_deref()
+        .map(|s| serde_json::from_str(s).ok())
+        .unwrap_or_else(|| Some(Map::new()));
+
+    let mut report = MarkerReport {
+        database: name.to_string(),
+        marker_present: marker_str.is_some(),
+        marker_valid: marker.is_some(),
+        marker_keys: 0,
+        dangling: Vec::new(),
+        unregistered: Vec::new(),
+    };
+    // Marker 损坏时无法判断字段是否登记
+    let Some(marker) = marker else {
+        return Ok(report);
+    };
+
+    report.marker_keys = marker.len();
+    for key in marker.keys() {
+        if !row_exists(conn, key)? {
+            report.dangling.push(key.clone());
+        }
+    }
+    for key in marker_managed_keys(conn)? {
+        if !marker.contains_key(&key) && row_exists(conn, &key)? {
+            report.unregistered.push(key);
+        }
+    }
+
+    Ok(report)
+}
+
+/// 检查两个数据库中 Marker 与实际数据是否一致（只读）
+pub fn check_marker(app_data: &Path) -> Result<Vec<MarkerReport>, String> {
+    state_databases(app_data)?
+        .into_iter()
+        .map(|(name, path)| marker_report(name, &db_preflight::open_read_only(&path)?))
+        .collect()
+}
+
+/// 将缺失的字段按默认 flag 登记到 Marker（与恢复时没有备份 Marker 信息的处理一致）
+fn register_keys_in_marker(conn: &Connection, keys: &[String]) -> Result<(), String> {
+    let mut marker: Map<String, Value> = conn
+        .query_row(
+            "SELECT value FROM ItemTable WHERE key = ?",
+            [database::TARGET_STORAGE_MARKER],
+            |row| row.get::<_, String>(0),
+        )
+        .optional()
+        .map_err(|e| format!("读取 Marker 失败: {}", e))?
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default();
+
+    for key in keys {
+        let flag = default_marker_flag(key);
+        tracing::info!("  ➕ 登记到 Marker: {} = {}", key, flag);
+        marker.insert(key.clone(), json!(flag));
+    }
+
+    let marker_str = serde_json::to_string(&marker).map_err(|e| format!("序列化失败: {}", e))?;
+    conn.execute(
+        "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
+        params![database::TARGET_STORAGE_MARKER, marker_str],
+    )
+    .map_err(|e| format!("写入 Marker 失败: {}", e))?;
+
+    // 与恢复时一样重置上传时间戳（防止 Sync 冲突）
+    conn.execute(
+        "INSERT OR REPLACE INTO ItemTable (key, value) VALUES (?, ?)",
+        params![database::ANALYTICS_LAST_UPLOAD_TIME, "0"],
+    )
+    .map_err(|e| format!("重置分析时间戳失败: {}", e))?;
+
+    Ok(())
+}
+
+/// 修复两个数据库的 Marker：移除没有对应行的字段，登记缺失的字段
+///
+/// 每个数据库在单独的事务中修复；Marker 损坏时不做修改。
+/// 写入前检查两个数据库，任意一个被占用都不做任何修改
+///
+/// # 返回
+/// - `Ok(reports)`: 修复前的检查结果
+pub fn repair_marker(app_data: &Path) -> Result<Vec<MarkerReport>, String> {
+    let databases = state_databases(app_data)?;
+    let mut connections = databases
+        .iter()
+        .map(|(_, path)| db_preflight::open_for_write(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut reports = Vec::new();
+    for ((name, _), conn) in databases.iter().zip(connections.iter_mut()) {
+        tracing::info!("🔧 修复 Marker: {}", name);
+        let report = marker_report(name, conn)?;
+
+        if !report.marker_valid {
+            tracing::warn!("  ⚠️ Marker 不是有效的 JSON，跳过修复");
+        } else if report.is_consistent() {
+            tracing::info!("  ✅ Marker 与数据一致，无需修复");
+        } else {
+            let tx = conn
+                .transaction()
+                .map_err(|e| format!("开启事务失败: {}", e))?;
+            if !report.dangling.is_empty() {
+                let dangling: Vec<&str> = report.dangling.iter().map(String::as_str).collect();
+                remove_keys_from_marker(&tx, &dangling)?;
+            }
+            if !report.unregistered.is_empty() {
+                register_keys_in_marker(&tx, &report.unregistered)?;
+            }
+            tx.commit().map_err(|e| format!("提交事务失败: {}", e))?;
+            tracing::info!(
+                "  ✅ 已移除 {} 个、登记 {} 个字段",
+                report.dangling.len(),
+                report.unregistered.len()
+            );
+        }
+
+        reports.push(report);
+    }
+
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    fn state_db(rows: &[(&str, &str)]) -> Connection {
+        // 自定义字段设置从临时 agent 主目录读取（未配置）
+        test_support::agent_home();
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE ItemTable (key TEXT PRIMARY KEY, value BLOB)")
+            .unwrap();
+        for (key, value) in rows {
+            conn.execute(
+                "INSERT INTO ItemTable (key, value) VALUES (?, ?)",
+                params![key, value],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn marker_report_finds_dangling_and_unregistered_keys() {
+        let marker = json!({
+            database::AUTH_STATUS: 1,
+            database::ONBOARDING: 1,
+        })
+        .to_string();
+        let conn = state_db(&[
+            (database::AUTH_STATUS, "{}"),
+            (database::PROFILE_URL, "https://example.com/a.png"),
+            (database::NEW_STORAGE_MARKER, "true"),
+            (database::TARGET_STORAGE_MARKER, &marker),
+        ]);
+
+        let report = marker_report("state.vscdb", &conn).unwrap();
+        assert!(report.marker_present);
+        assert!(report.marker_valid);
+        assert_eq!(report.marker_keys, 2);
+        assert_eq!(report.dangling, [database::ONBOARDING]);
+        // __$__isNewStorageMarker 不需要登记
+        assert_eq!(report.unregistered, [database::PROFILE_URL]);
+        assert!(!report.is_consistent());
+    }
+
+    #[test]
+    fn marker_report_consistent_marker() {
+        let marker = json!({ database::AUTH_STATUS: 1 }).to_string();
+        let conn = state_db(&[
+            (database::AUTH_STATUS, "{}"),
+            (database::TARGET_STORAGE_MARKER, &marker),
+        ]);
+
+        assert!(marker_report("state.vscdb", &conn).unwrap().is_consistent());
+    }
+
+    #[test]
+    fn marker_report_handles_missing_and_invalid_marker() {
+        let missing =
+            marker_report("state.vscdb", &state_db(&[(database::AUTH_STATUS, "{}")])).unwrap();
+        assert!(!missing.marker_present);
+        assert!(missing.marker_valid);
+        assert_eq!(missing.unregistered, [database::AUTH_STATUS]);
+
+        let invalid = marker_report(
+            "state.vscdb",
+            &state_db(&[
+                (database::AUTH_STATUS, "{}"),
+                (database::TARGET_STORAGE_MARKER, "not json"),
+            ]),
+        )
+        .unwrap();
+        assert!(invalid.marker_present);
+        assert!(!invalid.marker_valid);
+        assert!(invalid.unregistered.is_empty());
+        assert!(!invalid.is_consistent());
+    }
+}
@@ -0,0 +1,386 @@
+// 数据库写入前检查模块
+// 所有写入 state.vscdb / state.vscdb.backup 的操作都先经过这里：
+// 检查是否有其他进程打开了数据库文件、等待锁释放、处理 -journal / -wal 文件，检查不通过时拒绝写入
+
+use rusqlite::{Connection, ErrorCode, OpenFlags};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// 获取写锁时的忙等待时间（SQLite 在此期间自动重试）
+pub const BUSY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 正在使用数据库文件的进程
+#[derive(Debug, Clone, Serialize)]
+pub struct FileHolder {
+    pub pid: u32,
+    pub name: String,
+    /// 被打开的文件（无法确认具体文件时为 None）
+    pub file: Option<String>,
+}
+
+impl std::fmt::Display for FileHolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} (PID: {})", self.name, self.pid)?;
+        if let Some(file) = &self.file {
+            write!(f, " -> {}", file)?;
+        }
+        Ok(())
+    }
+}
+
+/// 写入前检查结果
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PreflightReport {
+    pub db_path: PathBuf,
+    /// 存在未完成事务的回滚日志，已由 SQLite 自动回滚
+    pub journal_recovered: bool,
+    /// WAL 文件已合并回数据库
+    pub wal_checkpointed: bool,
+}
+
+/// 数据库的附属文件路径（如 `state.vscdb-wal`）
+fn sidecar(db_path: &Path, suffix: &str) -> PathBuf {
+    let mut path = db_path.as_os_str().to_owned();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+fn is_non_empty(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|m| m.len() > 0)
+}
+
+fn describe_holders(holders: &[FileHolder]) -> String {
+    holders
+        .iter()
+        .map(|h| h.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 查找打开了数据库（及其 -wal / -journal / -shm 文件）的其他进程
+pub fn find_holders(db_path: &Path) -> Vec<FileHolder> {
+    let files: Vec<PathBuf> = ["", "-wal", "-journal", "-shm"]
+        .iter()
+        .map(|suffix| sidecar(db_path, suffix))
+        .filter(|path| path.exists())
+        .map(|path| path.canonicalize().unwrap_or(path))
+        .collect();
+
+    if files.is_empty() {
+        return Vec::new();
+    }
+
+    open_file_holders(&files)
+}
+
+/// 进程名称
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn process_name(pid: u32) -> String {
+    let mut system = sysinfo::System::new();
+    let pid = sysinfo::Pid::from_u32(pid);
+    system.refresh_process(pid);
+    system
+        .process(pid)
+        .map(|p| p.name().to_string())
+        .unwrap_or_else(|| "未知进程".to_string())
+}
+
+/// Linux：遍历 /proc/<pid>/fd 查找打开了文件的进程
+#[cfg(target_os = "linux")]
+fn open_file_holders(files: &[PathBuf]) -> Vec<FileHolder> {
+    let own_pid = std::process::id();
+    let mut holders = Vec::new();
+
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return holders;
+    };
+
+    for entry in entries.flatten() {
+        let Some(pid) = entry
+            .file_name()
+            .to_str()
+            .and_then(|s| s.parse::<u32>().ok())
+        else {
+            continue;
+        };
+        if pid == own_pid {
+            continue;
+        }
+        // 没有权限读取的进程直接跳过
+        let Ok(fds) = fs::read_dir(entry.path().join("fd")) else {
+            continue;
+        };
+
+        let held = fds
+            .flatten()
+            .filter_map(|fd| fs::read_link(fd.path()).ok())
+            .find(|target| files.contains(target));
+        if let Some(file) = held {
+            holders.push(FileHolder {
+                pid,
+                name: process_name(pid),
+                file: Some(file.display().to_string()),
+            });
+        }
+    }
+
+    holders
+}
+
+/// macOS：使用 lsof 查找打开了文件的进程
+#[cfg(target_os = "macos")]
+fn open_file_holders(files: &[PathBuf]) -> Vec<FileHolder> {
+    let own_pid = std::process::id();
+    let mut holders: Vec<FileHolder> = Vec::new();
+
+    for file in files {
+        let Ok(output) = std::process::Command::new("lsof")
+            .arg("-t")
+            .arg(file)
+            .output()
+        else {
+            continue;
+        };
+
+        for pid in String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().parse::<u32>().ok())
+        {
+            if pid != own_pid && !holders.iter().any(|h| h.pid == pid) {
+                holders.push(FileHolder {
+                    pid,
+                    name: process_name(pid),
+                    file: Some(file.display().to_string()),
+                });
+            }
+        }
+    }
+
+    holders
+}
+
+/// Windows 等平台：无法直接查询文件句柄，以正在运行的 Antigravity 进程作为占用者
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn open_file_holders(_files: &[PathBuf]) -> Vec<FileHolder> {
+    use crate::process::{AntigravityProcess, MatchStatus};
+
+    AntigravityProcess
+        .scan()
+        .into_iter()
+        .filter(|m| m.status == MatchStatus::Matched)
+        .map(|m| FileHolder {
+            pid: m.pid,
+            name: m.name,
+            file: None,
+        })
+        .collect()
+}
+
+/// 打开数据库并设置忙等待超时
+fn open_with_busy_timeout(db_path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open(db_path)
+        .map_err(|e| format!("打开数据库失败 ({}): {}", db_path.display(), e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("设置数据库忙等待超时失败: {}", e))?;
+    Ok(conn)
+}
+
+/// 以只读方式打开数据库并设置忙等待超时（只读取时无需写入前检查）
+pub fn open_read_only(db_path: &Path) -> Result<Connection, String> {
+    let conn = Connection::open_with_flags(db_path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| format!("打开数据库失败 ({}): {}", db_path.display(), e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("设置数据库忙等待超时失败: {}", e))?;
+    Ok(conn)
+}
+
+fn is_busy(error: &rusqlite::Error) -> bool {
+    matches!(
+        error,
+        rusqlite::Error::SqliteFailure(e, _)
+            if matches!(e.code, ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked)
+    )
+}
+
+/// 写入前检查数据库（数据库不存在时直接通过）
+///
+/// 1. 有其他进程打开了数据库文件时拒绝写入，并报告占用的进程
+/// 2. 在 [`BUSY_TIMEOUT`] 内重试获取写锁，获取写锁时 SQLite 会自动回滚残留的 -journal
+/// 3. 存在 -wal 文件时将其合并回数据库，无法完全合并时拒绝写入
+pub fn check(db_path: &Path) -> Result<PreflightReport, String> {
+    let mut report = PreflightReport {
+        db_path: db_path.to_path_buf(),
+        ..Default::default()
+    };
+
+    if !db_path.exists() {
+        return Ok(report);
+    }
+
+    let holders = find_holders(db_path);
+    if !holders.is_empty() {
+        return Err(format!(
+            "数据库正在被其他进程使用，已拒绝写入 ({}): {}。请先关闭 Antigravity",
+            db_path.display(),
+            describe_holders(&holders)
+        ));
+    }
+
+    let conn = open_with_busy_timeout(db_path)?;
+
+    let journal = sidecar(db_path, "-journal");
+    let had_journal = is_non_empty(&journal);
+
+    if let Err(e) = conn.execute_batch("BEGIN IMMEDIATE; ROLLBACK;") {
+        return Err(if is_busy(&e) {
+            format!(
+                "数据库在 {} 秒内仍被锁定，已拒绝写入 ({})",
+                BUSY_TIMEOUT.as_secs(),
+                db_path.display()
+            )
+        } else {
+            format!("获取数据库写锁失败 ({}): {}", db_path.display(), e)
+        });
+    }
+
+    if had_journal {
+        tracing::info!(
+            "  🩹 检测到残留的回滚日志，已由 SQLite 自动恢复: {}",
+            journal.display()
+        );
+        report.journal_recovered = true;
+    }
+
+    let wal = sidecar(db_path, "-wal");
+    if is_non_empty(&wal) {
+        let journal_mode: String = conn
+            .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+            .map_err(|e| format!("读取数据库日志模式失败: {}", e))?;
+
+        if journal_mode.eq_ignore_ascii_case("wal") {
+            let (busy, log_frames, checkpointed): (i64, i64, i64) = conn
+                .query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .map_err(|e| format!("合并 WAL 文件失败: {}", e))?;
+
+            if busy != 0 || checkpointed < log_frames {
+                return Err(format!(
+                    "WAL 文件无法完全合并（{}/{} 页），可能仍有进程在使用数据库，已拒绝写入 ({})",
+                    checkpointed.max(0),
+                    log_frames.max(0),
+                    wal.display()
+                ));
+            }
+
+            tracing::info!("  🧩 已合并 WAL 文件: {}", wal.display());
+            report.wal_checkpointed = true;
+        }
+    }
+
+    Ok(report)
+}
+
+/// 检查通过后打开用于写入的连接（已设置忙等待超时）
+pub fn open_for_write(db_path: &Path) -> Result<Connection, String> {
+    check(db_path)?;
+    open_with_busy_timeout(db_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn missing_database_passes() {
+        let dir = test_support::temp_dir("preflight-missing");
+        let report = check(&dir.join("state.vscdb")).unwrap();
+        assert!(!report.journal_recovered && !report.wal_checkpointed);
+    }
+
+    #[test]
+    fn checkpoints_pending_wal_frames() {
+        let dir = test_support::temp_dir("preflight-wal");
+        let db_path = dir.join("state.vscdb");
+        // 本进程的连接不算占用，保持连接打开使 WAL 文件留在磁盘上
+        let conn = test_support::create_state_db(&db_path, &[]);
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA wal_autocheckpoint = 0;")
+            .unwrap();
+        conn.execute("INSERT INTO ItemTable VALUES ('key', 'value')", [])
+            .unwrap();
+        assert!(is_non_empty(&sidecar(&db_path, "-wal")));
+
+        let report = check(&db_path).unwrap();
+        assert!(report.wal_checkpointed);
+        assert!(!is_non_empty(&sidecar(&db_path, "-wal")));
+    }
+
+    #[test]
+    fn recovers_hot_journal_before_writing() {
+        let dir = test_support::temp_dir("preflight-journal");
+        let source = dir.join("source.vscdb");
+        let db_path = dir.join("state.vscdb");
+        drop(test_support::create_state_db(
+            &source,
+            &[("key", "committed")],
+        ));
+
+        // 事务进行中（页面已溢出到数据库文件）时复制数据库和回滚日志，模拟写入中途崩溃
+        let conn = Connection::open(&source).unwrap();
+        conn.execute_batch(
+            "PRAGMA cache_size = 1; BEGIN;
+             UPDATE ItemTable SET value = 'uncommitted';
+             WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 2000)
+             INSERT INTO ItemTable SELECT 'filler' || i, hex(randomblob(200)) FROM n;",
+        )
+        .unwrap();
+        fs::copy(&source, &db_path).unwrap();
+        fs::copy(sidecar(&source, "-journal"), sidecar(&db_path, "-journal")).unwrap();
+        drop(conn);
+
+        let report = check(&db_path).unwrap();
+        assert!(report.journal_recovered);
+        assert_eq!(
+            test_support::item_rows(&db_path),
+            vec![("key".to_string(), "committed".to_string())]
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn refuses_database_opened_by_another_process() {
+        let dir = test_support::temp_dir("preflight-holder");
+        let db_path = dir.join("state.vscdb");
+        drop(test_support::create_state_db(&db_path, &[]));
+
+        let mut holder = std::process::Command::new("sleep")
+            .arg("30")
+            .stdin(fs::File::open(&db_path).unwrap())
+            .spawn()
+            .unwrap();
+        let result = check(&db_path);
+        let _ = holder.kill();
+        let _ = holder.wait();
+
+        let error = result.unwrap_err();
+        assert!(
+            error.contains(&format!("PID: {}", holder.id())),
+            "{}",
+            error
+        );
+    }
+
+    #[test]
+    fn refuses_database_locked_by_another_connection() {
+        let dir = test_support::temp_dir("preflight-locked");
+        let db_path = dir.join("state.vscdb");
+        let conn = test_support::create_state_db(&db_path, &[]);
+        conn.execute_batch("BEGIN IMMEDIATE").unwrap();
+
+        let error = check(&db_path).unwrap_err();
+        assert!(error.contains("仍被锁定"), "{}", error);
+    }
+}
@@ -0,0 +1,578 @@
+// 完整数据库快照模块
+// 使用 SQLite 在线备份 API 复制完整的 state.vscdb 和 state.vscdb.backup（Antigravity 运行时也能得到一致的副本），
+// 与按字段的 JSON 账户备份互补：未列入备份字段的数据也能完整还原
+//
+// 快照存放在配置目录下的 db-snapshots/<id>/，ID 与账户备份版本使用相同的时间戳格式，
+// 列出、按数量清理和按保留策略清理的方式也与账户备份一致
+// 快照包含登录凭据，保险库启用时数据库文件与账户备份一样加密保存
+
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::account_vault;
+use crate::audit_log::{self, AuditAction, AuditEntry};
+use crate::backup_history::{self, RetentionPolicy};
+use crate::constants::db_snapshot;
+use crate::db_preflight;
+use crate::path_utils::AppPaths;
+use crate::state_db::StateDb;
+
+/// 主数据库在快照目录中的文件名
+const MAIN_DB_FILE: &str = "state.vscdb";
+
+/// 备份数据库在快照目录中的文件名
+const BACKUP_DB_FILE: &str = "state.vscdb.backup";
+
+/// 写入中的快照目录后缀（写入完成后重命名，列表中忽略）
+const PARTIAL_SUFFIX: &str = ".partial";
+
+/// 恢复时加密数据库解密出的临时文件后缀（读取完成后删除）
+const PLAIN_SUFFIX: &str = ".plain";
+
+/// 完整数据库快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbSnapshot {
+    /// 快照 ID（时间戳，按字典序排序即为时间顺序）
+    pub id: String,
+    /// 创建时间（RFC3339）
+    pub created_at: String,
+    /// 备注（如 "恢复快照前自动保存"）
+    pub label: Option<String>,
+    /// 快照时登录的账户
+    pub account: Option<String>,
+    /// 源 state.vscdb 路径
+    pub source: PathBuf,
+    /// 是否包含 state.vscdb.backup
+    pub has_backup_db: bool,
+    /// 快照文件总大小（字节）
+    pub size_bytes: u64,
+    /// 快照目录
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+impl DbSnapshot {
+    /// 快照中的 state.vscdb
+    pub fn main_db(&self) -> PathBuf {
+        self.dir.join(MAIN_DB_FILE)
+    }
+
+    /// 快照中的 state.vscdb.backup（快照不包含时为 None）
+    pub fn backup_db(&self) -> Option<PathBuf> {
+        self.has_backup_db.then(|| self.dir.join(BACKUP_DB_FILE))
+    }
+}
+
+/// 是否为快照中的数据库文件（保险库启用时加密）
+pub fn is_database_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name == MAIN_DB_FILE || name == BACKUP_DB_FILE)
+}
+
+/// 快照存放目录
+pub fn snapshot_root() -> Result<PathBuf, String> {
+    AppPaths::config_dir()
+        .map(|dir| dir.join(db_snapshot::DIR_NAME))
+        .ok_or_else(|| "无法获取配置目录".to_string())
+}
+
+/// 使用在线备份 API 将 `source` 的全部页面复制到 `dest`
+///
+/// 复制分步进行，每一步之间释放读锁；期间源数据库被修改时 SQLite 会自动重新开始，
+/// 因此得到的总是某一时刻的一致副本
+fn copy_database(source: &Connection, dest: &mut Connection) -> Result<(), String> {
+    let backup = Backup::new(source, dest).map_err(|e| format!("初始化在线备份失败: {}", e))?;
+    backup
+        .run_to_completion(
+            db_snapshot::PAGES_PER_STEP,
+            Duration::from_millis(db_snapshot::STEP_PAUSE_MS),
+            None,
+        )
+        .map_err(|e| format!("复制数据库失败: {}", e))
+}
+
+/// 将数据库文件完整复制到快照目录中的新文件（保险库启用时随后原地加密）
+fn snapshot_file(source: &Path, dest: &Path) -> Result<u64, String> {
+    let source_conn = db_preflight::open_read_only(source)?;
+    let mut dest_conn = Connection::open(dest)
+        .map_err(|e| format!("创建快照文件失败 ({}): {}", dest.display(), e))?;
+    copy_database(&source_conn, &mut dest_conn)?;
+    drop(dest_conn);
+    account_vault::seal_file(dest).map_err(|e| format!("加密快照文件失败: {}", e))?;
+
+    fs::metadata(dest)
+        .map(|metadata| metadata.len())
+        .map_err(|e| format!("读取快照文件失败: {}", e))
+}
+
+/// 保存 state.vscdb（及同目录下的 state.vscdb.backup）的完整快照（记录到审计日志）
+///
+/// Antigravity 运行时也可以安全调用
+pub fn create_snapshot(app_data: &Path, label: Option<&str>) -> Result<DbSnapshot, String> {
+    let account = StateDb::new(app_data).current_email().ok().flatten();
+    let result = create_snapshot_in(&snapshot_root()?, app_data, account.clone(), label);
+
+    let mut entry = AuditEntry::new(AuditAction::Snapshot).from_account(account.as_deref());
+    if let Ok(snapshot) = &result {
+        entry = entry.detail(match &snapshot.label {
+            Some(label) => format!("快照: {} ({})", snapshot.id, label),
+            None => format!("快照: {}", snapshot.id),
+        });
+    }
+    audit_log::record(entry.result(&result));
+    result
+}
+
+fn create_snapshot_in(
+    root: &Path,
+    app_data: &Path,
+    account: Option<String>,
+    label: Option<&str>,
+) -> Result<DbSnapshot, String> {
+    if !app_data.exists() {
+        return Err(format!(
+            "Antigravity 状态数据库不存在: {}",
+            app_data.display()
+        ));
+    }
+
+    let id = backup_history::new_revision_id();
+    let dir = root.join(&id);
+    if dir.exists() {
+        return Err(format!("快照 {} 已存在", id));
+    }
+
+    tracing::info!("📸 开始保存完整数据库快照: {}", id);
+
+    // 先写入临时目录，全部完成后再重命名，避免留下不完整的快照
+    let partial = root.join(format!("{}{}", id, PARTIAL_SUFFIX));
+    fs::create_dir_all(&partial).map_err(|e| format!("创建快照目录失败: {}", e))?;
+
+    let result = write_snapshot(&partial, app_data, id, account, label);
+    let mut snapshot = match result {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&partial);
+            return Err(e);
+        }
+    };
+
+    fs::rename(&partial, &dir).map_err(|e| {
+        let _ = fs::remove_dir_all(&partial);
+        format!("保存快照失败: {}", e)
+    })?;
+    snapshot.dir = dir;
+
+    tracing::info!(
+        "✅ 完整数据库快照已保存: {} ({} 字节)",
+        snapshot.id,
+        snapshot.size_bytes
+    );
+    Ok(snapshot)
+}
+
+fn write_snapshot(
+    dir: &Path,
+    app_data: &Path,
+    id: String,
+    account: Option<String>,
+    label: Option<&str>,
+) -> Result<DbSnapshot, String> {
+    tracing::info!("  📊 复制 state.vscdb");
+    let mut size_bytes = snapshot_file(app_data, &dir.join(MAIN_DB_FILE))?;
+
+    let backup_db = app_data.with_extension("vscdb.backup");
+    let has_backup_db = backup_db.exists();
+    if has_backup_db {
+        tracing::info!("  💾 复制 state.vscdb.backup");
+        size_bytes += snapshot_file(&backup_db, &dir.join(BACKUP_DB_FILE))?;
+    } else {
+        tracing::info!("  ℹ️ 备份数据库不存在，跳过");
+    }
+
+    let snapshot = DbSnapshot {
+        id,
+        created_at: chrono::Local::now().to_rfc3339(),
+        label: label
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .map(String::from),
+        account,
+        source: app_data.to_path_buf(),
+        has_backup_db,
+        size_bytes,
+        dir: dir.to_path_buf(),
+    };
+
+    let content = serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| format!("序列化快照信息失败: {}", e))?;
+    fs::write(dir.join(db_snapshot::META_FILE), content)
+        .map_err(|e| format!("写入快照信息失败: {}", e))?;
+
+    Ok(snapshot)
+}
+
+fn read_snapshot(dir: &Path) -> Result<DbSnapshot, String> {
+    let content = fs::read_to_string(dir.join(db_snapshot::META_FILE))
+        .map_err(|e| format!("读取快照信息失败: {}", e))?;
+    let mut snapshot: DbSnapshot =
+        serde_json::from_str(&content).map_err(|e| format!("解析快照信息失败: {}", e))?;
+
+    if !dir.join(MAIN_DB_FILE).is_file() {
+        return Err("快照中缺少 state.vscdb".to_string());
+    }
+    snapshot.dir = dir.to_path_buf();
+    Ok(snapshot)
+}
+
+/// 列出所有完整数据库快照（最新的在前）
+pub fn list_snapshots() -> Result<Vec<DbSnapshot>, String> {
+    let root = snapshot_root()?;
+    let mut snapshots = Vec::new();
+
+    if !root.exists() {
+        return Ok(snapshots);
+    }
+
+    for entry in fs::read_dir(&root).map_err(|e| format!("读取快照目录失败: {}", e))? {
+        let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+
+        if !path.is_dir() || name.ends_with(PARTIAL_SUFFIX) {
+            continue;
+        }
+
+        match read_snapshot(&path) {
+            Ok(snapshot) => snapshots.push(snapshot),
+            Err(e) => tracing::warn!("⚠️ 跳过损坏的快照 {}: {}", name, e),
+        }
+    }
+
+    snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+    Ok(snapshots)
+}
+
+/// 按 ID 查找快照
+pub fn find_snapshot(id: &str) -> Result<DbSnapshot, String> {
+    list_snapshots()?
+        .into_iter()
+        .find(|snapshot| snapshot.id == id)
+        .ok_or_else(|| format!("快照不存在: {}", id))
+}
+
+/// 删除指定快照
+pub fn delete_snapshot(id: &str) -> Result<(), String> {
+    let snapshot = find_snapshot(id)?;
+    fs::remove_dir_all(&snapshot.dir).map_err(|e| format!("删除快照 {} 失败: {}", id, e))?;
+    tracing::info!("🗑️ 已删除完整数据库快照: {}", id);
+    Ok(())
+}
+
+/// 清理旧快照，只保留最新的 `keep` 个
+///
+/// # 返回
+/// - `Ok(removed)`: 被删除的快照 ID 列表
+pub fn prune_snapshots(keep: usize) -> Result<Vec<String>, String> {
+    let mut removed = Vec::new();
+
+    for snapshot in list_snapshots()?.into_iter().skip(keep) {
+        fs::remove_dir_all(&snapshot.dir)
+            .map_err(|e| format!("删除快照 {} 失败: {}", snapshot.id, e))?;
+        removed.push(snapshot.id);
+    }
+
+    if !removed.is_empty() {
+        tracing::info!("  🧹 清理了 {} 个旧的完整数据库快照", removed.len());
+    }
+
+    Ok(removed)
+}
+
+/// 按保留策略清理旧快照（与账户备份使用相同的策略，最新的快照总是保留）
+///
+/// # 返回
+/// - `Ok(removed)`: 被删除的快照 ID 列表
+pub fn apply_retention(policy: &RetentionPolicy) -> Result<Vec<String>, String> {
+    let snapshots = list_snapshots()?;
+    let times: Vec<(&str, chrono::NaiveDateTime)> = snapshots
+        .iter()
+        .filter_map(|snapshot| {
+            backup_history::parse_revision_id(&snapshot.id).map(|time| (snapshot.id.as_str(), time))
+        })
+        .collect();
+    let keep = policy.retained_ids(&times);
+
+    let mut removed = Vec::new();
+    for snapshot in &snapshots {
+        // ID 不是时间戳的快照无法归入时间段，保留不动
+        if keep.contains(&snapshot.id) || backup_history::parse_revision_id(&snapshot.id).is_none()
+        {
+            continue;
+        }
+        fs::remove_dir_all(&snapshot.dir)
+            .map_err(|e| format!("删除快照 {} 失败: {}", snapshot.id, e))?;
+        removed.push(snapshot.id.clone());
+    }
+
+    if !removed.is_empty() {
+        tracing::info!(
+            "  🧹 按保留策略清理了 {} 个旧的完整数据库快照",
+            removed.len()
+        );
+    }
+
+    Ok(removed)
+}
+
+/// 用快照覆盖 state.vscdb（及 state.vscdb.backup），记录到审计日志
+///
+/// 覆盖前会先为当前数据库保存一个快照，恢复错误时可以再恢复回去。
+/// 写入前检查数据库是否被占用，调用方应先关闭 Antigravity
+pub fn restore_snapshot(app_data: &Path, id: &str) -> Result<DbSnapshot, String> {
+    let from_account = StateDb::new(app_data).current_email().ok().flatten();
+    let snapshot = find_snapshot(id);
+    let result =
+        snapshot.and_then(|snapshot| restore_snapshot_to(app_data, &snapshot).map(|_| snapshot));
+
+    let to_account = result
+        .as_ref()
+        .ok()
+        .and_then(|snapshot| snapshot.account.clone());
+    audit_log::record(
+        AuditEntry::new(AuditAction::RestoreSnapshot)
+            .from_account(from_account.as_deref())
+            .to_account(to_account.as_deref())
+            .detail(format!("快照: {}", id))
+            .result(&result),
+    );
+    result
+}
+
+fn restore_snapshot_to(app_data: &Path, snapshot: &DbSnapshot) -> Result<(), String> {
+    tracing::info!("⏪ 开始恢复完整数据库快照: {}", snapshot.id);
+
+    // 写入前检查两个数据库，任意一个被占用都不做任何修改
+    let mut main_conn = db_preflight::open_for_write(app_data)?;
+    let backup_db = app_data.with_extension("vscdb.backup");
+    let mut backup_conn = match snapshot.backup_db() {
+        Some(_) => Some(db_preflight::open_for_write(&backup_db)?),
+        None => None,
+    };
+
+    // 覆盖前保存当前状态
+    tracing::info!("  📸 保存恢复前的数据库快照");
+    let safety = create_snapshot(app_data, Some("恢复快照前自动保存"))
+        .map_err(|e| format!("保存恢复前快照失败，已取消恢复: {}", e))?;
+    tracing::info!("  ✅ 恢复前快照: {}", safety.id);
+
+    // 两个文件无法在同一事务中覆盖，任意一个失败都用恢复前快照把两个文件还原
+    if let Err(e) = copy_snapshot(snapshot, &mut main_conn, backup_conn.as_mut()) {
+        tracing::error!("❌ 恢复快照失败，使用恢复前快照还原: {}", e);
+        return Err(
+            match copy_snapshot(&safety, &mut main_conn, backup_conn.as_mut()) {
+                Ok(()) => format!(
+                    "恢复快照失败，数据库已还原为恢复前的状态（快照 {}）: {}",
+                    safety.id, e
+                ),
+                Err(rollback_error) => format!(
+                    "恢复快照失败: {}；还原为恢复前的状态也失败: {}，请手动恢复快照 {}",
+                    e, rollback_error, safety.id
+                ),
+            },
+        );
+    }
+
+    tracing::info!("✅ 完整数据库快照已恢复: {}", snapshot.id);
+    Ok(())
+}
+
+/// 将快照中的数据库复制到已打开的 state.vscdb（及 state.vscdb.backup）
+fn copy_snapshot(
+    snapshot: &DbSnapshot,
+    main_conn: &mut Connection,
+    backup_conn: Option<&mut Connection>,
+) -> Result<(), String> {
+    tracing::info!("  📊 恢复 state.vscdb");
+    read_snapshot_database(&snapshot.main_db(), |source| {
+        copy_database(source, main_conn)
+    })?;
+
+    match (snapshot.backup_db(), backup_conn) {
+        (Some(source), Some(conn)) => {
+            tracing::info!("  💾 恢复 state.vscdb.backup");
+            read_snapshot_database(&source, |source| copy_database(source, conn))
+        }
+        _ => {
+            tracing::info!("  ℹ️ 快照不包含备份数据库，state.vscdb.backup 保持不变");
+            Ok(())
+        }
+    }
+}
+
+/// 以只读方式打开快照中的数据库文件
+///
+/// 保险库加密的文件先解密到同目录下的临时文件，读取完成后立即删除
+fn read_snapshot_database<T>(
+    path: &Path,
+    read: impl FnOnce(&Connection) -> Result<T, String>,
+) -> Result<T, String> {
+    if !account_vault::is_sealed(path)? {
+        return read(&db_preflight::open_read_only(path)?);
+    }
+
+    let mut plain = path.as_os_str().to_owned();
+    plain.push(PLAIN_SUFFIX);
+    let plain = PathBuf::from(plain);
+    account_vault::unseal_file_to(path, &plain).map_err(|e| format!("解密快照文件失败: {}", e))?;
+    let result = db_preflight::open_read_only(&plain).and_then(|conn| read(&conn));
+    let _ = fs::remove_file(&plain);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+
+    #[test]
+    fn failed_copy_restores_both_databases_from_safety_snapshot() {
+        let _vault = test_support::lock_vault();
+        let dir = test_support::temp_dir("db-snapshot-rollback");
+        let app_data = dir.join("state.vscdb");
+        let backup_db = dir.join("state.vscdb.backup");
+        drop(test_support::create_state_db(&app_data, &[("key", "old")]));
+        drop(test_support::create_state_db(
+            &backup_db,
+            &[("key", "old-backup")],
+        ));
+
+        // 主库可以正常复制，备份库文件损坏，复制到一半失败
+        let snapshot_dir = dir.join("snapshot");
+        fs::create_dir_all(&snapshot_dir).unwrap();
+        drop(test_support::create_state_db(
+            &snapshot_dir.join(MAIN_DB_FILE),
+            &[("key", "new")],
+        ));
+        fs::write(snapshot_dir.join(BACKUP_DB_FILE), vec![0x5a; 8192]).unwrap();
+        let snapshot = DbSnapshot {
+            id: "broken".to_string(),
+            created_at: String::new(),
+            label: None,
+            account: None,
+            source: app_data.clone(),
+            has_backup_db: true,
+            size_bytes: 0,
+            dir: snapshot_dir,
+        };
+
+        let error = restore_snapshot_to(&app_data, &snapshot).unwrap_err();
+        assert!(error.contains("已还原为恢复前的状态"), "{}", error);
+        assert_eq!(
+            test_support::item_rows(&app_data),
+            vec![("key".to_string(), "old".to_string())]
+        );
+        assert_eq!(
+            test_support::item_rows(&backup_db),
+            vec![("key".to_string(), "old-backup".to_string())]
+        );
+    }
+
+    #[test]
+    fn snapshots_are_encrypted_while_the_vault_is_enabled() {
+        let _vault = test_support::lock_vault();
+        let dir = test_support::temp_dir("db-snapshot-vault");
+        let app_data = dir.join("state.vscdb");
+        drop(test_support::create_state_db(&app_data, &[("key", "old")]));
+
+        // 先完成所有操作并关闭保险库，再检查结果，避免断言失败时保险库保持启用
+        account_vault::enable("master").unwrap();
+        let result = create_snapshot_in(&dir.join("snapshots"), &app_data, None, None).and_then(
+            |snapshot| {
+                let sealed = account_vault::is_sealed(&snapshot.main_db())?;
+                drop(test_support::create_state_db(&app_data, &[("key", "new")]));
+                restore_snapshot_to(&app_data, &snapshot)?;
+                let safety = list_snapshots()?
+                    .into_iter()
+                    .next()
+                    .ok_or("没有恢复前快照")?;
+                Ok((snapshot, sealed, safety))
+            },
+        );
+        account_vault::disable("master").unwrap();
+
+        let (snapshot, sealed, safety) = result.unwrap();
+        assert!(sealed);
+        assert!(!snapshot
+            .dir
+            .join(format!("{}{}", MAIN_DB_FILE, PLAIN_SUFFIX))
+            .exists());
+        assert_eq!(
+            test_support::item_rows(&app_data),
+            vec![("key".to_string(), "old".to_string())]
+        );
+        // 关闭保险库时快照目录中的数据库文件也被解密
+        assert!(!account_vault::is_sealed(&safety.main_db()).unwrap());
+        assert_eq!(
+            test_support::item_rows(&safety.main_db()),
+            vec![("key".to_string(), "new".to_string())]
+        );
+    }
+
+    #[test]
+    fn snapshot_restores_both_databases_and_can_be_pruned() {
+        let _vault = test_support::lock_vault();
+        let dir = test_support::temp_dir("db-snapshot-restore");
+        let app_data = dir.join("state.vscdb");
+        let backup_db = dir.join("state.vscdb.backup");
+        drop(test_support::create_state_db(&app_data, &[("key", "main")]));
+        drop(test_support::create_state_db(
+            &backup_db,
+            &[("key", "backup")],
+        ));
+
+        let snapshot = create_snapshot_in(
+            &dir.join("snapshots"),
+            &app_data,
+            Some("a@example.com".to_string()),
+            Some("  "),
+        )
+        .unwrap();
+        assert!(snapshot.has_backup_db);
+        assert_eq!(snapshot.label, None);
+        assert_eq!(read_snapshot(&snapshot.dir).unwrap().id, snapshot.id);
+
+        drop(test_support::create_state_db(
+            &app_data,
+            &[("key", "changed")],
+        ));
+        drop(test_support::create_state_db(
+            &backup_db,
+            &[("key", "changed")],
+        ));
+        restore_snapshot_to(&app_data, &snapshot).unwrap();
+        assert_eq!(
+            test_support::item_rows(&app_data),
+            vec![("key".to_string(), "main".to_string())]
+        );
+        assert_eq!(
+            test_support::item_rows(&backup_db),
+            vec![("key".to_string(), "backup".to_string())]
+        );
+
+        // 恢复前自动保存的快照包含被覆盖的数据
+        let safety = list_snapshots().unwrap().into_iter().next().unwrap();
+        assert_eq!(safety.label.as_deref(), Some("恢复快照前自动保存"));
+        assert_eq!(
+            test_support::item_rows(&safety.main_db()),
+            vec![("key".to_string(), "changed".to_string())]
+        );
+
+        assert!(!prune_snapshots(0).unwrap().is_empty());
+        assert!(list_snapshots().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+// 自定义备份字段模块
+// 除内置字段（constants::database）外，用户可以在设置中添加额外的精确字段或匹配模式（GLOB / LIKE），
+// Antigravity 新增按账户存储的字段时无需等待新版本即可备份和恢复
+//
+// 备份时把匹配到的字段及其设置写入备份文件（extra_keys），恢复只依据备份文件，不依赖当前配置
+
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::constants::database;
+use crate::data_category::DataCategory;
+use crate::path_utils::AppPaths;
+
+/// 配置文件名（位于配置目录）
+const CONFIG_FILE_NAME: &str = "extra_keys.json";
+
+/// 备份文件中记录自定义字段的元数据键
+pub const BACKUP_FIELD: &str = "extra_keys";
+
+/// 备份文件中的元数据键，数据库中同名的字段不能作为自定义字段
+const BACKUP_METADATA_KEYS: &[&str] = &[
+    "account_email",
+    "backup_time",
+    "notification_keys",
+    "data_categories",
+    BACKUP_FIELD,
+];
+
+/// 匹配方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KeyMatch {
+    /// 精确匹配字段名
+    #[default]
+    Exact,
+    /// SQLite GLOB 模式（区分大小写，`*` 和 `?` 通配）
+    Glob,
+    /// SQL LIKE 模式（`%` 和 `_` 通配）
+    Like,
+}
+
+impl KeyMatch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyMatch::Exact => "exact",
+            KeyMatch::Glob => "glob",
+            KeyMatch::Like => "like",
+        }
+    }
+}
+
+impl FromStr for KeyMatch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "exact" => Ok(KeyMatch::Exact),
+            "glob" => Ok(KeyMatch::Glob),
+            "like" => Ok(KeyMatch::Like),
+            _ => Err(format!(
+                "不支持的匹配方式: {}（支持 exact、glob 或 like）",
+                s
+            )),
+        }
+    }
+}
+
+/// 一条自定义字段设置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtraKey {
+    /// 字段名或匹配模式
+    pub pattern: String,
+    #[serde(default)]
+    pub kind: KeyMatch,
+    /// 恢复时是否在 __$__targetStorageMarker 中登记（与内置字段一样参与 Marker 合并）
+    #[serde(default)]
+    pub marker: bool,
+    /// 所属数据类别（按类别备份和恢复时使用）
+    #[serde(default = "default_category")]
+    pub category: DataCategory,
+}
+
+fn default_category() -> DataCategory {
+    DataCategory::Auth
+}
+
+impl ExtraKey {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.pattern.trim().is_empty() {
+            return Err("自定义字段不能为空".to_string());
+        }
+        if self.kind == KeyMatch::Exact && is_reserved(&self.pattern) {
+            return Err(format!("{} 是内置字段，无需添加", self.pattern));
+        }
+        Ok(())
+    }
+
+    /// 在数据库中查找匹配的字段（排除内置字段）
+    fn matching_keys(&self, conn: &Connection) -> Result<Vec<String>, String> {
+        let sql = match self.kind {
+            KeyMatch::Exact => "SELECT key FROM ItemTable WHERE key = ?",
+            KeyMatch::Glob => "SELECT key FROM ItemTable WHERE key GLOB ? ORDER BY key",
+            KeyMatch::Like => "SELECT key FROM ItemTable WHERE key LIKE ? ORDER BY key",
+        };
+
+        let keys = conn
+            .prepare(sql)
+            .and_then(|mut stmt| {
+                stmt.query_map([&self.pattern], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|e| format!("查询自定义字段失败 ({}): {}", self.pattern, e))?;
+
+        Ok(keys.into_iter().filter(|key| !is_reserved(key)).collect())
+    }
+}
+
+/// 由内置逻辑处理的字段（内置字段、Marker、通知字段和备份元数据）
+fn is_reserved(key: &str) -> bool {
+    database::ALL_KEYS.contains(&key)
+        || key == database::TARGET_STORAGE_MARKER
+        || key == database::ANALYTICS_LAST_UPLOAD_TIME
+        || key.starts_with(database::NOTIFICATION_PREFIX)
+        || BACKUP_METADATA_KEYS.contains(&key)
+}
+
+/// 备份文件中记录的一个自定义字段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackedUpKey {
+    pub key: String,
+    /// 匹配到该字段的设置
+    pub pattern: String,
+    pub marker: bool,
+    pub category: DataCategory,
+}
+
+fn config_file() -> Result<PathBuf, String> {
+    AppPaths::config_dir()
+        .map(|dir| dir.join(CONFIG_FILE_NAME))
+        .ok_or_else(|| "无法获取配置目录".to_string())
+}
+
+/// 读取自定义字段设置（未配置时为空）
+pub fn load() -> Result<Vec<ExtraKey>, String> {
+    let path = config_file()?;
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("读取自定义字段设置失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析自定义字段设置失败: {}", e))
+}
+
+/// 保存自定义字段设置（保存前逐条校验，重复的设置只保留一条）
+pub fn save(keys: &[ExtraKey]) -> Result<(), String> {
+    let mut unique: Vec<ExtraKey> = Vec::new();
+    for key in keys {
+        key.validate()?;
+        if !unique
+            .iter()
+            .any(|k| k.pattern == key.pattern && k.kind == key.kind)
+        {
+            unique.push(key.clone());
+        }
+    }
+
+    let path = config_file()?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&unique)
+        .map_err(|e| format!("序列化自定义字段设置失败: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("写入自定义字段设置失败: {}", e))?;
+
+    tracing::info!("✅ 已保存 {} 条自定义备份字段", unique.len());
+    Ok(())
+}
+
+/// 按设置在数据库中查找选定类别的自定义字段（同一字段只记录第一条匹配的设置）
+pub fn resolve(
+    conn: &Connection,
+    extra_keys: &[ExtraKey],
+    categories: &[DataCategory],
+) -> Result<Vec<BackedUpKey>, String> {
+    let mut resolved: Vec<BackedUpKey> = Vec::new();
+
+    for extra in extra_keys
+        .iter()
+        .filter(|extra| categories.contains(&extra.category))
+    {
+        for key in extra.matching_keys(conn)? {
+            if resolved.iter().any(|k| k.key == key) {
+                continue;
+            }
+            resolved.push(BackedUpKey {
+                key,
+                pattern: extra.pattern.clone(),
+                marker: extra.marker,
+                category: extra.category,
+            });
+        }
+    }
+
+    Ok(resolved)
+}
+
+/// 读取备份文件中记录的自定义字段（旧版备份没有该字段）
+pub fn from_backup(backup_data: &Value) -> Vec<BackedUpKey> {
+    backup_data
+        .get(BACKUP_FIELD)
+        .and_then(|keys| serde_json::from_value(keys.clone()).ok())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item_table(keys: &[&str]) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("CREATE TABLE ItemTable (key TEXT PRIMARY KEY, value BLOB)")
+            .unwrap();
+        for key in keys {
+            conn.execute("INSERT INTO ItemTable (key, value) VALUES (?, '1')", [key])
+                .unwrap();
+        }
+        conn
+    }
+
+    fn extra_key(pattern: &str, kind: KeyMatch) -> ExtraKey {
+        ExtraKey {
+            pattern: pattern.to_string(),
+            kind,
+            marker: false,
+            category: DataCategory::Auth,
+        }
+    }
+
+    #[test]
+    fn matching_keys_by_kind() {
+        let conn = item_table(&[
+            "antigravity.account.id",
+            "antigravity.account.plan",
+            "antigravity.Account.legacy",
+            "antigravity.profileUrl",
+            "other.key",
+        ]);
+
+        assert_eq!(
+            extra_key("other.key", KeyMatch::Exact)
+                .matching_keys(&conn)
+                .unwrap(),
+            vec!["other.key"]
+        );
+        assert!(extra_key("missing", KeyMatch::Exact)
+            .matching_keys(&conn)
+            .unwrap()
+            .is_empty());
+        // GLOB 区分大小写
+        assert_eq!(
+            extra_key("antigravity.account.*", KeyMatch::Glob)
+                .matching_keys(&conn)
+                .unwrap(),
+            vec!["antigravity.account.id", "antigravity.account.plan"]
+        );
+        // LIKE 不区分 ASCII 大小写
+        assert_eq!(
+            extra_key("antigravity.account.%", KeyMatch::Like)
+                .matching_keys(&conn)
+                .unwrap(),
+            vec![
+                "antigravity.Account.legacy",
+                "antigravity.account.id",
+                "antigravity.account.plan"
+            ]
+        );
+    }
+
+    #[test]
+    fn matching_keys_excludes_reserved_keys() {
+        let conn = item_table(&[
+            database::PROFILE_URL,
+            database::TARGET_STORAGE_MARKER,
+            "antigravity.notification.welcome",
+            "antigravity.custom",
+        ]);
+
+        assert_eq!(
+            extra_key("antigravity.*", KeyMatch::Glob)
+                .matching_keys(&conn)
+                .unwrap(),
+            vec!["antigravity.custom"]
+        );
+    }
+}
@@ -0,0 +1,75 @@
+//! Antigravity Agent 核心库
+//!
+//! 不依赖 Tauri 的账户备份、恢复、切换和进程控制逻辑，供桌面应用和命令行工具共用。
+//!
+//! 主要类型：
+//! - [`AccountStore`] — 账户备份存储（多版本备份、保险库读写、导入导出）
+//! - [`StateDb`] — Antigravity 状态数据库（读取登录信息、提取备份、恢复、清除）
+//! - [`AntigravityProcess`] — Antigravity 进程控制（检测、关闭、启动）
+//!
+//! [`account_ops`] 将以上类型组合成完整的账户操作流程（切换、撤销切换、注销）。
+
+/// 账户元数据模块
+pub mod account_metadata;
+/// 账户操作流程模块
+pub mod account_ops;
+/// 账户注册表模块
+pub mod account_registry;
+/// 账户备份存储模块
+pub mod account_store;
+/// 账户备份保险库模块
+pub mod account_vault;
+/// Antigravity 用户数据备份模块
+pub mod antigravity_backup;
+/// Antigravity 用户数据清除模块
+pub mod antigravity_cleanup;
+/// Antigravity 路径配置模块
+pub mod antigravity_path_config;
+/// Antigravity 用户数据恢复模块
+pub mod antigravity_restore;
+/// Antigravity 启动模块
+pub mod antigravity_starter;
+/// 审计日志模块
+pub mod audit_log;
+/// 账户备份历史版本模块
+pub mod backup_history;
+/// 定时备份计划模块
+pub mod backup_schedule;
+/// 常量定义模块
+pub mod constants;
+/// 导出文件与保险库加密模块
+pub mod crypto;
+/// 数据类别模块
+pub mod data_category;
+/// 数据库健康检查模块
+pub mod db_health;
+/// 数据库写入前检查模块
+pub mod db_preflight;
+/// 完整数据库快照模块
+pub mod db_snapshot;
+/// 自定义备份字段模块
+pub mod extra_keys;
+/// 应用路径模块
+pub mod path_utils;
+/// 平台相关工具模块
+pub mod platform_utils;
+/// Antigravity 进程控制模块
+pub mod process;
+/// 状态数据库变化检测模块
+pub mod state_changes;
+/// Antigravity 状态数据库模块
+pub mod state_db;
+/// 字段级差异模块
+pub mod state_diff;
+/// 切换前快照模块
+pub mod switch_snapshot;
+/// 测试辅助模块
+#[cfg(test)]
+mod test_support;
+
+pub use account_metadata::{AccountFilter, AccountMetadata};
+pub use account_registry::{AccountRecord, AccountRegistry};
+pub use account_store::AccountStore;
+pub use data_category::DataCategory;
+pub use process::AntigravityProcess;
+pub use state_db::StateDb;
@@ -2,8 +2,30 @@ use dirs::*;
 /// 统一的跨平台路径处理工具
 ///
 /// 提供跨平台兼容的路径处理方法，避免硬编码路径
+use serde::Serialize;
 use std::path::PathBuf;
 
+use crate::constants::paths;
+
+/// agent 主目录的来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HomeSource {
+    /// 由 ANTIGRAVITY_AGENT_HOME 环境变量指定
+    Env,
+    /// 便携模式（可执行文件旁有标记文件）
+    Portable,
+    /// 系统配置目录下的默认位置
+    Default,
+}
+
+/// agent 主目录（配置、备份、日志等所有数据的根目录）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct AgentHome {
+    pub dir: PathBuf,
+    pub source: HomeSource,
+}
+
 /// 应用程序相关路径管理器
 pub struct AppPaths;
 
@@ -22,25 +44,22 @@ impl AppPaths {
             _ => Self::fallback_antigravity_data_dir(),
         };
 
-      match &result {
-        Some(path) => tracing::info!("🔍 检测 Antigravity 数据目录: {}",
-                path.display()),
-        None => tracing::info!("🔍 检测 Antigravity 数据目录: None"),
-      }
+        match &result {
+            Some(path) => tracing::info!("🔍 检测 Antigravity 数据目录: {}", path.display()),
+            None => tracing::info!("🔍 检测 Antigravity 数据目录: None"),
+        }
 
         result
     }
 
     /// Windows: %APPDATA%\Antigravity\User\globalStorage\
     fn windows_antigravity_data_dir() -> Option<PathBuf> {
-        config_dir()
-            .map(|path| path.join("Antigravity").join("User").join("globalStorage"))
+        config_dir().map(|path| path.join("Antigravity").join("User").join("globalStorage"))
     }
 
     /// macOS: ~/Library/Application Support/Antigravity/User/globalStorage/
     fn macos_antigravity_data_dir() -> Option<PathBuf> {
-        data_dir()
-            .map(|path| path.join("Antigravity").join("User").join("globalStorage"))
+        data_dir().map(|path| path.join("Antigravity").join("User").join("globalStorage"))
     }
 
     /// Linux: ~/.config/Antigravity/User/globalStorage/ (优先) 或 ~/.local/share/Antigravity/User/globalStorage/ (备用)
@@ -50,15 +69,13 @@ impl AppPaths {
             .map(|path| path.join("Antigravity").join("User").join("globalStorage"))
             .or_else(|| {
                 // 备用：~/.local/share
-                data_dir()
-                    .map(|path| path.join("Antigravity").join("User").join("globalStorage"))
+                data_dir().map(|path| path.join("Antigravity").join("User").join("globalStorage"))
             })
     }
 
     /// 其他系统的备用方案
     fn fallback_antigravity_data_dir() -> Option<PathBuf> {
-        data_dir()
-            .map(|path| path.join("Antigravity").join("User").join("globalStorage"))
+        data_dir().map(|path| path.join("Antigravity").join("User").join("globalStorage"))
     }
 
     /// 获取 Antigravity 可执行文件路径
@@ -82,16 +99,34 @@ impl AppPaths {
 
         // 用户程序目录: %LOCALAPPDATA%\Programs\
         if let Some(local_data) = data_local_dir() {
-            paths.push(local_data.join("Programs").join("Antigravity").join("Antigravity.exe"));
+            paths.push(
+                local_data
+                    .join("Programs")
+                    .join("Antigravity")
+                    .join("Antigravity.exe"),
+            );
         }
 
         // 用户数据目录的其他位置
         if let Some(home) = home_dir() {
             // %APPDATA%\Local\Programs\Antigravity\
-            paths.push(home.join("AppData").join("Local").join("Programs").join("Antigravity").join("Antigravity.exe"));
+            paths.push(
+                home.join("AppData")
+                    .join("Local")
+                    .join("Programs")
+                    .join("Antigravity")
+                    .join("Antigravity.exe"),
+            );
 
             // %APPDATA%\Roaming\Local\Programs\Antigravity\ (虽然不常见，但有些应用会这样安装)
-            paths.push(home.join("AppData").join("Roaming").join("Local").join("Programs").join("Antigravity").join("Antigravity.exe"));
+            paths.push(
+                home.join("AppData")
+                    .join("Roaming")
+                    .join("Local")
+                    .join("Programs")
+                    .join("Antigravity")
+                    .join("Antigravity.exe"),
+            );
         }
 
         // 系统程序目录
@@ -100,7 +135,11 @@ impl AppPaths {
         }
 
         if let Some(program_files_x86) = Self::get_program_files_x86_dir() {
-            paths.push(program_files_x86.join("Antigravity").join("Antigravity.exe"));
+            paths.push(
+                program_files_x86
+                    .join("Antigravity")
+                    .join("Antigravity.exe"),
+            );
         }
 
         paths
@@ -114,7 +153,7 @@ impl AppPaths {
             "Antigravity.app",
             "Antigravity-electron.app",
             "Antigravity-alpha.app",
-            "Antigravity-beta.app"
+            "Antigravity-beta.app",
         ];
 
         // 系统应用程序目录
@@ -161,18 +200,61 @@ impl AppPaths {
         // Flatpak
         paths.push(PathBuf::from("/var/lib/flatpak/exports/bin/antigravity"));
         if let Some(home) = home_dir() {
-            paths.push(home.join(".local").join("share").join("flatpak").join("exports").join("bin").join("antigravity"));
+            paths.push(
+                home.join(".local")
+                    .join("share")
+                    .join("flatpak")
+                    .join("exports")
+                    .join("bin")
+                    .join("antigravity"),
+            );
         }
 
         paths
     }
 
-    /// 获取配置目录
+    /// 解析 agent 主目录，所有模块都通过这里获取数据目录
+    ///
+    /// 优先级：
+    /// 1. `ANTIGRAVITY_AGENT_HOME` 环境变量
+    /// 2. 便携模式：可执行文件所在目录有 `antigravity-agent.portable` 标记文件时，使用该目录下的 `.antigravity-agent`
+    /// 3. 系统配置目录下的 `.antigravity-agent`（Windows 为 %APPDATA%），其次是用户主目录
+    pub fn agent_home() -> Option<AgentHome> {
+        if let Some(dir) = std::env::var_os(paths::HOME_ENV).filter(|dir| !dir.is_empty()) {
+            return Some(AgentHome {
+                dir: PathBuf::from(dir),
+                source: HomeSource::Env,
+            });
+        }
+
+        if let Some(exe_dir) = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(PathBuf::from))
+        {
+            if exe_dir.join(paths::PORTABLE_MARKER).is_file() {
+                return Some(AgentHome {
+                    dir: exe_dir.join(paths::CONFIG_DIR_NAME),
+                    source: HomeSource::Portable,
+                });
+            }
+        }
+
+        config_dir().or_else(home_dir).map(|dir| AgentHome {
+            dir: dir.join(paths::CONFIG_DIR_NAME),
+            source: HomeSource::Default,
+        })
+    }
+
+    /// 获取配置目录（agent 主目录）
     ///
     /// 统一的配置目录获取，避免硬编码
     pub fn config_dir() -> Option<PathBuf> {
-        config_dir()
-            .map(|path| path.join(".antigravity-agent"))
+        Self::agent_home().map(|home| home.dir)
+    }
+
+    /// 获取日志目录
+    pub fn logs_dir() -> Option<PathBuf> {
+        Self::config_dir().map(|path| path.join(paths::LOGS_DIR_NAME))
     }
 
     /// 获取备份目录
@@ -183,16 +265,12 @@ impl AppPaths {
     // Windows 特定的辅助方法
     #[cfg(target_os = "windows")]
     fn get_program_files_dir() -> Option<PathBuf> {
-        std::env::var("ProgramFiles")
-            .ok()
-            .map(PathBuf::from)
+        std::env::var("ProgramFiles").ok().map(PathBuf::from)
     }
 
     #[cfg(target_os = "windows")]
     fn get_program_files_x86_dir() -> Option<PathBuf> {
-        std::env::var("ProgramFiles(x86)")
-            .ok()
-            .map(PathBuf::from)
+        std::env::var("ProgramFiles(x86)").ok().map(PathBuf::from)
     }
 
     // macOS 特定的辅助方法
@@ -217,4 +295,3 @@ impl AppPaths {
         None
     }
 }
-
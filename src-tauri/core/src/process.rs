@@ -0,0 +1,749 @@
+// Antigravity 进程控制模块
+// 以 AntigravityProcess 封装进程检测、关闭和启动
+//
+// 进程按已配置或自动检测到的安装路径匹配（可执行文件路径、安装目录、AppImage），
+// Antigravity Agent 自身及其子进程始终被排除
+//
+// 关闭分三步：请求正常退出 -> 超时后强制终止 -> 等待所有进程退出且数据库解锁
+
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, Process, ProcessStatus, System};
+
+use crate::state_db::StateDb;
+use crate::{antigravity_path_config, antigravity_starter};
+
+/// 未检测到安装路径时按进程名精确匹配
+#[cfg(target_os = "windows")]
+const FALLBACK_PROCESS_NAMES: &[&str] = &["Antigravity.exe"];
+#[cfg(target_os = "macos")]
+const FALLBACK_PROCESS_NAMES: &[&str] = &["Antigravity"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const FALLBACK_PROCESS_NAMES: &[&str] = &["antigravity"];
+
+/// 进程的匹配状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchStatus {
+    /// 属于 Antigravity，会被检测和关闭
+    Matched,
+    /// 本应匹配，但属于 Antigravity Agent 自身，已排除
+    Excluded,
+    /// 名称或命令行提到 antigravity，但不属于 Antigravity 安装
+    Ignored,
+}
+
+/// 单个进程的匹配结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessMatch {
+    pub pid: u32,
+    pub name: String,
+    /// 可执行文件路径（无权限读取时为 None）
+    pub exe: Option<String>,
+    pub command: String,
+    pub status: MatchStatus,
+    /// 匹配、排除或忽略的原因
+    pub reason: String,
+}
+
+/// 用于匹配进程的 Antigravity 安装信息
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ProcessTargets {
+    /// Antigravity 可执行文件（已解析符号链接）
+    pub executables: Vec<PathBuf>,
+    /// 安装目录（目录名包含 antigravity，其中的所有进程都属于 Antigravity）
+    pub install_dirs: Vec<PathBuf>,
+    /// AppImage 文件（通过进程环境变量 APPIMAGE 识别）
+    pub appimages: Vec<PathBuf>,
+}
+
+impl ProcessTargets {
+    /// 根据自定义可执行文件路径和自动检测结果解析安装信息
+    pub fn resolve() -> Self {
+        let mut targets = Self::default();
+
+        let configured = antigravity_path_config::get_custom_executable_path()
+            .ok()
+            .flatten()
+            .map(PathBuf::from);
+        let detected = antigravity_starter::detect_antigravity_executable();
+
+        for path in configured.into_iter().chain(detected) {
+            targets.add(&path);
+        }
+
+        targets
+    }
+
+    fn add(&mut self, path: &Path) {
+        if !path.exists() {
+            return;
+        }
+
+        if path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("AppImage"))
+        {
+            push_unique(&mut self.appimages, path.to_path_buf());
+            return;
+        }
+
+        let resolved = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+
+        // macOS .app 包或安装目录本身
+        if resolved.is_dir() {
+            push_unique(&mut self.install_dirs, resolved);
+            return;
+        }
+
+        // 只信任名称包含 antigravity 的可执行文件和安装目录，
+        // 避免 /usr/bin/antigravity -> /usr/bin/snap 之类的包装器把无关进程算进来
+        if mentions_antigravity(&resolved) {
+            push_unique(&mut self.executables, resolved.clone());
+        }
+        if let Some(parent) = resolved.parent().filter(|p| mentions_antigravity(p)) {
+            push_unique(&mut self.install_dirs, parent.to_path_buf());
+        }
+    }
+
+    /// 是否没有解析到任何安装信息（此时按进程名精确匹配）
+    pub fn is_empty(&self) -> bool {
+        self.executables.is_empty() && self.install_dirs.is_empty() && self.appimages.is_empty()
+    }
+
+    /// 判断进程是否属于 Antigravity，返回匹配原因
+    fn match_reason(&self, process: &Process) -> Option<String> {
+        if let Some(exe) = process.exe() {
+            if let Some(target) = self.executables.iter().find(|t| same_path(exe, t)) {
+                return Some(format!(
+                    "可执行文件与 Antigravity 一致: {}",
+                    target.display()
+                ));
+            }
+            if let Some(dir) = self.install_dirs.iter().find(|d| is_within(exe, d)) {
+                return Some(format!(
+                    "可执行文件位于 Antigravity 安装目录: {}",
+                    dir.display()
+                ));
+            }
+        }
+
+        if !self.appimages.is_empty() {
+            for var in process.environ() {
+                if let Some(appimage) = var.strip_prefix("APPIMAGE=") {
+                    if let Some(target) = self
+                        .appimages
+                        .iter()
+                        .find(|t| same_path(Path::new(appimage), t))
+                    {
+                        return Some(format!("Antigravity AppImage 进程: {}", target.display()));
+                    }
+                }
+            }
+        }
+
+        if self.is_empty() && FALLBACK_PROCESS_NAMES.contains(&process.name()) {
+            return Some(format!(
+                "进程名与 Antigravity 一致: {}（未检测到安装路径）",
+                process.name()
+            ));
+        }
+
+        None
+    }
+}
+
+fn push_unique(paths: &mut Vec<PathBuf>, path: PathBuf) {
+    if !paths.iter().any(|p| same_path(p, &path)) {
+        paths.push(path);
+    }
+}
+
+fn mentions_antigravity(path: &Path) -> bool {
+    path.file_name().is_some_and(|name| {
+        name.to_string_lossy()
+            .to_lowercase()
+            .contains("antigravity")
+    })
+}
+
+/// 比较路径（Windows 不区分大小写）
+fn same_path(a: &Path, b: &Path) -> bool {
+    if cfg!(windows) {
+        a.to_string_lossy()
+            .eq_ignore_ascii_case(&b.to_string_lossy())
+    } else {
+        a == b
+    }
+}
+
+/// 路径是否位于目录中（按路径组件比较，`Antigravity Agent` 不会被当作 `Antigravity` 的子目录）
+fn is_within(path: &Path, dir: &Path) -> bool {
+    if cfg!(windows) {
+        let path = PathBuf::from(path.to_string_lossy().to_lowercase());
+        let dir = PathBuf::from(dir.to_string_lossy().to_lowercase());
+        path.starts_with(dir)
+    } else {
+        path.starts_with(dir)
+    }
+}
+
+/// Antigravity Agent 自身的进程信息（用于排除）
+struct SelfProcess {
+    pid: Option<Pid>,
+    exe: Option<PathBuf>,
+}
+
+impl SelfProcess {
+    fn current() -> Self {
+        Self {
+            pid: sysinfo::get_current_pid().ok(),
+            exe: std::env::current_exe()
+                .ok()
+                .map(|exe| exe.canonicalize().unwrap_or(exe)),
+        }
+    }
+
+    /// 判断进程是否属于 Antigravity Agent 自身，返回排除原因
+    fn exclusion_reason(&self, system: &System, pid: Pid, process: &Process) -> Option<String> {
+        let own_pid = self.pid?;
+
+        if pid == own_pid {
+            return Some("Antigravity Agent 自身进程".to_string());
+        }
+
+        // 沿父进程链向上查找，防止进程 ID 循环时死循环
+        let mut visited = HashSet::new();
+        let mut current = pid;
+        let mut parent = process.parent();
+        while let Some(parent_pid) = parent {
+            // 由 Agent 启动的 Antigravity（及其子进程）属于 Antigravity 应用本身
+            if antigravity_starter::is_launched_by_agent(current.as_u32()) {
+                break;
+            }
+            if parent_pid == own_pid {
+                return Some(format!(
+                    "Antigravity Agent 的子进程（父进程链包含 PID {}）",
+                    own_pid
+                ));
+            }
+            if !visited.insert(parent_pid) {
+                break;
+            }
+            current = parent_pid;
+            parent = system.process(parent_pid).and_then(|p| p.parent());
+        }
+
+        if let (Some(own_exe), Some(exe)) = (&self.exe, process.exe()) {
+            if same_path(own_exe, exe) {
+                return Some("与 Antigravity Agent 为同一可执行文件".to_string());
+            }
+        }
+
+        None
+    }
+}
+
+/// 名称或命令行是否提到 antigravity（用于列出被忽略的进程）
+fn mentions_antigravity_text(process: &Process) -> bool {
+    process.name().to_lowercase().contains("antigravity")
+        || process
+            .cmd()
+            .iter()
+            .any(|arg| arg.to_lowercase().contains("antigravity"))
+}
+
+/// 扫描所有进程，返回与 Antigravity 相关的进程及匹配结果
+fn scan_processes(system: &System, targets: &ProcessTargets) -> Vec<ProcessMatch> {
+    let own = SelfProcess::current();
+    let mut results = Vec::new();
+
+    for (pid, process) in system.processes() {
+        let matched = targets.match_reason(process);
+        let mentioned = mentions_antigravity_text(process);
+        if matched.is_none() && !mentioned {
+            continue;
+        }
+
+        let (status, reason) = match own.exclusion_reason(system, *pid, process) {
+            Some(reason) => (MatchStatus::Excluded, reason),
+            None => match matched {
+                Some(reason) => (MatchStatus::Matched, reason),
+                None => (
+                    MatchStatus::Ignored,
+                    "名称或命令行包含 antigravity，但可执行文件不属于 Antigravity 安装".to_string(),
+                ),
+            },
+        };
+
+        results.push(ProcessMatch {
+            pid: pid.as_u32(),
+            name: process.name().to_string(),
+            exe: process.exe().map(|exe| exe.display().to_string()),
+            command: process.cmd().join(" "),
+            status,
+            reason,
+        });
+    }
+
+    results.sort_by_key(|m| m.pid);
+    results
+}
+
+/// 关闭 Antigravity 时的超时设置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ShutdownOptions {
+    /// 请求正常退出后等待的时间，超时后强制终止
+    pub graceful_timeout: Duration,
+    /// 强制终止后等待进程退出的时间
+    pub kill_timeout: Duration,
+    /// 进程退出后等待数据库解锁的时间
+    pub unlock_timeout: Duration,
+}
+
+impl ShutdownOptions {
+    /// 默认超时：正常退出 10 秒，强制终止 5 秒，数据库解锁 5 秒
+    pub const DEFAULT: Self = Self {
+        graceful_timeout: Duration::from_secs(10),
+        kill_timeout: Duration::from_secs(5),
+        unlock_timeout: Duration::from_secs(5),
+    };
+}
+
+impl Default for ShutdownOptions {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// 当前使用的关闭超时设置（由桌面应用设置或命令行参数配置）
+static SHUTDOWN_OPTIONS: Mutex<ShutdownOptions> = Mutex::new(ShutdownOptions::DEFAULT);
+
+/// 设置关闭 Antigravity 时使用的超时
+pub fn set_shutdown_options(options: ShutdownOptions) {
+    if let Ok(mut current) = SHUTDOWN_OPTIONS.lock() {
+        *current = options;
+    }
+}
+
+/// 关闭 Antigravity 时使用的超时
+pub fn shutdown_options() -> ShutdownOptions {
+    SHUTDOWN_OPTIONS
+        .lock()
+        .map(|options| *options)
+        .unwrap_or_default()
+}
+
+/// 等待进程退出和数据库解锁时的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 请求进程正常退出（Windows 发送关闭窗口消息，其他平台发送 SIGTERM）
+fn request_exit(pid: Pid, process: &Process) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let _ = process;
+        std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string()])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = pid;
+        process.kill_with(sysinfo::Signal::Term).unwrap_or(false)
+    }
+}
+
+/// 进程是否仍在运行（僵尸进程视为已退出）
+fn is_alive(system: &mut System, pid: Pid) -> bool {
+    system.refresh_process(pid)
+        && system
+            .process(pid)
+            .is_some_and(|p| !matches!(p.status(), ProcessStatus::Zombie | ProcessStatus::Dead))
+}
+
+/// 等待进程全部退出，返回超时后仍在运行的进程
+async fn wait_for_exit(pids: &[Pid], timeout: Duration) -> Vec<Pid> {
+    let deadline = Instant::now() + timeout;
+    let mut system = System::new();
+
+    loop {
+        let alive: Vec<Pid> = pids
+            .iter()
+            .copied()
+            .filter(|pid| is_alive(&mut system, *pid))
+            .collect();
+        if alive.is_empty() || Instant::now() >= deadline {
+            return alive;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// 等待 state.vscdb 不再被锁定（找不到数据库路径时直接返回）
+async fn wait_for_db_unlock(timeout: Duration) -> Result<(), String> {
+    let Ok(state_db) = StateDb::locate() else {
+        return Ok(());
+    };
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        let last_error = match state_db.is_locked() {
+            Ok(false) => return Ok(()),
+            Ok(true) => "数据库被锁定".to_string(),
+            Err(e) => e,
+        };
+
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "数据库在 {} 秒内仍不可写入 ({}): {}，已取消写入数据库",
+                timeout.as_secs_f32(),
+                state_db.path().display(),
+                last_error
+            ));
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+fn format_pids(pids: &[Pid]) -> String {
+    pids.iter()
+        .map(|pid| pid.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Antigravity 进程控制
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AntigravityProcess;
+
+impl AntigravityProcess {
+    /// 用于匹配进程的安装信息
+    pub fn targets(&self) -> ProcessTargets {
+        ProcessTargets::resolve()
+    }
+
+    /// 列出与 Antigravity 相关的进程，并说明每个进程被匹配、排除或忽略的原因
+    pub fn scan(&self) -> Vec<ProcessMatch> {
+        let system = System::new_all();
+        scan_processes(&system, &self.targets())
+    }
+
+    /// Antigravity 是否正在运行
+    pub fn is_running(&self) -> bool {
+        tracing::info!("🔍 检查 Antigravity 进程是否运行");
+
+        match self
+            .scan()
+            .into_iter()
+            .find(|m| m.status == MatchStatus::Matched)
+        {
+            Some(m) => {
+                tracing::info!(
+                    "✅ 发现运行中的 Antigravity 进程: {} (PID: {})",
+                    m.name,
+                    m.pid
+                );
+                true
+            }
+            None => {
+                tracing::info!("ℹ️ 未发现运行中的 Antigravity 进程");
+                false
+            }
+        }
+    }
+
+    /// 关闭所有 Antigravity 进程（未找到时返回错误）
+    pub fn kill(&self) -> Result<String, String> {
+        tracing::info!("🔍 开始搜索并关闭 Antigravity 进程");
+
+        let system = System::new_all();
+        let targets = self.targets();
+        tracing::info!("🎯 匹配依据: {:?}", targets);
+
+        let mut killed_processes = Vec::new();
+
+        for m in scan_processes(&system, &targets) {
+            if m.status != MatchStatus::Matched {
+                tracing::debug!("跳过进程 {} (PID: {}): {}", m.name, m.pid, m.reason);
+                continue;
+            }
+
+            let Some(process) = system.process(Pid::from_u32(m.pid)) else {
+                continue;
+            };
+
+            tracing::info!(
+                "🎯 找到目标进程: {} (PID: {}) - {}",
+                m.name,
+                m.pid,
+                m.reason
+            );
+
+            // 尝试终止进程（第一次失败时再试一次）
+            if process.kill() {
+                killed_processes.push(format!("{} (PID: {})", m.name, m.pid));
+                tracing::info!("✅ 成功终止进程: {} (PID: {})", m.name, m.pid);
+            } else if process.kill() {
+                killed_processes.push(format!("{} (PID: {} - 强制)", m.name, m.pid));
+                tracing::info!("✅ 强制终止进程: {} (PID: {})", m.name, m.pid);
+            } else {
+                tracing::error!("❌ 终止进程失败: {} (PID: {})", m.name, m.pid);
+            }
+        }
+
+        if killed_processes.is_empty() {
+            tracing::info!("ℹ️ 未找到匹配的 Antigravity 进程");
+            Err("未找到Antigravity进程".to_string())
+        } else {
+            let success_msg = format!("已成功关闭Antigravity进程: {}", killed_processes.join(", "));
+            tracing::info!("🎉 {}", success_msg);
+            Ok(success_msg)
+        }
+    }
+
+    /// 使用当前的超时设置关闭 Antigravity，见 [`AntigravityProcess::shutdown_with`]
+    pub async fn shutdown(&self) -> Result<String, String> {
+        self.shutdown_with(&shutdown_options()).await
+    }
+
+    /// 关闭 Antigravity 并确认已退出（未运行时视为成功）
+    ///
+    /// 1. 请求顶层进程正常退出，等待 `graceful_timeout`
+    /// 2. 仍未退出的进程强制终止，等待 `kill_timeout`
+    /// 3. 等待 state.vscdb 解锁，最多 `unlock_timeout`
+    ///
+    /// 返回 `Ok` 后才可以写入数据库；任何一步超时都会返回错误
+    pub async fn shutdown_with(&self, options: &ShutdownOptions) -> Result<String, String> {
+        tracing::info!("🔍 开始关闭 Antigravity 进程");
+
+        let system = System::new_all();
+        let matched: Vec<ProcessMatch> = scan_processes(&system, &self.targets())
+            .into_iter()
+            .filter(|m| m.status == MatchStatus::Matched)
+            .collect();
+
+        let message = if matched.is_empty() {
+            tracing::info!("ℹ️ Antigravity 进程未运行，跳过关闭步骤");
+            "Antigravity 进程未运行".to_string()
+        } else {
+            let pids: Vec<Pid> = matched.iter().map(|m| Pid::from_u32(m.pid)).collect();
+
+            // 1. 只向顶层进程请求退出，子进程由 Antigravity 自己关闭
+            for m in &matched {
+                let pid = Pid::from_u32(m.pid);
+                let Some(process) = system.process(pid) else {
+                    continue;
+                };
+                if process
+                    .parent()
+                    .is_some_and(|parent| pids.contains(&parent))
+                {
+                    continue;
+                }
+                if request_exit(pid, process) {
+                    tracing::info!("👋 已请求进程退出: {} (PID: {})", m.name, m.pid);
+                } else {
+                    tracing::warn!("⚠️ 请求进程退出失败: {} (PID: {})", m.name, m.pid);
+                }
+            }
+
+            let mut remaining = wait_for_exit(&pids, options.graceful_timeout).await;
+            let forced = !remaining.is_empty();
+
+            // 2. 超时后强制终止
+            if forced {
+                tracing::warn!(
+                    "⏱️ {} 秒内未正常退出，强制终止进程: {}",
+                    options.graceful_timeout.as_secs_f32(),
+                    format_pids(&remaining)
+                );
+                let system = System::new_all();
+                for pid in &remaining {
+                    if let Some(process) = system.process(*pid) {
+                        process.kill();
+                    }
+                }
+                remaining = wait_for_exit(&remaining, options.kill_timeout).await;
+            }
+
+            if !remaining.is_empty() {
+                return Err(format!(
+                    "强制终止后 Antigravity 进程仍未退出 (PID: {})，已取消写入数据库",
+                    format_pids(&remaining)
+                ));
+            }
+
+            let names = matched
+                .iter()
+                .map(|m| format!("{} (PID: {})", m.name, m.pid))
+                .collect::<Vec<_>>()
+                .join(", ");
+            if forced {
+                format!("已强制关闭Antigravity进程: {}", names)
+            } else {
+                format!("已关闭Antigravity进程: {}", names)
+            }
+        };
+
+        // 3. 等待数据库解锁
+        wait_for_db_unlock(options.unlock_timeout).await?;
+
+        tracing::info!("✅ {}", message);
+        Ok(message)
+    }
+
+    /// 启动 Antigravity
+    pub fn start(&self) -> Result<String, String> {
+        antigravity_starter::start_antigravity()
+    }
+
+    /// 重新启动 Antigravity（失败时返回说明文字而不是错误）
+    pub fn restart(&self) -> String {
+        match self.start() {
+            Ok(result) => {
+                tracing::info!("✅ 启动结果: {}", result);
+                result
+            }
+            Err(e) => {
+                tracing::warn!("⚠️ 启动失败: {}", e);
+                format!("启动失败: {}", e)
+            }
+        }
+    }
+
+    /// 自动检测 Antigravity 可执行文件路径
+    pub fn detect_executable(&self) -> Option<PathBuf> {
+        antigravity_starter::detect_antigravity_executable()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use std::fs;
+
+    #[test]
+    fn targets_only_trust_antigravity_paths() {
+        let dir = test_support::temp_dir("process-targets");
+        let install_dir = dir.join("Antigravity");
+        fs::create_dir_all(&install_dir).unwrap();
+        let executable = install_dir.join("antigravity");
+        let wrapper = dir.join("launcher");
+        let appimage = dir.join("Antigravity-x86_64.AppImage");
+        for file in [&executable, &wrapper, &appimage] {
+            fs::write(file, "").unwrap();
+        }
+
+        let mut targets = ProcessTargets::default();
+        targets.add(&executable);
+        targets.add(&wrapper);
+        targets.add(&appimage);
+        targets.add(&dir.join("missing"));
+
+        assert_eq!(
+            targets.executables,
+            vec![executable.canonicalize().unwrap()]
+        );
+        assert_eq!(
+            targets.install_dirs,
+            vec![install_dir.canonicalize().unwrap()]
+        );
+        assert_eq!(targets.appimages, vec![appimage]);
+    }
+
+    #[test]
+    fn install_dir_matches_whole_path_components() {
+        let install_dir = Path::new("/opt/Antigravity");
+        assert!(is_within(
+            Path::new("/opt/Antigravity/bin/antigravity"),
+            install_dir
+        ));
+        assert!(!is_within(
+            Path::new("/opt/Antigravity Agent/antigravity-agent"),
+            install_dir
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn agent_process_tree_is_excluded() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let child_pid = Pid::from_u32(child.id());
+        let own_pid = sysinfo::get_current_pid().unwrap();
+
+        // 由 shell 启动后立即脱离的进程不属于 Agent 的进程树
+        let output = std::process::Command::new("sh")
+            .args(["-c", "sleep 30 >/dev/null 2>&1 & echo $!"])
+            .output()
+            .unwrap();
+        let orphan_pid = Pid::from_u32(
+            String::from_utf8_lossy(&output.stdout)
+                .trim()
+                .parse()
+                .unwrap(),
+        );
+
+        let system = System::new_all();
+        let exe_of = |pid: Pid| system.process(pid).unwrap().exe().unwrap().to_path_buf();
+        let targets = ProcessTargets {
+            executables: vec![exe_of(own_pid), exe_of(child_pid)],
+            ..Default::default()
+        };
+        let matches = scan_processes(&system, &targets);
+        let _ = child.kill();
+        let _ = child.wait();
+        if let Some(orphan) = system.process(orphan_pid) {
+            orphan.kill();
+        }
+
+        let status_of = |pid: Pid| {
+            matches
+                .iter()
+                .find(|m| m.pid == pid.as_u32())
+                .map(|m| m.status)
+        };
+        assert_eq!(status_of(own_pid), Some(MatchStatus::Excluded));
+        assert_eq!(status_of(child_pid), Some(MatchStatus::Excluded));
+        assert_eq!(status_of(orphan_pid), Some(MatchStatus::Matched));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn wait_for_exit_reports_processes_that_ignore_the_exit_request() {
+        let mut polite = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let mut stubborn = std::process::Command::new("sh")
+            .args(["-c", "trap '' TERM; exec sleep 30"])
+            .spawn()
+            .unwrap();
+        // 等待 shell 设置好信号处理并 exec
+        tokio::time::sleep(Duration::from_millis(300)).await;
+
+        let pids = [Pid::from_u32(polite.id()), Pid::from_u32(stubborn.id())];
+        let system = System::new_all();
+        for pid in pids {
+            assert!(request_exit(pid, system.process(pid).unwrap()));
+        }
+        // 已退出但未被回收的僵尸进程视为已退出
+        let alive = wait_for_exit(&pids, Duration::from_millis(500)).await;
+
+        for child in [&mut polite, &mut stubborn] {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        assert_eq!(alive, vec![pids[1]]);
+    }
+}
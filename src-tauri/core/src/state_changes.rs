@@ -0,0 +1,339 @@
+// 状态数据库变化检测模块
+// 收到文件变化通知（或定时兜底检查）时调用 ChangeDetector::poll：
+// 先比较 state.vscdb 和 -wal 文件的大小与修改时间，确实变化后才重新读取关注的字段
+// StateChange::events 将字段变化归类为登录、登出、切换账户、刷新令牌、设置变化和新通知等语义事件
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use crate::constants::database;
+use crate::state_db::StateDb;
+use crate::state_diff::{diff_fields, ChangeKind};
+
+/// 数据库文件指纹（主库和 WAL 文件的大小与修改时间）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct Fingerprint {
+    db: Option<(u64, SystemTime)>,
+    wal: Option<(u64, SystemTime)>,
+}
+
+impl Fingerprint {
+    fn of(db_path: &Path) -> Self {
+        fn stat(path: &Path) -> Option<(u64, SystemTime)> {
+            let metadata = fs::metadata(path).ok()?;
+            Some((metadata.len(), metadata.modified().ok()?))
+        }
+
+        let mut wal_path = db_path.as_os_str().to_owned();
+        wal_path.push("-wal");
+
+        Self {
+            db: stat(db_path),
+            wal: stat(Path::new(&wal_path)),
+        }
+    }
+}
+
+/// 一次检测到的变化（只包含关注的字段）
+#[derive(Debug, Clone)]
+pub struct StateChange {
+    pub old_data: Map<String, Value>,
+    pub new_data: Map<String, Value>,
+}
+
+impl StateChange {
+    /// 将字段变化归类为语义事件（只携带相关字段）
+    pub fn events(&self) -> Vec<StateEvent> {
+        let mut events = Vec::new();
+
+        let old_auth = auth_of(&self.old_data);
+        let new_auth = auth_of(&self.new_data);
+        match (old_auth, new_auth) {
+            (None, Some(auth)) => events.push(StateEvent::LoggedIn {
+                email: field(auth, "email"),
+                name: field(auth, "name"),
+            }),
+            (Some(auth), None) => events.push(StateEvent::LoggedOut {
+                email: field(auth, "email"),
+            }),
+            (Some(old), Some(new)) if old != new => {
+                let from = field(old, "email");
+                let to = field(new, "email");
+                events.push(if from == to {
+                    StateEvent::TokenRefreshed { email: to }
+                } else {
+                    StateEvent::AccountChanged { from, to }
+                });
+            }
+            _ => {}
+        }
+
+        let changes = diff_fields(&self.old_data, &self.new_data);
+
+        let settings: Vec<String> = changes
+            .iter()
+            .filter(|change| database::SETTINGS_KEYS.contains(&change.key.as_str()))
+            .map(|change| change.key.clone())
+            .collect();
+        if !settings.is_empty() {
+            events.push(StateEvent::SettingsChanged { keys: settings });
+        }
+
+        for change in &changes {
+            if change.kind == ChangeKind::Removed
+                || !change.key.starts_with(database::NOTIFICATION_PREFIX)
+            {
+                continue;
+            }
+            if let Some(value) = self.new_data.get(&change.key) {
+                events.push(StateEvent::NotificationAdded {
+                    key: change.key.clone(),
+                    value: value.clone(),
+                });
+            }
+        }
+
+        events
+    }
+}
+
+/// 认证信息（字段不存在或为 null 时视为未登录）
+fn auth_of(data: &Map<String, Value>) -> Option<&Value> {
+    data.get(database::AUTH_STATUS)
+        .filter(|auth| !auth.is_null())
+}
+
+fn field(value: &Value, name: &str) -> Option<String> {
+    value.get(name).and_then(|v| v.as_str()).map(String::from)
+}
+
+/// 语义化的状态变化事件
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StateEvent {
+    /// 用户登录
+    LoggedIn {
+        email: Option<String>,
+        name: Option<String>,
+    },
+    /// 用户登出
+    LoggedOut { email: Option<String> },
+    /// 切换到另一个账户
+    AccountChanged {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    /// 同一账户的认证信息更新（令牌刷新）
+    TokenRefreshed { email: Option<String> },
+    /// 用户设置变化（变化的字段名）
+    SettingsChanged { keys: Vec<String> },
+    /// 新增或更新的通知
+    NotificationAdded { key: String, value: Value },
+}
+
+impl std::fmt::Display for StateEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let email =
+            |email: &Option<String>| email.clone().unwrap_or_else(|| "未知账户".to_string());
+        match self {
+            StateEvent::LoggedIn { email: e, .. } => write!(f, "登录: {}", email(e)),
+            StateEvent::LoggedOut { email: e } => write!(f, "登出: {}", email(e)),
+            StateEvent::AccountChanged { from, to } => {
+                write!(f, "切换账户: {} -> {}", email(from), email(to))
+            }
+            StateEvent::TokenRefreshed { email: e } => write!(f, "令牌刷新: {}", email(e)),
+            StateEvent::SettingsChanged { keys } => write!(f, "设置变化: {}", keys.join(", ")),
+            StateEvent::NotificationAdded { key, .. } => write!(f, "新通知: {}", key),
+        }
+    }
+}
+
+/// 状态数据库变化检测器
+#[derive(Debug)]
+pub struct ChangeDetector {
+    state_db: StateDb,
+    fingerprint: Option<Fingerprint>,
+    last_data: Option<Map<String, Value>>,
+}
+
+impl ChangeDetector {
+    pub fn new(state_db: StateDb) -> Self {
+        Self {
+            state_db,
+            fingerprint: None,
+            last_data: None,
+        }
+    }
+
+    /// 被检测的数据库
+    pub fn state_db(&self) -> &StateDb {
+        &self.state_db
+    }
+
+    /// 检查数据库是否变化
+    ///
+    /// - `force` 为 false 时，文件指纹没有变化就不读取数据库
+    /// - 第一次调用只记录基准数据，返回 `Ok(None)`
+    /// - 关注的字段有变化时返回变化前后的数据
+    pub fn poll(&mut self, force: bool) -> Result<Option<StateChange>, String> {
+        let fingerprint = Fingerprint::of(self.state_db.path());
+        if !force && self.fingerprint == Some(fingerprint) {
+            return Ok(None);
+        }
+
+        let new_data = self.state_db.read_watched_keys()?;
+        self.fingerprint = Some(fingerprint);
+
+        let change = match self.last_data.take() {
+            Some(old_data) if old_data != new_data => Some(StateChange {
+                old_data,
+                new_data: new_data.clone(),
+            }),
+            _ => None,
+        };
+
+        self.last_data = Some(new_data);
+        Ok(change)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use rusqlite::Connection;
+    use serde_json::json;
+
+    #[test]
+    fn poll_rereads_only_when_the_database_changes() {
+        let dir = test_support::temp_dir("state-changes-poll");
+        let db_path = dir.join("state.vscdb");
+        drop(test_support::create_state_db(
+            &db_path,
+            &[
+                (database::AUTH_STATUS, r#"{"email":"a@example.com"}"#),
+                ("unrelated.key", "1"),
+            ],
+        ));
+        let mut detector = ChangeDetector::new(StateDb::new(&db_path));
+
+        // 第一次只记录基准数据，未关注的字段不会被读取
+        assert!(detector.poll(false).unwrap().is_none());
+        assert!(!detector
+            .last_data
+            .as_ref()
+            .unwrap()
+            .contains_key("unrelated.key"));
+
+        // 文件大小和修改时间不变时不重新读取
+        let modified = fs::metadata(&db_path).unwrap().modified().unwrap();
+        let conn = Connection::open(&db_path).unwrap();
+        conn.execute(
+            "UPDATE ItemTable SET value = ? WHERE key = ?",
+            [r#"{"email":"b@example.com"}"#, database::AUTH_STATUS],
+        )
+        .unwrap();
+        drop(conn);
+        fs::File::options()
+            .write(true)
+            .open(&db_path)
+            .unwrap()
+            .set_modified(modified)
+            .unwrap();
+        assert!(detector.poll(false).unwrap().is_none());
+
+        let change = detector.poll(true).unwrap().unwrap();
+        assert_eq!(
+            change.new_data[database::AUTH_STATUS]["email"],
+            "b@example.com"
+        );
+        assert!(detector.poll(true).unwrap().is_none());
+
+        // 只有未关注的字段变化时不产生变化
+        Connection::open(&db_path)
+            .unwrap()
+            .execute(
+                "UPDATE ItemTable SET value = '2' WHERE key = 'unrelated.key'",
+                [],
+            )
+            .unwrap();
+        assert!(detector.poll(false).unwrap().is_none());
+    }
+
+    fn events_between(old: Value, new: Value) -> Vec<StateEvent> {
+        StateChange {
+            old_data: old.as_object().unwrap().clone(),
+            new_data: new.as_object().unwrap().clone(),
+        }
+        .events()
+    }
+
+    #[test]
+    fn classifies_auth_changes() {
+        let auth =
+            |email: &str, token: &str| json!({ "email": email, "name": "N", "token": token });
+        let logged_out = json!({ database::AUTH_STATUS: null });
+        let a = json!({ database::AUTH_STATUS: auth("a@example.com", "1") });
+        let a_refreshed = json!({ database::AUTH_STATUS: auth("a@example.com", "2") });
+        let b = json!({ database::AUTH_STATUS: auth("b@example.com", "1") });
+
+        assert_eq!(
+            events_between(logged_out.clone(), a.clone()),
+            vec![StateEvent::LoggedIn {
+                email: Some("a@example.com".to_string()),
+                name: Some("N".to_string()),
+            }]
+        );
+        assert_eq!(
+            events_between(a.clone(), logged_out),
+            vec![StateEvent::LoggedOut {
+                email: Some("a@example.com".to_string()),
+            }]
+        );
+        assert_eq!(
+            events_between(a.clone(), a_refreshed),
+            vec![StateEvent::TokenRefreshed {
+                email: Some("a@example.com".to_string()),
+            }]
+        );
+        assert_eq!(
+            events_between(a, b),
+            vec![StateEvent::AccountChanged {
+                from: Some("a@example.com".to_string()),
+                to: Some("b@example.com".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn classifies_settings_and_notifications() {
+        let added = format!("{}added", database::NOTIFICATION_PREFIX);
+        let removed = format!("{}removed", database::NOTIFICATION_PREFIX);
+        let events = events_between(
+            json!({
+                database::USER_SETTINGS: { "theme": "dark" },
+                &removed: "old",
+            }),
+            json!({
+                database::USER_SETTINGS: { "theme": "light" },
+                &added: { "title": "hello" },
+            }),
+        );
+
+        assert_eq!(
+            events,
+            vec![
+                StateEvent::SettingsChanged {
+                    keys: vec![database::USER_SETTINGS.to_string()],
+                },
+                StateEvent::NotificationAdded {
+                    key: added,
+                    value: json!({ "title": "hello" }),
+                },
+            ]
+        );
+    }
+}
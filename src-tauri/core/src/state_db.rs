@@ -0,0 +1,225 @@
+// Antigravity 状态数据库模块
+// 以 StateDb 封装 state.vscdb（及同目录的 state.vscdb.backup）：读取当前登录信息、提取备份数据、恢复、预览和清除
+
+use rusqlite::{Connection, ErrorCode, OpenFlags, OptionalExtension};
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::antigravity_restore::{self, RestoreError, RestorePreview};
+use crate::constants::database;
+use crate::data_category::DataCategory;
+use crate::db_health::{self, DatabaseHealth, MarkerReport, VacuumResult};
+use crate::db_preflight::{self, PreflightReport};
+use crate::db_snapshot::{self, DbSnapshot};
+use crate::{antigravity_backup, antigravity_cleanup, platform_utils};
+
+/// Antigravity 状态数据库（state.vscdb）
+#[derive(Debug, Clone)]
+pub struct StateDb {
+    path: PathBuf,
+}
+
+impl StateDb {
+    /// 使用指定的 state.vscdb 路径
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// 定位当前要操作的 state.vscdb（自定义路径优先，其次是自动检测到的位置）
+    pub fn locate() -> Result<Self, String> {
+        platform_utils::resolve_antigravity_db_path().map(Self::new)
+    }
+
+    /// state.vscdb 路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 同目录下的 state.vscdb.backup 路径
+    pub fn backup_path(&self) -> PathBuf {
+        self.path.with_extension("vscdb.backup")
+    }
+
+    /// 数据库文件是否存在
+    pub fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// 数据库是否仍被其他进程锁定（尝试立即获取排他锁，不等待）
+    ///
+    /// 数据库不存在时返回 `Ok(false)`
+    pub fn is_locked(&self) -> Result<bool, String> {
+        if !self.exists() {
+            return Ok(false);
+        }
+
+        let conn = self.open()?;
+        conn.busy_timeout(Duration::ZERO)
+            .map_err(|e| format!("设置数据库超时失败: {}", e))?;
+
+        match conn.execute_batch("BEGIN EXCLUSIVE; ROLLBACK;") {
+            Ok(()) => Ok(false),
+            Err(rusqlite::Error::SqliteFailure(e, _))
+                if matches!(e.code, ErrorCode::DatabaseBusy | ErrorCode::DatabaseLocked) =>
+            {
+                Ok(true)
+            }
+            Err(e) => Err(format!("检查数据库锁失败: {}", e)),
+        }
+    }
+
+    /// 写入前检查（占用进程、写锁、-journal / -wal 文件），见 [`db_preflight::check`]
+    pub fn preflight(&self) -> Result<PreflightReport, String> {
+        db_preflight::check(&self.path)
+    }
+
+    fn open(&self) -> Result<Connection, String> {
+        Connection::open(&self.path)
+            .map_err(|e| format!("连接数据库失败 ({}): {}", self.path.display(), e))
+    }
+
+    fn auth_json(&self) -> Result<Option<String>, String> {
+        self.open()?
+            .query_row(
+                "SELECT value FROM ItemTable WHERE key = ?",
+                [database::AUTH_STATUS],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| format!("查询认证信息失败: {}", e))
+    }
+
+    /// 读取认证信息（未登录时返回 None）
+    pub fn auth_status(&self) -> Result<Option<Value>, String> {
+        self.auth_json()?
+            .map(|auth| serde_json::from_str(&auth).map_err(|e| format!("解析认证信息失败: {}", e)))
+            .transpose()
+    }
+
+    /// 当前登录账户的邮箱（数据库不存在或未登录时返回 None）
+    pub fn current_email(&self) -> Result<Option<String>, String> {
+        if !self.exists() {
+            return Ok(None);
+        }
+
+        Ok(self
+            .auth_json()?
+            .and_then(|auth| serde_json::from_str::<Value>(&auth).ok())
+            .and_then(|auth| auth.get("email").and_then(|e| e.as_str()).map(String::from)))
+    }
+
+    /// 读取需要关注的字段（所有备份字段和通知字段），值能解析为 JSON 时返回解析后的内容
+    ///
+    /// 数据库不存在时返回空对象
+    pub fn read_watched_keys(&self) -> Result<Map<String, Value>, String> {
+        let mut data = Map::new();
+        if !self.exists() {
+            return Ok(data);
+        }
+
+        let conn = Connection::open_with_flags(&self.path, OpenFlags::SQLITE_OPEN_READ_ONLY)
+            .map_err(|e| format!("连接数据库失败 ({}): {}", self.path.display(), e))?;
+        conn.busy_timeout(Duration::from_secs(1))
+            .map_err(|e| format!("设置数据库超时失败: {}", e))?;
+
+        let placeholders = vec!["?"; database::ALL_KEYS.len()].join(", ");
+        let sql = format!(
+            "SELECT key, value FROM ItemTable WHERE key IN ({}) OR key LIKE ? ORDER BY key",
+            placeholders
+        );
+        let notification_pattern = format!("{}%", database::NOTIFICATION_PREFIX);
+        let params = database::ALL_KEYS
+            .iter()
+            .copied()
+            .chain(std::iter::once(notification_pattern.as_str()));
+
+        let mut stmt = conn
+            .prepare(&sql)
+            .map_err(|e| format!("查询数据库失败: {}", e))?;
+        let rows = stmt
+            .query_map(rusqlite::params_from_iter(params), |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(|e| format!("查询数据库失败: {}", e))?;
+
+        for row in rows {
+            let (key, value) = row.map_err(|e| format!("读取数据库失败: {}", e))?;
+            let value = serde_json::from_str(&value).unwrap_or(Value::String(value));
+            data.insert(key, value);
+        }
+
+        Ok(data)
+    }
+
+    /// 提取账户选定类别的备份数据（关键字段、通知字段、Marker 和元信息）
+    pub fn extract_backup(
+        &self,
+        email: &str,
+        categories: &[DataCategory],
+    ) -> Result<Map<String, Value>, String> {
+        antigravity_backup::extract_backup_data(&self.path, email, categories)
+    }
+
+    /// 预览将备份文件中选定类别的数据恢复到本数据库会带来的变化（只读）
+    pub fn preview_restore(
+        &self,
+        backup_file: &Path,
+        categories: &[DataCategory],
+    ) -> Result<RestorePreview, RestoreError> {
+        antigravity_restore::preview_restore_to(&self.path, backup_file, categories)
+    }
+
+    /// 将备份文件中选定类别的数据恢复到本数据库（主库和备份库在同一事务中写入）
+    pub fn restore(
+        &self,
+        backup_file: &Path,
+        categories: &[DataCategory],
+    ) -> Result<String, RestoreError> {
+        antigravity_restore::restore_backup_to(&self.path, backup_file, categories)
+    }
+
+    /// 将已读取的备份内容中选定类别的数据恢复到本数据库
+    pub fn restore_data(
+        &self,
+        backup_data: &Value,
+        categories: &[DataCategory],
+    ) -> Result<String, RestoreError> {
+        antigravity_restore::restore_backup_data_to(&self.path, backup_data, categories)
+    }
+
+    /// 清除选定类别的用户数据（保留设备指纹）
+    pub fn clear(&self, categories: &[DataCategory]) -> Result<String, String> {
+        antigravity_cleanup::clear_state_databases(&self.path, categories)
+    }
+
+    /// 对主库和备份库执行完整性检查并统计大小和空闲页（只读）
+    pub fn health(&self) -> Result<Vec<DatabaseHealth>, String> {
+        db_health::check_health(&self.path)
+    }
+
+    /// 对主库和备份库执行 VACUUM
+    pub fn vacuum(&self) -> Result<Vec<VacuumResult>, String> {
+        db_health::vacuum(&self.path)
+    }
+
+    /// 检查主库和备份库的 Marker 与实际数据是否一致（只读）
+    pub fn check_marker(&self) -> Result<Vec<MarkerReport>, String> {
+        db_health::check_marker(&self.path)
+    }
+
+    /// 修复主库和备份库的 Marker（移除没有对应行的字段，登记缺失的字段）
+    pub fn repair_marker(&self) -> Result<Vec<MarkerReport>, String> {
+        db_health::repair_marker(&self.path)
+    }
+
+    /// 保存主库和备份库的完整快照（在线备份，Antigravity 运行时也可以调用）
+    pub fn snapshot(&self, label: Option<&str>) -> Result<DbSnapshot, String> {
+        db_snapshot::create_snapshot(&self.path, label)
+    }
+
+    /// 用完整快照覆盖主库和备份库（覆盖前自动保存当前状态）
+    pub fn restore_snapshot(&self, id: &str) -> Result<DbSnapshot, String> {
+        db_snapshot::restore_snapshot(&self.path, id)
+    }
+}
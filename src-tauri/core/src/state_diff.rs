@@ -0,0 +1,69 @@
+// 数据库字段差异模块
+// 逐字段比较两个 JSON 对象，供数据库监控和恢复预览共用
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// 字段变化类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Added,
+    Changed,
+    Removed,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Changed => "changed",
+            ChangeKind::Removed => "removed",
+        }
+    }
+}
+
+/// 单个字段的变化
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldChange {
+    pub key: String,
+    pub kind: ChangeKind,
+}
+
+/// 逐字段比较两个 JSON 对象（先列出新增/修改，再列出删除）
+pub fn diff_fields(
+    old_obj: &serde_json::Map<String, Value>,
+    new_obj: &serde_json::Map<String, Value>,
+) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    // 检查新增和修改的字段
+    for (key, new_value) in new_obj {
+        match old_obj.get(key) {
+            Some(old_value) => {
+                if old_value != new_value {
+                    changes.push(FieldChange {
+                        key: key.clone(),
+                        kind: ChangeKind::Changed,
+                    });
+                }
+            }
+            None => changes.push(FieldChange {
+                key: key.clone(),
+                kind: ChangeKind::Added,
+            }),
+        }
+    }
+
+    // 检查删除的字段
+    for key in old_obj.keys() {
+        if !new_obj.contains_key(key) {
+            changes.push(FieldChange {
+                key: key.clone(),
+                kind: ChangeKind::Removed,
+            });
+        }
+    }
+
+    changes
+}
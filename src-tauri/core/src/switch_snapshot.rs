@@ -0,0 +1,374 @@
+// 切换前安全快照模块
+// 在账户切换写入数据库之前保存被替换的状态，支持一键撤销上次切换
+
+use rusqlite::{params, Connection, OptionalExtension, Transaction};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::account_vault;
+use crate::antigravity_restore::{self, RestoreError, BACKUP_DB_SCHEMA};
+use crate::constants::{database, switch_snapshot};
+use crate::data_category::DataCategory;
+use crate::path_utils::AppPaths;
+use crate::platform_utils;
+
+/// 单个数据库的快照内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseSnapshot {
+    /// 切换时会被写入的字段的原始值（None 表示该字段当时不存在）
+    pub values: BTreeMap<String, Option<String>>,
+    /// __$__targetStorageMarker 的原始字符串
+    pub marker: Option<String>,
+}
+
+/// 切换前快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwitchSnapshot {
+    /// 快照 ID（时间戳，按字典序排序即为时间顺序）
+    pub id: String,
+    /// 创建时间（RFC3339）
+    pub created_at: String,
+    /// 切换前登录的账户
+    pub from_account: Option<String>,
+    /// 切换的目标账户
+    pub to_account: String,
+    /// state.vscdb 快照
+    pub main: DatabaseSnapshot,
+    /// state.vscdb.backup 快照（文件不存在时为 None）
+    pub backup: Option<DatabaseSnapshot>,
+}
+
+/// 快照摘要（用于列表显示）
+#[derive(Debug, Clone, Serialize)]
+pub struct SwitchSnapshotInfo {
+    pub id: String,
+    pub created_at: String,
+    pub from_account: Option<String>,
+    pub to_account: String,
+}
+
+impl From<&SwitchSnapshot> for SwitchSnapshotInfo {
+    fn from(snapshot: &SwitchSnapshot) -> Self {
+        Self {
+            id: snapshot.id.clone(),
+            created_at: snapshot.created_at.clone(),
+            from_account: snapshot.from_account.clone(),
+            to_account: snapshot.to_account.clone(),
+        }
+    }
+}
+
+/// 快照存放目录
+fn snapshot_dir() -> Result<PathBuf, String> {
+    AppPaths::config_dir()
+        .map(|dir| dir.join(switch_snapshot::DIR_NAME))
+        .ok_or_else(|| "无法获取配置目录".to_string())
+}
+
+/// 读取指定 schema 下的快照内容
+fn read_database_snapshot(
+    conn: &Connection,
+    schema: &str,
+    keys: &[String],
+) -> Result<DatabaseSnapshot, String> {
+    let select_sql = format!("SELECT value FROM {}.ItemTable WHERE key = ?", schema);
+
+    let mut values = BTreeMap::new();
+    for key in keys {
+        let value: Option<String> = conn
+            .query_row(&select_sql, [key], |row| row.get(0))
+            .optional()
+            .map_err(|e| format!("读取字段 {} 失败: {}", key, e))?;
+        values.insert(key.clone(), value);
+    }
+
+    let marker: Option<String> = conn
+        .query_row(&select_sql, [database::TARGET_STORAGE_MARKER], |row| {
+            row.get(0)
+        })
+        .optional()
+        .map_err(|e| format!("读取 Marker 失败: {}", e))?;
+
+    Ok(DatabaseSnapshot { values, marker })
+}
+
+/// 从认证信息中解析当前登录邮箱
+fn current_email(snapshot: &DatabaseSnapshot) -> Option<String> {
+    snapshot
+        .values
+        .get(database::AUTH_STATUS)
+        .and_then(|v| v.as_deref())
+        .and_then(|auth| serde_json::from_str::<Value>(auth).ok())
+        .and_then(|auth| auth.get("email").and_then(|e| e.as_str()).map(String::from))
+}
+
+/// 在切换账户前保存当前数据库状态
+///
+/// 读取两个 state.vscdb 中恢复 `backup_data` 时会写入的全部字段（见 [`antigravity_restore::written_keys`]）
+/// 的值和 Marker 并写入快照目录，超出数量上限的旧快照会被删除
+///
+/// # 返回
+/// - `Ok(Some(info))`: 快照已保存
+/// - `Ok(None)`: 数据库不存在，无需快照
+/// - `Err(message)`: 快照失败，调用方不应继续写入数据库
+pub fn capture_switch_snapshot(
+    to_account: &str,
+    backup_data: &Value,
+) -> Result<Option<SwitchSnapshotInfo>, String> {
+    let app_data = platform_utils::resolve_antigravity_db_path()?;
+    capture_switch_snapshot_from(&app_data, to_account, backup_data)
+}
+
+/// 从指定的 state.vscdb（及同目录下的 state.vscdb.backup）保存切换前快照
+pub fn capture_switch_snapshot_from(
+    app_data: &Path,
+    to_account: &str,
+    backup_data: &Value,
+) -> Result<Option<SwitchSnapshotInfo>, String> {
+    if !app_data.exists() {
+        tracing::info!("  ℹ️ 数据库不存在，跳过切换前快照");
+        return Ok(None);
+    }
+
+    let (conn, has_backup_db) =
+        antigravity_restore::open_state_databases(app_data).map_err(String::from)?;
+
+    // 切换时恢复全部类别
+    let keys = antigravity_restore::written_keys(backup_data, DataCategory::ALL);
+    let main = read_database_snapshot(&conn, "main", &keys)?;
+    let backup = if has_backup_db {
+        Some(read_database_snapshot(&conn, BACKUP_DB_SCHEMA, &keys)?)
+    } else {
+        None
+    };
+
+    let snapshot = SwitchSnapshot {
+        id: chrono::Local::now().format("%Y%m%d-%H%M%S-%3f").to_string(),
+        created_at: chrono::Local::now().to_rfc3339(),
+        from_account: current_email(&main),
+        to_account: to_account.to_string(),
+        main,
+        backup,
+    };
+
+    let dir = snapshot_dir()?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建快照目录失败: {}", e))?;
+
+    let file = dir.join(format!("{}.json", snapshot.id));
+    let content =
+        serde_json::to_string_pretty(&snapshot).map_err(|e| format!("序列化快照失败: {}", e))?;
+    // 快照包含登录凭据，保险库启用时与账户备份一样加密保存
+    account_vault::write_account_file(&file, &content)
+        .map_err(|e| format!("写入快照失败: {}", e))?;
+
+    tracing::info!("  📸 已保存切换前快照: {}", snapshot.id);
+
+    prune_snapshots(&dir, switch_snapshot::MAX_SNAPSHOTS)?;
+
+    Ok(Some(SwitchSnapshotInfo::from(&snapshot)))
+}
+
+/// 按 ID 降序列出快照文件
+fn snapshot_files(dir: &Path) -> Result<Vec<(String, PathBuf)>, String> {
+    let mut files = Vec::new();
+
+    if !dir.exists() {
+        return Ok(files);
+    }
+
+    for entry in fs::read_dir(dir).map_err(|e| format!("读取快照目录失败: {}", e))? {
+        let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
+        let path = entry.path();
+
+        if path.extension().is_some_and(|ext| ext == "json") {
+            if let Some(stem) = path.file_stem() {
+                files.push((stem.to_string_lossy().to_string(), path));
+            }
+        }
+    }
+
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    Ok(files)
+}
+
+/// 只保留最新的 `keep` 个快照
+fn prune_snapshots(dir: &Path, keep: usize) -> Result<(), String> {
+    for (id, path) in snapshot_files(dir)?.into_iter().skip(keep) {
+        fs::remove_file(&path).map_err(|e| format!("删除快照 {} 失败: {}", id, e))?;
+        tracing::info!("  🧹 已删除旧快照: {}", id);
+    }
+    Ok(())
+}
+
+fn read_snapshot(path: &Path) -> Result<SwitchSnapshot, String> {
+    let content =
+        account_vault::read_account_file(path).map_err(|e| format!("读取快照失败: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析快照失败: {}", e))
+}
+
+/// 列出所有切换前快照（最新的在前）
+pub fn list_switch_snapshots() -> Result<Vec<SwitchSnapshotInfo>, String> {
+    let mut snapshots = Vec::new();
+
+    for (id, path) in snapshot_files(&snapshot_dir()?)? {
+        match read_snapshot(&path) {
+            Ok(snapshot) => snapshots.push(SwitchSnapshotInfo::from(&snapshot)),
+            Err(e) => tracing::warn!("⚠️ 跳过损坏的快照 {}: {}", id, e),
+        }
+    }
+
+    Ok(snapshots)
+}
+
+/// 是否存在可撤销的快照
+pub fn has_switch_snapshot() -> bool {
+    snapshot_dir()
+        .and_then(|dir| snapshot_files(&dir))
+        .map(|files| !files.is_empty())
+        .unwrap_or(false)
+}
+
+/// 将快照内容写回指定 schema（字段不存在时删除对应行）
+fn apply_database_snapshot(
+    tx: &Transaction,
+    schema: &str,
+    db_name: &str,
+    snapshot: &DatabaseSnapshot,
+) -> Result<(), RestoreError> {
+    let insert_sql = format!(
+        "INSERT OR REPLACE INTO {}.ItemTable (key, value) VALUES (?, ?)",
+        schema
+    );
+    let delete_sql = format!("DELETE FROM {}.ItemTable WHERE key = ?", schema);
+
+    let entries = snapshot
+        .values
+        .iter()
+        .map(|(key, value)| (key.as_str(), value.as_deref()))
+        .chain(std::iter::once((
+            database::TARGET_STORAGE_MARKER,
+            snapshot.marker.as_deref(),
+        )));
+
+    for (key, value) in entries {
+        let result = match value {
+            Some(v) => tx.execute(&insert_sql, params![key, v]),
+            None => tx.execute(&delete_sql, params![key]),
+        };
+        result.map_err(|e| RestoreError::at_key(db_name, key, e.to_string()))?;
+    }
+
+    tracing::info!("  ✅ {} 已回滚到快照状态", db_name);
+    Ok(())
+}
+
+/// 将数据库恢复到指定快照（默认最新快照），成功后删除该快照
+///
+/// 两个数据库在同一个事务中写入，任意一个失败都会整体回滚
+pub fn restore_switch_snapshot(id: Option<&str>) -> Result<SwitchSnapshotInfo, RestoreError> {
+    let app_data = platform_utils::resolve_antigravity_db_path().map_err(RestoreError::new)?;
+    restore_switch_snapshot_to(&app_data, id)
+}
+
+/// 将指定的 state.vscdb（及同目录下的 state.vscdb.backup）恢复到切换前快照
+pub fn restore_switch_snapshot_to(
+    app_data: &Path,
+    id: Option<&str>,
+) -> Result<SwitchSnapshotInfo, RestoreError> {
+    let dir = snapshot_dir().map_err(RestoreError::new)?;
+    let files = snapshot_files(&dir).map_err(RestoreError::new)?;
+
+    let (snapshot_id, path) = match id {
+        Some(id) => files
+            .into_iter()
+            .find(|(file_id, _)| file_id == id)
+            .ok_or_else(|| RestoreError::new(format!("快照不存在: {}", id)))?,
+        None => files
+            .into_iter()
+            .next()
+            .ok_or_else(|| RestoreError::new("没有可撤销的账户切换"))?,
+    };
+
+    let snapshot = read_snapshot(&path).map_err(RestoreError::new)?;
+    tracing::info!("⏪ 撤销账户切换，回滚到快照: {}", snapshot_id);
+
+    let (mut conn, has_backup_db) = antigravity_restore::open_state_databases_for_write(app_data)?;
+
+    antigravity_restore::write_state_databases(&mut conn, has_backup_db, |tx| {
+        apply_database_snapshot(tx, "main", "state.vscdb", &snapshot.main)?;
+        match (&snapshot.backup, has_backup_db) {
+            (Some(backup), true) => {
+                apply_database_snapshot(tx, BACKUP_DB_SCHEMA, "state.vscdb.backup", backup)?
+            }
+            (Some(_), false) => tracing::warn!("  ⚠️ 备份数据库已不存在，跳过"),
+            (None, _) => tracing::warn!("  ℹ️ 快照中没有备份数据库内容，跳过"),
+        }
+        Ok(())
+    })?;
+
+    if let Err(e) = fs::remove_file(&path) {
+        tracing::warn!("  ⚠️ 删除已使用的快照失败: {}", e);
+    }
+
+    Ok(SwitchSnapshotInfo::from(&snapshot))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support;
+    use serde_json::json;
+
+    const NOTIFICATION_A: &str = "antigravity.notification.a";
+    const NOTIFICATION_B: &str = "antigravity.notification.b";
+
+    #[test]
+    fn undo_reverts_every_key_written_by_the_switch() {
+        let _vault = test_support::lock_vault();
+        let dir = test_support::temp_dir("switch-undo");
+        let app_data = dir.join("state.vscdb");
+        let backup_db = app_data.with_extension("vscdb.backup");
+        for path in [&app_data, &backup_db] {
+            test_support::create_state_db(
+                path,
+                &[
+                    (database::AUTH_STATUS, r#"{"email":"from@example.com"}"#),
+                    (NOTIFICATION_A, "old-a"),
+                    (database::ANALYTICS_LAST_UPLOAD_TIME, "1700000000"),
+                    (
+                        database::TARGET_STORAGE_MARKER,
+                        r#"{"antigravityAuthStatus":1}"#,
+                    ),
+                ],
+            );
+        }
+        let before = (
+            test_support::item_rows(&app_data),
+            test_support::item_rows(&backup_db),
+        );
+
+        let backup_data = json!({
+            database::AUTH_STATUS: r#"{"email":"to@example.com"}"#,
+            database::PROFILE_URL: "to-profile",
+            "notification_keys": [NOTIFICATION_A, NOTIFICATION_B],
+            NOTIFICATION_A: "new-a",
+            NOTIFICATION_B: "new-b",
+        });
+        let info = capture_switch_snapshot_from(&app_data, "to@example.com", &backup_data)
+            .unwrap()
+            .unwrap();
+        assert_eq!(info.from_account.as_deref(), Some("from@example.com"));
+
+        let backup_file = dir.join("backup.json");
+        fs::write(&backup_file, backup_data.to_string()).unwrap();
+        antigravity_restore::restore_backup_to(&app_data, &backup_file, DataCategory::ALL).unwrap();
+        assert_ne!(test_support::item_rows(&app_data), before.0);
+
+        restore_switch_snapshot_to(&app_data, Some(&info.id)).unwrap();
+        assert_eq!(test_support::item_rows(&app_data), before.0);
+        assert_eq!(test_support::item_rows(&backup_db), before.1);
+    }
+}
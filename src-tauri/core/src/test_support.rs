@@ -0,0 +1,69 @@
+// 测试辅助模块
+// 单元测试共用一个临时 agent 主目录（通过 ANTIGRAVITY_AGENT_HOME 指定），避免读写真实的配置目录
+
+use rusqlite::{params, Connection};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use crate::constants::paths;
+
+static AGENT_HOME: OnceLock<PathBuf> = OnceLock::new();
+
+/// 保险库和配置目录下的快照目录在测试间共享，读写它们的测试需要串行执行
+static VAULT_LOCK: Mutex<()> = Mutex::new(());
+
+/// 指向临时 agent 主目录（整个测试进程只初始化一次）
+pub fn agent_home() -> &'static Path {
+    AGENT_HOME.get_or_init(|| {
+        let dir =
+            std::env::temp_dir().join(format!("antigravity-agent-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).expect("创建测试目录失败");
+        std::env::set_var(paths::HOME_ENV, &dir);
+        dir
+    })
+}
+
+/// 独占保险库及其管理的目录（测试 panic 后锁仍可继续使用）
+pub fn lock_vault() -> MutexGuard<'static, ()> {
+    VAULT_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 在临时 agent 主目录下创建一个空目录（每个测试使用不同的名称）
+pub fn temp_dir(name: &str) -> PathBuf {
+    let dir = agent_home().join("tmp").join(name);
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("创建测试目录失败");
+    dir
+}
+
+/// 创建带 ItemTable 的状态数据库并写入指定的行
+pub fn create_state_db(path: &Path, rows: &[(&str, &str)]) -> Connection {
+    let conn = Connection::open(path).expect("创建测试数据库失败");
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS ItemTable (key TEXT UNIQUE ON CONFLICT REPLACE, value BLOB)",
+    )
+    .expect("创建 ItemTable 失败");
+    for (key, value) in rows {
+        conn.execute(
+            "INSERT INTO ItemTable (key, value) VALUES (?, ?)",
+            params![key, value],
+        )
+        .expect("写入测试数据失败");
+    }
+    conn
+}
+
+/// 按字段名排序的 ItemTable 全部内容
+pub fn item_rows(path: &Path) -> Vec<(String, String)> {
+    let conn = Connection::open(path).expect("打开测试数据库失败");
+    let mut stmt = conn
+        .prepare("SELECT key, CAST(value AS TEXT) FROM ItemTable ORDER BY key")
+        .unwrap();
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    rows
+}
@@ -9,13 +9,37 @@ use crate::config_manager::ConfigManager;
 
 /// 应用程序设置
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct AppSettings {
     /// 是否启用系统托盘
     pub system_tray_enabled: bool,
     /// 是否启用数据库监控
     pub db_monitoring_enabled: bool,
+    /// 是否额外推送包含完整数据的原始数据库变化事件（调试用）
+    pub db_monitor_raw_events: bool,
+    /// 是否在检测到登录、切换账户或令牌刷新时自动备份账户
+    pub auto_backup_enabled: bool,
+    /// 自动备份的防抖时间（秒），账户最后一次变化后等待这段时间再备份
+    pub auto_backup_debounce_secs: u64,
+    /// 定时备份设置
+    pub scheduled_backup: crate::backup_schedule::ScheduledBackupSettings,
     /// 是否启用静默启动（启动时最小化到托盘或后台）
     pub silent_start_enabled: bool,
+    /// 关闭 Antigravity 时等待其正常退出的秒数，超时后强制终止
+    pub shutdown_graceful_timeout_secs: u64,
+    /// 强制终止 Antigravity 后等待其退出的秒数
+    pub shutdown_kill_timeout_secs: u64,
+}
+
+impl AppSettings {
+    /// 关闭 Antigravity 时使用的超时设置
+    pub fn shutdown_options(&self) -> crate::process::ShutdownOptions {
+        crate::process::ShutdownOptions {
+            graceful_timeout: std::time::Duration::from_secs(self.shutdown_graceful_timeout_secs),
+            kill_timeout: std::time::Duration::from_secs(self.shutdown_kill_timeout_secs),
+            ..crate::process::ShutdownOptions::default()
+        }
+    }
 }
 
 impl Default for AppSettings {
@@ -23,7 +47,13 @@ impl Default for AppSettings {
         Self {
             system_tray_enabled: false, // 默认不启用，避免打扰用户
             db_monitoring_enabled: true, // 默认启用数据库监控
+            db_monitor_raw_events: false, // 默认只推送语义事件
+            auto_backup_enabled: true, // 默认启用，保证备份不会过期
+            auto_backup_debounce_secs: 10,
+            scheduled_backup: Default::default(), // 默认不启用定时备份
             silent_start_enabled: false, // 默认不启用静默启动，让用户看到应用界面
+            shutdown_graceful_timeout_secs: crate::process::ShutdownOptions::DEFAULT.graceful_timeout.as_secs(),
+            shutdown_kill_timeout_secs: crate::process::ShutdownOptions::DEFAULT.kill_timeout.as_secs(),
         }
     }
 }
@@ -55,6 +85,8 @@ impl AppSettingsManager {
             AppSettings::default()
         };
 
+        crate::process::set_shutdown_options(settings.shutdown_options());
+
         Self {
             settings: Mutex::new(settings),
             config_path,
@@ -73,6 +105,7 @@ impl AppSettingsManager {
     {
         let mut settings = self.settings.lock().unwrap();
         update_fn(&mut settings);
+        crate::process::set_shutdown_options(settings.shutdown_options());
         
         // 保存到文件
         let json = serde_json::to_string_pretty(&*settings)
@@ -0,0 +1,155 @@
+//! 自动备份模块
+//! 数据库监控检测到登录、切换账户或已知账户刷新令牌时，在后台备份该账户
+//!
+//! 同一账户的多次变化只在最后一次变化后等待防抖时间再备份一次
+//! 数据与最新备份版本相同时（例如恢复账户后监控到本程序写入的变化）不会新增版本
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager};
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::account_ops::{self, BackupTrigger};
+use crate::app_settings::AppSettingsManager;
+use crate::state_changes::StateEvent;
+use crate::state_db::StateDb;
+
+/// 后台备份（自动备份或定时备份）完成后推送到前端的事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountBackedUp {
+    email: String,
+    db_path: PathBuf,
+    /// 该账户此前没有备份
+    is_new: bool,
+}
+
+/// 通知前端账户已在后台备份
+pub(crate) fn emit_backed_up(app_handle: &AppHandle, email: String, db_path: PathBuf, is_new: bool) {
+    let payload = AccountBackedUp {
+        email,
+        db_path,
+        is_new,
+    };
+    if let Err(e) = app_handle.emit("account-backed-up", &payload) {
+        error!("❌ 推送备份完成事件失败: {}", e);
+    }
+}
+
+/// 自动备份调度器（按账户防抖）
+#[derive(Default)]
+pub struct AutoBackup {
+    /// 每个账户最近一次安排备份的序号，序号变化说明有更新的变化，旧的备份任务放弃执行
+    pending: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl AutoBackup {
+    /// 根据语义事件安排备份（自动备份未启用时不做任何事）
+    pub fn handle(
+        &self,
+        app_handle: &AppHandle,
+        db_path: &Path,
+        events: &[StateEvent],
+        cancel: &CancellationToken,
+    ) {
+        let Some(settings) = app_handle
+            .try_state::<AppSettingsManager>()
+            .map(|manager| manager.get_settings())
+        else {
+            return;
+        };
+        if !settings.auto_backup_enabled {
+            return;
+        }
+
+        for event in events {
+            if let Some(email) = Self::backup_target(event) {
+                self.schedule(
+                    app_handle.clone(),
+                    db_path.to_path_buf(),
+                    email,
+                    Duration::from_secs(settings.auto_backup_debounce_secs),
+                    cancel.clone(),
+                );
+            }
+        }
+    }
+
+    /// 需要备份的账户：新登录或切换到的账户，以及已有备份的账户刷新令牌
+    fn backup_target(event: &StateEvent) -> Option<String> {
+        match event {
+            StateEvent::LoggedIn { email, .. } => email.clone(),
+            StateEvent::AccountChanged { to, .. } => to.clone(),
+            StateEvent::TokenRefreshed { email: Some(email) } => {
+                match account_ops::has_backup(email) {
+                    Ok(true) => Some(email.clone()),
+                    Ok(false) => None,
+                    Err(e) => {
+                        warn!("⚠️ 检查账户备份失败 ({}): {}", email, e);
+                        None
+                    }
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn schedule(
+        &self,
+        app_handle: AppHandle,
+        db_path: PathBuf,
+        email: String,
+        debounce: Duration,
+        cancel: CancellationToken,
+    ) {
+        let sequence = {
+            let Ok(mut pending) = self.pending.lock() else {
+                return;
+            };
+            let sequence = pending.get(&email).map_or(0, |sequence| sequence + 1);
+            pending.insert(email.clone(), sequence);
+            sequence
+        };
+        info!("⏳ {} 秒后自动备份账户: {}", debounce.as_secs(), email);
+
+        let pending = self.pending.clone();
+        tokio::spawn(async move {
+            tokio::select! {
+                _ = cancel.cancelled() => return,
+                _ = tokio::time::sleep(debounce) => {}
+            }
+
+            // 防抖期间又有新的变化时，交给最新的任务备份
+            {
+                let Ok(mut pending) = pending.lock() else {
+                    return;
+                };
+                if pending.get(&email) != Some(&sequence) {
+                    return;
+                }
+                pending.remove(&email);
+            }
+
+            let state_db = StateDb::new(&db_path);
+            let backup_email = email.clone();
+            let result = tokio::task::spawn_blocking(move || {
+                account_ops::backup_account_from(&state_db, &backup_email, BackupTrigger::Auto)
+            })
+            .await
+            .map_err(|e| format!("备份任务异常退出: {}", e))
+            .and_then(|result| result);
+
+            match result {
+                Ok((_, is_overwrite)) => {
+                    info!("✅ 自动备份完成: {}", email);
+                    emit_backed_up(&app_handle, email, db_path, !is_overwrite);
+                }
+                Err(e) => error!("❌ 自动备份失败 ({}): {}", email, e),
+            }
+        });
+    }
+}
@@ -0,0 +1,150 @@
+//! 定时备份模块
+//! 按设置的计划（每隔若干小时或每天固定时间）在后台备份当前登录的账户，备份后按保留策略清理定时备份创建的旧版本
+//! （手动备份、自动备份和导入的版本不受保留策略影响）
+//!
+//! 同一时间只有一个计划任务，修改设置后通过 `apply` 重新安排
+
+use chrono::Local;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::{error, info, warn};
+
+use crate::account_ops::{self, BackupTrigger};
+use crate::account_store::AccountStore;
+use crate::audit_log::{self, AuditAction, AuditEntry};
+use crate::auto_backup;
+use crate::backup_history::RetentionPolicy;
+use crate::backup_schedule::ScheduledBackupSettings;
+use crate::state_db::StateDb;
+
+/// 一次定时备份的结果
+struct ScheduledRun {
+    email: String,
+    db_path: PathBuf,
+    is_new: bool,
+    pruned: Vec<String>,
+}
+
+/// 正在运行的计划任务
+struct SchedulerTask {
+    handle: JoinHandle<()>,
+    cancel: CancellationToken,
+}
+
+/// 定时备份调度器
+pub struct BackupScheduler {
+    app_handle: AppHandle,
+    task: Mutex<Option<SchedulerTask>>,
+}
+
+impl BackupScheduler {
+    pub fn new(app_handle: AppHandle) -> Self {
+        Self {
+            app_handle,
+            task: Mutex::new(None),
+        }
+    }
+
+    /// 按新的设置重新安排定时备份（未启用时只停止现有计划）
+    pub async fn apply(&self, settings: ScheduledBackupSettings) -> Result<(), String> {
+        settings.validate()?;
+
+        let mut task = self.task.lock().await;
+        if let Some(task) = task.take() {
+            task.cancel.cancel();
+            if let Err(e) = task.handle.await {
+                warn!("⚠️ 定时备份任务异常退出: {}", e);
+            }
+        }
+
+        if !settings.enabled {
+            info!("⏹️ 定时备份未启用");
+            return Ok(());
+        }
+
+        let cancel = CancellationToken::new();
+        let handle = tokio::spawn(Self::run(
+            self.app_handle.clone(),
+            settings,
+            cancel.clone(),
+        ));
+        *task = Some(SchedulerTask { handle, cancel });
+        Ok(())
+    }
+
+    /// 计划任务主循环（取消后退出）
+    async fn run(app_handle: AppHandle, settings: ScheduledBackupSettings, cancel: CancellationToken) {
+        loop {
+            let next_run = match settings.schedule.next_run(Local::now()) {
+                Ok(next_run) => next_run,
+                Err(e) => {
+                    error!("❌ 无法计算下一次定时备份时间: {}", e);
+                    return;
+                }
+            };
+            info!("⏰ 下一次定时备份: {}", next_run.to_rfc3339());
+
+            let wait = (next_run - Local::now()).to_std().unwrap_or_default();
+            tokio::select! {
+                _ = cancel.cancelled() => {
+                    info!("⏹️ 定时备份已停止");
+                    return;
+                }
+                _ = tokio::time::sleep(wait) => {}
+            }
+
+            info!("💾 开始定时备份");
+            let retention = settings.retention;
+            let result = tokio::task::spawn_blocking(move || Self::run_once(&retention))
+                .await
+                .map_err(|e| format!("定时备份任务异常退出: {}", e))
+                .and_then(|result| result);
+
+            match result {
+                Ok(Some(run)) => {
+                    info!(
+                        "✅ 定时备份完成: {}（清理 {} 个旧版本）",
+                        run.email,
+                        run.pruned.len()
+                    );
+                    auto_backup::emit_backed_up(&app_handle, run.email, run.db_path, run.is_new);
+                }
+                Ok(None) => info!("ℹ️ 未检测到已登录用户，跳过本次定时备份"),
+                Err(e) => error!("❌ 定时备份失败: {}", e),
+            }
+        }
+    }
+
+    /// 备份当前登录的账户并按保留策略清理（未登录时返回 `Ok(None)`）
+    fn run_once(retention: &RetentionPolicy) -> Result<Option<ScheduledRun>, String> {
+        let state_db = StateDb::locate()?;
+        let Some(email) = state_db.current_email()? else {
+            return Ok(None);
+        };
+
+        let (_, is_overwrite) =
+            account_ops::backup_account_from(&state_db, &email, BackupTrigger::Scheduled)?;
+
+        // 按保留策略清理定时备份的旧版本，有版本被删除或清理失败时记录到审计日志
+        let pruned = AccountStore::open_default()?.apply_retention(&email, retention);
+        if pruned.as_ref().map_or(true, |pruned| !pruned.is_empty()) {
+            let count = pruned.as_ref().map_or(0, |pruned| pruned.len());
+            audit_log::record(
+                AuditEntry::new(AuditAction::Delete)
+                    .to_account(Some(&email))
+                    .detail(format!("定时备份保留策略清理 {} 个旧版本", count))
+                    .result(&pruned),
+            );
+        }
+
+        Ok(Some(ScheduledRun {
+            email,
+            db_path: state_db.path().to_path_buf(),
+            is_new: !is_overwrite,
+            pruned: pruned?,
+        }))
+    }
+}
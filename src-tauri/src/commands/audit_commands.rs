@@ -0,0 +1,30 @@
+//! 审计日志命令
+//! 按账户和时间范围查询账户操作记录，并导出为 CSV 或 JSON
+
+use crate::audit_log::{self, AuditEntry, AuditQuery, ExportFormat};
+
+/// 查询审计日志（按时间顺序）
+///
+/// `since` / `until` 支持 RFC3339 或 `YYYY-MM-DD`，不指定时不限制
+#[tauri::command]
+pub async fn query_audit_log(
+    account: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<Vec<AuditEntry>, String> {
+    let query = AuditQuery::parse(account, since.as_deref(), until.as_deref())?;
+    audit_log::query(&query)
+}
+
+/// 将满足条件的审计记录导出为 CSV 或 JSON 文本
+#[tauri::command]
+pub async fn export_audit_log(
+    format: String,
+    account: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<String, String> {
+    let format: ExportFormat = format.parse()?;
+    let query = AuditQuery::parse(account, since.as_deref(), until.as_deref())?;
+    audit_log::export(&audit_log::query(&query)?, format)
+}
@@ -1,34 +1,15 @@
+use crate::account_store::AccountStore;
+use crate::antigravity_restore::RestoreError;
+use crate::audit_log::{self, AuditAction, AuditEntry};
+use crate::backup_history;
+use crate::data_category::DataCategory;
 use crate::AppState;
-use serde::{Deserialize, Serialize};
-use serde_json::Value;
 use std::path::Path;
 /// 备份相关命令
 /// 负责配置文件和账户的备份、恢复、删除等操作
 use tauri::State;
 
-/// 备份数据收集结构
-#[derive(Serialize, Deserialize, Debug)]
-pub struct BackupData {
-    filename: String,
-    #[serde(rename = "content")]
-    content: Value,
-    #[serde(rename = "timestamp")]
-    timestamp: u64,
-}
-
-/// 恢复结果
-#[derive(Serialize, Deserialize, Debug)]
-pub struct RestoreResult {
-    #[serde(rename = "restoredCount")]
-    restored_count: u32,
-    failed: Vec<FailedBackup>,
-}
-
-#[derive(Serialize, Deserialize, Debug)]
-pub struct FailedBackup {
-    filename: String,
-    error: String,
-}
+pub use crate::account_store::{BackupData, FailedBackup, RestoreResult};
 
 use std::fs;
 use std::io::Write;
@@ -139,65 +120,36 @@ pub async fn restore_profile(
     Ok(format!("还原成功到: {}", target_path))
 }
 
+/// 应用配置目录下的账户备份存储
+pub(crate) fn account_store(state: &AppState) -> AccountStore {
+    AccountStore::new(state.config_dir.join("antigravity-accounts"))
+}
+
 /// 列出所有可用备份
 #[tauri::command]
 pub async fn list_backups(state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    let mut all_backups = Vec::new();
-
-    // 只读取Antigravity账户目录中的JSON文件
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
-
-    if antigravity_dir.exists() {
-        for entry in
-            fs::read_dir(&antigravity_dir).map_err(|e| format!("读取用户目录失败: {}", e))?
-        {
-            let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
-            let path = entry.path();
-
-            if path.extension().is_some_and(|ext| ext == "json") {
-                if let Some(name) = path.file_stem() {
-                    all_backups.push(name.to_string_lossy().to_string());
-                }
-            }
-        }
-    }
-
-    Ok(all_backups)
+    // 同时识别版本目录和旧版单文件
+    account_store(&state).accounts()
 }
 
-/// 获取最近使用的账户列表（基于文件修改时间排序）
+/// 获取最近使用的账户列表（基于最新备份的修改时间排序）
 #[tauri::command]
 pub async fn get_recent_accounts(
     state: State<'_, AppState>,
     limit: Option<usize>,
 ) -> Result<Vec<String>, String> {
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+    let store = account_store(&state);
 
-    if !antigravity_dir.exists() {
+    if !store.root().exists() {
         return Ok(Vec::new());
     }
 
     let mut accounts_with_time: Vec<(String, std::time::SystemTime)> = Vec::new();
 
-    // 读取所有账户文件并获取修改时间
-    for entry in fs::read_dir(&antigravity_dir).map_err(|e| format!("读取用户目录失败: {}", e))? {
-        let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
-        let path = entry.path();
-
-        if path.extension().is_some_and(|ext| ext == "json") {
-            if let Some(name) = path.file_stem() {
-                let account_name = name.to_string_lossy().to_string();
-                
-                // 获取文件修改时间
-                match fs::metadata(&path) {
-                    Ok(metadata) => {
-                        if let Ok(modified) = metadata.modified() {
-                            accounts_with_time.push((account_name, modified));
-                        }
-                    }
-                    Err(_) => continue,
-                }
-            }
+    // 读取所有账户并获取最新版本的修改时间
+    for account_name in store.accounts()? {
+        if let Some(modified) = store.last_modified(&account_name) {
+            accounts_with_time.push((account_name, modified));
         }
     }
 
@@ -217,62 +169,75 @@ pub async fn get_recent_accounts(
     Ok(result)
 }
 
-
-/// 收集所有备份文件的完整内容
+/// 列出指定账户的所有备份版本（最新的在前）
 #[tauri::command]
-pub async fn collect_backup_contents(
+pub async fn list_backup_revisions(
+    name: String,
     state: State<'_, AppState>,
-) -> Result<Vec<BackupData>, String> {
-    let mut backups_with_content = Vec::new();
-
-    // 读取Antigravity账户目录中的JSON文件
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+) -> Result<Vec<backup_history::BackupRevision>, String> {
+    account_store(&state).revisions(&name)
+}
 
-    if !antigravity_dir.exists() {
-        return Ok(backups_with_content);
+/// 将指定账户的某个备份版本恢复到 Antigravity（`categories` 为空时恢复所有数据类别）
+#[tauri::command]
+pub async fn restore_backup_revision(
+    name: String,
+    revision: String,
+    categories: Option<Vec<DataCategory>>,
+    state: State<'_, AppState>,
+) -> Result<String, RestoreError> {
+    let backup_file = account_store(&state)
+        .backup_file(&name, Some(&revision))
+        .map_err(RestoreError::new)?;
+
+    if !backup_file.is_file() {
+        return Err(RestoreError::new(format!(
+            "备份版本不存在: {} ({})",
+            name, revision
+        )));
     }
 
-    for entry in fs::read_dir(&antigravity_dir).map_err(|e| format!("读取用户目录失败: {}", e))?
-    {
-        let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
-        let path = entry.path();
+    let categories = categories.as_deref().unwrap_or(DataCategory::ALL);
+    crate::account_ops::restore_account(&name, Some(&revision), categories).await
+}
 
-        if path.extension().is_some_and(|ext| ext == "json") {
-            let filename = path
-                .file_name()
-                .and_then(|name| name.to_str())
-                .map(|s| s.to_string())
-                .unwrap_or_default();
+/// 清理旧的备份版本
+///
+/// 指定 `name` 时只清理该账户，否则清理所有账户；每个账户保留最新的 `keep` 个版本
+#[tauri::command]
+pub async fn prune_backup_revisions(
+    name: Option<String>,
+    keep: usize,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    if keep == 0 {
+        return Err("至少需要保留 1 个版本".to_string());
+    }
 
-            if filename.is_empty() {
-                continue;
-            }
+    let store = account_store(&state);
+    let accounts = match name {
+        Some(name) => vec![name],
+        None => store.accounts()?,
+    };
 
-            match fs::read_to_string(&path).map_err(|e| format!("读取文件失败 {}: {}", filename, e))
-            {
-                Ok(content) => match serde_json::from_str::<serde_json::Value>(&content) {
-                    Ok(json_value) => {
-                        backups_with_content.push(BackupData {
-                            filename,
-                            content: json_value,
-                            timestamp: std::time::SystemTime::now()
-                                .duration_since(std::time::UNIX_EPOCH)
-                                .unwrap_or_default()
-                                .as_secs(),
-                        });
-                    }
-                    Err(e) => {
-                        println!("⚠️ 跳过损坏的备份文件 {}: {}", filename, e);
-                    }
-                },
-                Err(_) => {
-                    println!("⚠️ 跳过无法读取的文件: {}", filename);
-                }
-            }
-        }
+    let mut removed_count = 0;
+    for account in &accounts {
+        removed_count += store.prune(account, keep)?.len();
     }
 
-    Ok(backups_with_content)
+    Ok(format!(
+        "已清理 {} 个旧版本（每个账户保留 {} 个）",
+        removed_count, keep
+    ))
+}
+
+/// 收集所有备份文件的完整内容
+#[tauri::command]
+pub async fn collect_backup_contents(
+    state: State<'_, AppState>,
+) -> Result<Vec<BackupData>, String> {
+    // 每个账户只导出最新版本，文件名保持 <email>.json
+    account_store(&state).export()
 }
 
 /// 恢复备份文件到本地
@@ -281,78 +246,52 @@ pub async fn restore_backup_files(
     backups: Vec<BackupData>,
     state: State<'_, AppState>,
 ) -> Result<RestoreResult, String> {
-    let mut results = RestoreResult {
-        restored_count: 0,
-        failed: Vec::new(),
-    };
-
-    // 获取目标目录
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
-
-    // 确保目录存在
-    if let Err(e) = fs::create_dir_all(&antigravity_dir) {
-        return Err(format!("创建目录失败: {}", e));
-    }
-
-    // 遍历每个备份
-    for backup in backups {
-        let file_path = antigravity_dir.join(&backup.filename);
-
-        match fs::write(
-            &file_path,
-            serde_json::to_string_pretty(&backup.content).unwrap_or_default(),
-        )
-        .map_err(|e| format!("写入文件失败: {}", e))
-        {
-            Ok(_) => {
-                results.restored_count += 1;
-            }
-            Err(e) => {
-                results.failed.push(FailedBackup {
-                    filename: backup.filename,
-                    error: e,
-                });
-            }
-        }
-    }
-
-    Ok(results)
+    // 遍历每个备份，作为对应账户的新版本写入
+    account_store(&state).import(backups)
 }
 
 /// 删除指定备份
 #[tauri::command]
 pub async fn delete_backup(name: String, state: State<'_, AppState>) -> Result<String, String> {
-    // 只删除Antigravity账户JSON文件
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
-    let antigravity_file = antigravity_dir.join(format!("{}.json", name));
-
-    if antigravity_file.exists() {
-        fs::remove_file(&antigravity_file).map_err(|e| format!("删除用户文件失败: {}", e))?;
+    // 删除账户的所有版本以及旧版单文件
+    if account_store(&state).delete(&name)? {
         Ok(format!("删除用户成功: {}", name))
     } else {
         Err("用户文件不存在".to_string())
     }
 }
 
-/// 清空所有备份
+/// 清空所有备份（记录到审计日志）
 #[tauri::command]
 pub async fn clear_all_backups(state: State<'_, AppState>) -> Result<String, String> {
-    let antigravity_dir = state.config_dir.join("antigravity-accounts");
+    let result = clear_backup_dir(&state.config_dir.join("antigravity-accounts"));
+    audit_log::record(
+        AuditEntry::new(AuditAction::Delete)
+            .detail("清空所有账户备份")
+            .result(&result),
+    );
+    result
+}
 
+fn clear_backup_dir(antigravity_dir: &Path) -> Result<String, String> {
     if antigravity_dir.exists() {
         // 读取目录中的所有文件
         let mut deleted_count = 0;
         for entry in
-            fs::read_dir(&antigravity_dir).map_err(|e| format!("读取用户目录失败: {}", e))?
+            fs::read_dir(antigravity_dir).map_err(|e| format!("读取用户目录失败: {}", e))?
         {
             let entry = entry.map_err(|e| format!("读取目录项失败: {}", e))?;
             let path = entry.path();
 
-            // 只删除 JSON 文件
+            // 删除旧版 JSON 文件和版本目录
             if path.extension().is_some_and(|ext| ext == "json") {
                 fs::remove_file(&path)
                     .map_err(|e| format!("删除文件 {} 失败: {}", path.display(), e))?;
                 deleted_count += 1;
+            } else if path.is_dir() {
+                fs::remove_dir_all(&path)
+                    .map_err(|e| format!("删除目录 {} 失败: {}", path.display(), e))?;
+                deleted_count += 1;
             }
         }
 
@@ -365,4 +304,54 @@ pub async fn clear_all_backups(state: State<'_, AppState>) -> Result<String, Str
     }
 }
 
+/// 保存当前 Antigravity 数据库的完整快照（state.vscdb 和 state.vscdb.backup，无需关闭进程）
+#[tauri::command]
+pub async fn create_db_snapshot(
+    label: Option<String>,
+) -> Result<crate::db_snapshot::DbSnapshot, String> {
+    crate::account_ops::snapshot_databases(label.as_deref())
+}
+
+/// 列出所有完整数据库快照（最新的在前）
+#[tauri::command]
+pub async fn list_db_snapshots() -> Result<Vec<crate::db_snapshot::DbSnapshot>, String> {
+    crate::db_snapshot::list_snapshots()
+}
+
+/// 恢复完整数据库快照
+///
+/// 关闭 Antigravity，保存当前数据库后用快照覆盖，默认重新启动
+#[tauri::command]
+pub async fn restore_db_snapshot(id: String, restart: Option<bool>) -> Result<String, String> {
+    crate::log_async_command!("restore_db_snapshot", async {
+        let final_message =
+            crate::account_ops::restore_db_snapshot(&id, restart.unwrap_or(true)).await?;
+        tracing::info!("🎉 完整数据库快照恢复完成: {}", final_message);
+        Ok(final_message)
+    })
+}
+
+/// 删除完整数据库快照
+#[tauri::command]
+pub async fn delete_db_snapshot(id: String) -> Result<String, String> {
+    crate::db_snapshot::delete_snapshot(&id)?;
+    Ok(format!("已删除快照: {}", id))
+}
+
+/// 清理旧的完整数据库快照
+///
+/// 指定 `keep` 时只保留最新的 `keep` 个，否则按保留策略清理（未指定策略时使用默认策略）
+#[tauri::command]
+pub async fn prune_db_snapshots(
+    keep: Option<usize>,
+    retention: Option<backup_history::RetentionPolicy>,
+) -> Result<String, String> {
+    let removed = match keep {
+        Some(0) => return Err("至少需要保留 1 个快照".to_string()),
+        Some(keep) => crate::db_snapshot::prune_snapshots(keep)?,
+        None => crate::db_snapshot::apply_retention(&retention.unwrap_or_default())?,
+    };
+    Ok(format!("已清理 {} 个旧快照", removed.len()))
+}
+
 // 备份相关函数将在后续步骤中移动到这里
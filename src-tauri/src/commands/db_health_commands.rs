@@ -0,0 +1,29 @@
+//! 数据库健康检查命令
+//! 对 state.vscdb 和 state.vscdb.backup 执行完整性检查、VACUUM，以及检查和修复 Marker
+
+use crate::db_health::{DatabaseHealth, MarkerReport, VacuumResult};
+use crate::state_db::StateDb;
+
+/// 完整性检查，并统计文件大小和空闲页（只读）
+#[tauri::command]
+pub async fn check_state_db_health() -> Result<Vec<DatabaseHealth>, String> {
+    StateDb::locate()?.health()
+}
+
+/// 执行 VACUUM 回收空闲页（数据库被占用时拒绝执行，需要先关闭 Antigravity）
+#[tauri::command]
+pub async fn vacuum_state_db() -> Result<Vec<VacuumResult>, String> {
+    StateDb::locate()?.vacuum()
+}
+
+/// 检查 __$__targetStorageMarker 与实际数据是否一致（只读）
+#[tauri::command]
+pub async fn check_state_db_marker() -> Result<Vec<MarkerReport>, String> {
+    StateDb::locate()?.check_marker()
+}
+
+/// 修复 Marker，返回修复前的检查结果（需要先关闭 Antigravity）
+#[tauri::command]
+pub async fn repair_state_db_marker() -> Result<Vec<MarkerReport>, String> {
+    StateDb::locate()?.repair_marker()
+}
@@ -1,7 +1,7 @@
 //! 数据库监控相关命令
 //! 提供数据库监控状态的查询和控制功能
 
-use crate::db_monitor::DatabaseMonitor;
+use crate::db_monitor::{DatabaseMonitor, MonitorState, MonitorStatus};
 use std::sync::Arc;
 use tauri::{AppHandle, Manager};
 
@@ -11,9 +11,19 @@ pub async fn is_database_monitoring_running(
     app: AppHandle,
 ) -> Result<bool, String> {
     crate::log_async_command!("is_database_monitoring_running", async {
-        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
-        let settings = settings_manager.get_settings();
-        Ok(settings.db_monitoring_enabled)
+        let monitor = app.state::<Arc<DatabaseMonitor>>();
+        Ok(monitor.status().await.state != MonitorState::Stopped)
+    })
+}
+
+/// 获取数据库监控详细状态（运行状态、启动时间、最近错误和监控的数据库）
+#[tauri::command]
+pub async fn get_database_monitoring_status(
+    app: AppHandle,
+) -> Result<MonitorStatus, String> {
+    crate::log_async_command!("get_database_monitoring_status", async {
+        let monitor = app.state::<Arc<DatabaseMonitor>>();
+        Ok(monitor.status().await)
     })
 }
 
@@ -41,3 +51,16 @@ pub async fn stop_database_monitoring(
         Ok("数据库监控已停止".to_string())
     })
 }
+
+/// 重启数据库监控
+#[tauri::command]
+pub async fn restart_database_monitoring(
+    app: AppHandle,
+) -> Result<String, String> {
+    crate::log_async_command!("restart_database_monitoring", async {
+        let monitor = app.state::<Arc<DatabaseMonitor>>();
+        monitor.restart_monitoring().await
+            .map_err(|e| format!("重启监控失败: {}", e))?;
+        Ok("数据库监控已重启".to_string())
+    })
+}
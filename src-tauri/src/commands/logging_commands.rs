@@ -1,22 +1,40 @@
 //! 日志相关命令
 //! 提供日志管理功能
 
-use dirs;
 use std::fs;
+use std::path::PathBuf;
+use crate::constants::paths;
+use crate::path_utils::AppPaths;
 use crate::utils::log_sanitizer::LogSanitizer;
 
+/// 当前的日志文件（日志按日滚动，取日期最新的 `antigravity-agent.YYYY-MM-DD.log`）
+fn current_log_file() -> Result<Option<PathBuf>, String> {
+    let log_dir = AppPaths::logs_dir().ok_or("无法获取日志目录")?;
+    if !log_dir.exists() {
+        return Ok(None);
+    }
+
+    let prefix = format!("{}.", paths::LOG_FILE_PREFIX);
+    let entries = fs::read_dir(&log_dir).map_err(|e| format!("读取日志目录失败: {}", e))?;
+
+    Ok(entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_prefix(&prefix))
+                .and_then(|name| name.strip_suffix(".log"))
+                .is_some_and(|date| chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok())
+        })
+        .max())
+}
+
 /// 获取日志文件信息
 /// 返回日志文件路径、大小等信息，用于前端显示状态
 #[tauri::command]
 pub async fn get_log_info() -> Result<LogInfo, String> {
-    let log_dir = dirs::config_dir()
-        .unwrap_or_else(|| std::path::PathBuf::from("."))
-        .join("antigravity-agent")
-        .join("logs");
-
-    let log_file = log_dir.join("antigravity-agent.log");
-
-    if log_file.exists() {
+    if let Some(log_file) = current_log_file()? {
         let metadata = fs::metadata(&log_file).map_err(|e| format!("获取文件信息失败: {}", e))?;
 
         let modified = metadata
@@ -68,30 +86,43 @@ pub async fn write_text_file(path: String, content: String) -> Result<String, St
     })
 }
 
+/// 加密配置数据
+/// 使用 Argon2id + AES-256-GCM 加密导出内容，返回 Base64 字符串
 #[tauri::command]
-pub async fn decrypt_config_data(encrypted_data: String, password: String) -> Result<String, String> {
-    crate::log_async_command!("decrypt_config_data", async {
-        use base64::{Engine as _, engine::general_purpose::STANDARD};
-
-        // 使用 XOR 解密
-        let encrypted = STANDARD
-            .decode(encrypted_data)
-            .map_err(|e| format!("Base64解码失败: {}", e))?;
+pub async fn encrypt_config_data(json_data: String, password: String) -> Result<String, String> {
+    crate::log_async_command!("encrypt_config_data", async {
+        // 验证是否为有效的JSON
+        if serde_json::from_str::<serde_json::Value>(&json_data).is_err() {
+            return Err("待加密的数据不是有效的JSON格式".to_string());
+        }
 
-        let encrypted_bytes = encrypted;
-        let key_bytes = password.as_bytes();
-        let mut decrypted_bytes = vec![0u8; encrypted_bytes.len()];
+        let encrypted = crate::crypto::encrypt_to_base64(&json_data, &password)?;
 
-        for (i, &byte) in encrypted_bytes.iter().enumerate() {
-            decrypted_bytes[i] = byte ^ key_bytes[i % key_bytes.len()];
-        }
+        tracing::info!("🔒 配置文件加密成功");
+        Ok(encrypted)
+    })
+}
 
-        let decrypted_json = String::from_utf8(decrypted_bytes)
-            .map_err(|e| format!("UTF-8解码失败: {}", e))?;
+/// 解密配置数据
+/// 自动识别新版认证加密格式，并兼容旧版 XOR 导出文件
+#[tauri::command]
+pub async fn decrypt_config_data(encrypted_data: String, password: String) -> Result<String, String> {
+    crate::log_async_command!("decrypt_config_data", async {
+        let (decrypted_json, is_legacy) =
+            crate::crypto::decrypt_from_base64(&encrypted_data, &password)?;
 
         // 验证是否为有效的JSON
         if serde_json::from_str::<serde_json::Value>(&decrypted_json).is_err() {
-            return Err("解密后的数据不是有效的JSON格式".to_string());
+            return Err(if is_legacy {
+                // 旧版格式无法校验完整性，解密结果异常通常意味着密码错误
+                crate::crypto::CryptoError::WrongPasswordOrTampered.to_string()
+            } else {
+                "解密后的数据不是有效的JSON格式".to_string()
+            });
+        }
+
+        if is_legacy {
+            tracing::warn!("⚠️ 使用旧版 XOR 格式解密，建议重新导出以使用新的加密格式");
         }
 
         tracing::info!("🔓 配置文件解密成功");
@@ -102,16 +133,9 @@ pub async fn decrypt_config_data(encrypted_data: String, password: String) -> Re
 #[tauri::command]
 pub async fn clear_logs() -> Result<String, String> {
     crate::log_async_command!("clear_logs", async {
-        let log_dir = dirs::config_dir()
-            .unwrap_or_else(|| std::path::PathBuf::from("."))
-            .join("antigravity-agent")
-            .join("logs");
-
-        let log_file = log_dir.join("antigravity-agent.log");
-
-        if log_file.exists() {
+        if let Some(log_file) = current_log_file()? {
             // 备份当前日志（可选）
-            let backup_path = log_dir.join("antigravity-agent.backup.log");
+            let backup_path = log_file.with_file_name(format!("{}.backup.log", paths::LOG_FILE_PREFIX));
             if let Ok(_) = fs::copy(&log_file, &backup_path) {
                 tracing::info!("📦 日志已备份");
             }
@@ -0,0 +1,66 @@
+//! 账户元数据命令
+//! 编辑账户的标签、备注、分类标签、颜色和置顶标记，并按这些信息筛选账户
+
+use tauri::{Manager, State};
+
+use crate::account_metadata::{AccountFilter, AccountMetadata, AccountWithMetadata};
+use crate::commands::backup_commands::account_store;
+use crate::system_tray::SystemTrayManager;
+use crate::AppState;
+
+/// 获取账户元数据（未设置时返回默认值）
+#[tauri::command]
+pub async fn get_account_metadata(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<AccountMetadata, String> {
+    Ok(account_store(&state).metadata(&name))
+}
+
+/// 更新账户元数据，返回规范化后的内容
+///
+/// 托盘菜单会随之重建，以显示新的标签和置顶顺序
+#[tauri::command]
+pub async fn update_account_metadata(
+    name: String,
+    metadata: AccountMetadata,
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<AccountMetadata, String> {
+    let store = account_store(&state);
+    if !store.accounts()?.contains(&name) {
+        return Err(format!("账户不存在: {}", name));
+    }
+
+    let metadata = store.set_metadata(&name, metadata)?;
+    tracing::info!(account = %name, "🏷️ 账户元数据已更新");
+
+    let system_tray = app.state::<SystemTrayManager>();
+    if let Err(e) = system_tray.update_menu(&app).await {
+        tracing::warn!("⚠️ 重建托盘菜单失败: {}", e);
+    }
+
+    Ok(metadata)
+}
+
+/// 按元数据筛选账户（置顶的在前），未指定条件时返回所有账户
+#[tauri::command]
+pub async fn list_accounts_with_metadata(
+    filter: Option<AccountFilter>,
+    state: State<'_, AppState>,
+) -> Result<Vec<AccountWithMetadata>, String> {
+    account_store(&state).accounts_with_metadata(&filter.unwrap_or_default())
+}
+
+/// 列出所有账户使用过的分类标签（按名称排序）
+#[tauri::command]
+pub async fn list_account_tags(state: State<'_, AppState>) -> Result<Vec<String>, String> {
+    let mut tags: Vec<String> = account_store(&state)
+        .accounts_with_metadata(&AccountFilter::default())?
+        .into_iter()
+        .flat_map(|entry| entry.metadata.tags)
+        .collect();
+    tags.sort();
+    tags.dedup();
+    Ok(tags)
+}
@@ -27,13 +27,29 @@ pub mod settings_commands;
 // 数据库监控命令
 pub mod db_monitor_commands;
 
+// 账户保险库命令
+pub mod vault_commands;
+
+// 账户元数据命令
+pub mod metadata_commands;
+
+// 审计日志命令
+pub mod audit_commands;
+
+// 数据库健康检查命令
+pub mod db_health_commands;
+
 
 // 重新导出所有命令，保持与 main.rs 的兼容性
 pub use account_commands::*;
+pub use audit_commands::*;
 pub use backup_commands::*;
+pub use db_health_commands::*;
 pub use db_monitor_commands::*;
 pub use logging_commands::*;
+pub use metadata_commands::*;
 pub use platform_commands::*;
 pub use process_commands::*;
 pub use settings_commands::*;
 pub use tray_commands::*;
+pub use vault_commands::*;
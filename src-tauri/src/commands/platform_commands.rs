@@ -2,6 +2,7 @@
 //! 负责获取平台信息、安装位置验证等跨平台操作
 
 use serde_json::Value;
+use tauri::Manager;
 
 /// 获取平台信息
 #[tauri::command]
@@ -133,7 +134,7 @@ pub async fn detect_antigravity_executable() -> Result<serde_json::Value, String
 
 /// 保存用户自定义的 Antigravity 数据目录路径
 #[tauri::command]
-pub async fn save_antigravity_path(path: String) -> Result<String, String> {
+pub async fn save_antigravity_path(app: tauri::AppHandle, path: String) -> Result<String, String> {
     // 1. 验证路径有效性
     if !crate::antigravity_path_config::validate_antigravity_path(&path) {
         return Err(format!("路径无效：未在目录 '{}' 中找到 state.vscdb 文件", path));
@@ -141,6 +142,11 @@ pub async fn save_antigravity_path(path: String) -> Result<String, String> {
     
     // 2. 保存路径到配置
     crate::antigravity_path_config::save_custom_data_path(path.clone())?;
+
+    // 3. 让数据库监控切换到新的路径
+    if let Some(monitor) = app.try_state::<std::sync::Arc<crate::db_monitor::DatabaseMonitor>>() {
+        monitor.reload_paths();
+    }
     
     Ok(format!("已保存 Antigravity 数据目录路径: {}", path))
 }
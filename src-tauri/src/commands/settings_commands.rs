@@ -28,11 +28,106 @@ pub async fn save_db_monitoring_state(
             settings.db_monitoring_enabled = enabled;
         })?;
 
+        // 立即启动或停止监控，无需等待前端再次调用
+        let monitor = app.state::<std::sync::Arc<crate::db_monitor::DatabaseMonitor>>();
+        if enabled {
+            monitor.start_monitoring().await?;
+        } else {
+            monitor.stop_monitoring().await;
+        }
+
         let status_text = if enabled { "已启用" } else { "已禁用" };
         Ok(format!("数据库监控{}", status_text))
     })
 }
 
+/// 保存是否推送原始数据库变化事件（调试用）
+#[tauri::command]
+pub async fn save_db_monitor_raw_events(
+    app: AppHandle,
+    enabled: bool,
+) -> Result<String, String> {
+    crate::log_async_command!("save_db_monitor_raw_events", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+
+        settings_manager.update_settings(|settings| {
+            settings.db_monitor_raw_events = enabled;
+        })?;
+
+        let status_text = if enabled { "已启用" } else { "已禁用" };
+        Ok(format!("原始数据库变化事件{}", status_text))
+    })
+}
+
+/// 保存自动备份设置
+///
+/// 检测到登录、切换账户或已知账户刷新令牌后等待 `debounce_secs` 秒再备份
+#[tauri::command]
+pub async fn save_auto_backup_settings(
+    app: AppHandle,
+    enabled: bool,
+    debounce_secs: u64,
+) -> Result<String, String> {
+    crate::log_async_command!("save_auto_backup_settings", async {
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+
+        settings_manager.update_settings(|settings| {
+            settings.auto_backup_enabled = enabled;
+            settings.auto_backup_debounce_secs = debounce_secs;
+        })?;
+
+        if enabled {
+            Ok(format!("自动备份已启用（防抖 {} 秒）", debounce_secs))
+        } else {
+            Ok("自动备份已禁用".to_string())
+        }
+    })
+}
+
+/// 保存定时备份设置并立即按新设置重新安排
+#[tauri::command]
+pub async fn save_scheduled_backup_settings(
+    app: AppHandle,
+    settings: crate::backup_schedule::ScheduledBackupSettings,
+) -> Result<String, String> {
+    crate::log_async_command!("save_scheduled_backup_settings", async {
+        settings.validate()?;
+
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+        settings_manager.update_settings(|current| {
+            current.scheduled_backup = settings.clone();
+        })?;
+
+        let scheduler = app.state::<std::sync::Arc<crate::backup_scheduler::BackupScheduler>>();
+        scheduler.apply(settings.clone()).await?;
+
+        if settings.enabled {
+            Ok("定时备份已启用".to_string())
+        } else {
+            Ok("定时备份已禁用".to_string())
+        }
+    })
+}
+
+/// 获取自定义备份字段（内置字段之外需要备份和恢复的字段或匹配模式）
+#[tauri::command]
+pub async fn get_extra_backup_keys() -> Result<Vec<crate::extra_keys::ExtraKey>, String> {
+    crate::extra_keys::load()
+}
+
+/// 保存自定义备份字段
+///
+/// 之后的备份会把匹配到的字段及其设置一起写入备份文件，恢复时以备份文件为准
+#[tauri::command]
+pub async fn save_extra_backup_keys(
+    keys: Vec<crate::extra_keys::ExtraKey>,
+) -> Result<String, String> {
+    crate::log_async_command!("save_extra_backup_keys", async {
+        crate::extra_keys::save(&keys)?;
+        Ok(format!("已保存 {} 条自定义备份字段", keys.len()))
+    })
+}
+
 /// 获取静默启动状态
 #[tauri::command]
 pub async fn is_silent_start_enabled(
@@ -63,6 +158,34 @@ pub async fn save_silent_start_state(
     })
 }
 
+/// 保存关闭 Antigravity 时的超时设置（秒）
+///
+/// 先请求正常退出，`graceful_timeout_secs` 后仍未退出则强制终止，再等待 `kill_timeout_secs`
+#[tauri::command]
+pub async fn save_shutdown_timeouts(
+    app: AppHandle,
+    graceful_timeout_secs: u64,
+    kill_timeout_secs: u64,
+) -> Result<String, String> {
+    crate::log_async_command!("save_shutdown_timeouts", async {
+        if kill_timeout_secs == 0 {
+            return Err("强制终止后的等待时间必须大于 0 秒".to_string());
+        }
+
+        let settings_manager = app.state::<crate::app_settings::AppSettingsManager>();
+
+        settings_manager.update_settings(|settings| {
+            settings.shutdown_graceful_timeout_secs = graceful_timeout_secs;
+            settings.shutdown_kill_timeout_secs = kill_timeout_secs;
+        })?;
+
+        Ok(format!(
+            "关闭超时已设置: 正常退出 {} 秒，强制终止 {} 秒",
+            graceful_timeout_secs, kill_timeout_secs
+        ))
+    })
+}
+
 /// 获取所有应用设置
 #[tauri::command]
 pub async fn get_all_settings(
@@ -75,7 +198,14 @@ pub async fn get_all_settings(
         Ok(serde_json::json!({
             "system_tray_enabled": settings.system_tray_enabled,
             "db_monitoring_enabled": settings.db_monitoring_enabled,
-            "silent_start_enabled": settings.silent_start_enabled
+            "db_monitor_raw_events": settings.db_monitor_raw_events,
+            "auto_backup_enabled": settings.auto_backup_enabled,
+            "auto_backup_debounce_secs": settings.auto_backup_debounce_secs,
+            "scheduled_backup": settings.scheduled_backup,
+            "extra_backup_keys": crate::extra_keys::load().unwrap_or_default(),
+            "silent_start_enabled": settings.silent_start_enabled,
+            "shutdown_graceful_timeout_secs": settings.shutdown_graceful_timeout_secs,
+            "shutdown_kill_timeout_secs": settings.shutdown_kill_timeout_secs
         }))
     })
 }
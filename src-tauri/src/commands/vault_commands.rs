@@ -0,0 +1,44 @@
+//! 账户保险库命令
+//! 启用、解锁、锁定和关闭账户备份的静态加密
+
+use crate::account_vault::{self, VaultStatus};
+
+/// 获取保险库状态
+#[tauri::command]
+pub async fn get_vault_status() -> Result<VaultStatus, String> {
+    Ok(account_vault::status())
+}
+
+/// 启用保险库模式，现有明文备份会被原地加密
+#[tauri::command]
+pub async fn enable_account_vault(password: String) -> Result<String, String> {
+    crate::log_async_command!("enable_account_vault", async {
+        let migrated = account_vault::enable(&password)?;
+        Ok(format!("账户保险库已启用，已加密 {} 个备份文件", migrated))
+    })
+}
+
+/// 使用主密码解锁保险库（本次会话内有效）
+#[tauri::command]
+pub async fn unlock_account_vault(password: String) -> Result<String, String> {
+    crate::log_async_command!("unlock_account_vault", async {
+        account_vault::unlock(&password)?;
+        Ok("账户保险库已解锁".to_string())
+    })
+}
+
+/// 锁定保险库，清除内存中的密钥
+#[tauri::command]
+pub async fn lock_account_vault() -> Result<String, String> {
+    account_vault::lock();
+    Ok("账户保险库已锁定".to_string())
+}
+
+/// 关闭保险库模式，所有备份会被解密为明文
+#[tauri::command]
+pub async fn disable_account_vault(password: String) -> Result<String, String> {
+    crate::log_async_command!("disable_account_vault", async {
+        let migrated = account_vault::disable(&password)?;
+        Ok(format!("账户保险库已关闭，已解密 {} 个备份文件", migrated))
+    })
+}
@@ -1,4 +1,5 @@
 use crate::constants::paths;
+use crate::path_utils::AppPaths;
 /// 配置管理器
 /// 统一管理所有配置目录和文件路径
 use std::fs;
@@ -12,9 +13,7 @@ pub struct ConfigManager {
 impl ConfigManager {
     /// 创建新的配置管理器
     pub fn new() -> Result<Self, String> {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join(paths::CONFIG_DIR_NAME);
+        let config_dir = AppPaths::config_dir().ok_or("无法获取配置目录")?;
 
         fs::create_dir_all(&config_dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
 
@@ -28,6 +27,6 @@ impl ConfigManager {
 
     /// 获取应用设置文件路径
     pub fn app_settings_file(&self) -> PathBuf {
-        self.config_dir.join("app_settings.json")
+        self.config_dir.join(paths::APP_SETTINGS_FILE)
     }
 }
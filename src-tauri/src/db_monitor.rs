@@ -1,13 +1,31 @@
 //! 数据库监控模块 - 简化版本：newData, oldData, diff
+//!
+//! 通过文件系统通知监听 state.vscdb 及其 -wal / -journal 文件，收到通知后先比较文件指纹，
+//! 确实变化时才重新读取关注的字段；另有低频定时检查兜底（文件通知不可用时退回到定时轮询）
+//!
+//! 监控所有检测到的 Antigravity 安装，路径设置变化时自动切换
+//!
+//! 同一时间只有一个监控任务，通过 CancellationToken 停止，并记录真实的运行状态
 
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
 use serde::Serialize;
 use serde_json::Value;
-use std::sync::Arc;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex as StdMutex};
 use tauri::{AppHandle, Emitter, Manager};
-use tokio::sync::Mutex;
+use tokio::sync::{mpsc, Mutex, Notify};
+use tokio::task::JoinHandle;
 use tokio::time::{interval, Duration};
+use tokio_util::sync::CancellationToken;
 use tracing::{error, info, warn};
 
+use crate::app_settings::AppSettingsManager;
+use crate::audit_log::{self, AuditAction, AuditEntry};
+use crate::auto_backup::AutoBackup;
+use crate::state_changes::{ChangeDetector, StateChange, StateEvent};
+use crate::state_diff::diff_fields;
+
 // 数据差异结构
 #[derive(Debug, Clone, Serialize)]
 pub struct DataDiff {
@@ -16,11 +34,91 @@ pub struct DataDiff {
     pub summary: String,
 }
 
-// 数据库监控器
+/// 推送到前端的语义事件（`type` 字段区分事件类型）
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct DatabaseEvent<'a> {
+    db_path: &'a Path,
+    #[serde(flatten)]
+    event: &'a StateEvent,
+}
+
+/// 收到文件通知后等待后续通知的时间（合并一次写入产生的多个通知）
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 文件通知可用时的兜底检查间隔
+const FALLBACK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 文件通知不可用时的轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(3);
+
+/// 需要关注的数据库文件名
+const WATCHED_FILES: &[&str] = &["state.vscdb", "state.vscdb-wal", "state.vscdb-journal"];
+
+/// 被监控的一个数据库（一个 Antigravity 安装）
+struct MonitoredDb {
+    detector: ChangeDetector,
+    /// watcher 被丢弃时停止监听，需要与检测器一起持有；为 None 时只能依靠定时检查
+    watcher: Option<RecommendedWatcher>,
+}
+
+/// 触发一次检查的原因
+enum Trigger {
+    /// 这些数据库的文件发生了变化
+    Files(HashSet<PathBuf>),
+    /// 定时检查
+    Tick,
+    /// 数据库路径设置已变化
+    Reload,
+}
+
+/// 监控运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MonitorState {
+    /// 未运行
+    Stopped,
+    /// 正在运行
+    Running,
+    /// 监控任务仍在运行但最近一次检查失败，或任务意外退出
+    Errored,
+}
+
+/// 监控状态（供前端显示）
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorStatus {
+    pub state: MonitorState,
+    /// 本次启动时间（RFC3339）
+    pub started_at: Option<String>,
+    /// 最近一次错误
+    pub last_error: Option<String>,
+    /// 正在监控的数据库
+    pub db_paths: Vec<PathBuf>,
+}
+
+impl MonitorStatus {
+    fn stopped() -> Self {
+        Self {
+            state: MonitorState::Stopped,
+            started_at: None,
+            last_error: None,
+            db_paths: Vec::new(),
+        }
+    }
+}
+
+/// 正在运行的监控任务
+struct MonitorTask {
+    handle: JoinHandle<()>,
+    cancel: CancellationToken,
+}
+
+// 数据库监控器（同一时间只有一个监控任务）
 pub struct DatabaseMonitor {
     app_handle: AppHandle,
-    last_data: Arc<Mutex<Option<Value>>>,
-    is_running: Arc<Mutex<bool>>,
+    task: Mutex<Option<MonitorTask>>,
+    status: Arc<StdMutex<MonitorStatus>>,
+    reload: Arc<Notify>,
 }
 
 impl DatabaseMonitor {
@@ -28,137 +126,325 @@ impl DatabaseMonitor {
     pub fn new(app_handle: AppHandle) -> Self {
         Self {
             app_handle,
-            last_data: Arc::new(Mutex::new(None)),
-            is_running: Arc::new(Mutex::new(false)),
+            task: Mutex::new(None),
+            status: Arc::new(StdMutex::new(MonitorStatus::stopped())),
+            reload: Arc::new(Notify::new()),
+        }
+    }
+
+    /// 启动数据库监控（已在运行时不会重复启动）
+    pub async fn start_monitoring(&self) -> Result<(), String> {
+        let mut task = self.task.lock().await;
+        if task.as_ref().is_some_and(|task| !task.handle.is_finished()) {
+            info!("ℹ️ 数据库监控已在运行，跳过启动");
+            return Ok(());
         }
+
+        info!("🔧 启动数据库自动监控（文件通知）");
+
+        let cancel = CancellationToken::new();
+        Self::update_status(&self.status, |status| {
+            *status = MonitorStatus {
+                state: MonitorState::Running,
+                started_at: Some(chrono::Local::now().to_rfc3339()),
+                ..MonitorStatus::stopped()
+            };
+        });
+
+        let handle = tokio::spawn(Self::run(
+            self.app_handle.clone(),
+            cancel.clone(),
+            self.reload.clone(),
+            self.status.clone(),
+        ));
+        *task = Some(MonitorTask { handle, cancel });
+
+        Ok(())
+    }
+
+    /// 停止数据库监控并等待监控任务退出
+    pub async fn stop_monitoring(&self) {
+        info!("⏹️ 停止数据库自动监控");
+
+        if let Some(task) = self.task.lock().await.take() {
+            task.cancel.cancel();
+            if let Err(e) = task.handle.await {
+                warn!("⚠️ 数据库监控任务异常退出: {}", e);
+            }
+        }
+
+        Self::update_status(&self.status, |status| *status = MonitorStatus::stopped());
     }
 
-    /// 启动数据库监控
-    pub async fn start_monitoring(&self) -> Result<(), Box<dyn std::error::Error>> {
-        info!("🔧 启动数据库自动监控（简化版）");
+    /// 重启数据库监控
+    pub async fn restart_monitoring(&self) -> Result<(), String> {
+        self.stop_monitoring().await;
+        self.start_monitoring().await
+    }
 
-        let last_data = self.last_data.clone();
-        let is_running = self.is_running.clone();
-        let app_handle = self.app_handle.clone();
+    /// 当前监控状态（任务意外退出时报告为 Errored）
+    pub async fn status(&self) -> MonitorStatus {
+        let task = self.task.lock().await;
+        let mut status = self
+            .status
+            .lock()
+            .map(|status| status.clone())
+            .unwrap_or_else(|_| MonitorStatus::stopped());
 
-        // 标记监控为运行状态
-        *is_running.lock().await = true;
+        if status.state != MonitorState::Stopped
+            && task.as_ref().is_none_or(|task| task.handle.is_finished())
+        {
+            status.state = MonitorState::Errored;
+            status.last_error.get_or_insert_with(|| "监控任务意外退出".to_string());
+        }
+        status
+    }
 
-        tokio::spawn(async move {
-            let mut interval = interval(Duration::from_secs(3)); // 3秒间隔，更敏感
+    /// 数据库路径设置变化后调用，监控会立即切换到新的路径
+    pub fn reload_paths(&self) {
+        self.reload.notify_one();
+    }
 
-            loop {
-                interval.tick().await;
+    fn update_status(status: &StdMutex<MonitorStatus>, update: impl FnOnce(&mut MonitorStatus)) {
+        if let Ok(mut status) = status.lock() {
+            update(&mut status);
+        }
+    }
 
-                // 检查监控是否还在运行
-                let running = is_running.lock().await;
-                if !*running {
+    /// 监控任务主循环（取消后退出）
+    async fn run(
+        app_handle: AppHandle,
+        cancel: CancellationToken,
+        reload: Arc<Notify>,
+        status: Arc<StdMutex<MonitorStatus>>,
+    ) {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let mut paths = Self::database_paths();
+        let mut targets = Self::monitor(&paths, &tx);
+        let mut ticker = interval(Self::check_interval(&targets));
+        let auto_backup = AutoBackup::default();
+        Self::update_status(&status, |s| s.db_paths = paths.clone());
+
+        loop {
+            let trigger = tokio::select! {
+                _ = cancel.cancelled() => {
                     info!("⏹️ 数据库监控已停止");
                     break;
                 }
-                drop(running);
-
-                // 检查是否启用了监控设置
-                let settings_manager = app_handle.state::<crate::app_settings::AppSettingsManager>();
-                let settings = settings_manager.get_settings();
+                Some(path) = rx.recv() => {
+                    tokio::time::sleep(DEBOUNCE).await;
+                    let mut changed = HashSet::from([path]);
+                    while let Ok(path) = rx.try_recv() {
+                        changed.insert(path);
+                    }
+                    Trigger::Files(changed)
+                }
+                _ = ticker.tick() => Trigger::Tick,
+                _ = reload.notified() => Trigger::Reload,
+            };
 
-                if !settings.db_monitoring_enabled {
-                    continue;
+            // 定时检查时也重新解析路径，以便发现在其他地方（如命令行工具）修改的路径设置
+            if !matches!(trigger, Trigger::Files(_)) {
+                let new_paths = Self::database_paths();
+                if new_paths != paths {
+                    info!("🔄 数据库路径已变化，重新开始监控");
+                    paths = new_paths;
+                    targets = Self::monitor(&paths, &tx);
+                    ticker = interval(Self::check_interval(&targets));
+                    ticker.tick().await;
+                    Self::update_status(&status, |s| s.db_paths = paths.clone());
                 }
+            }
 
-                // 获取当前完整数据
-                match Self::get_complete_data().await {
-                    Ok(new_data) => {
-                        let mut last = last_data.lock().await;
-
-                        // 检查是否有数据变化
-                        if let Some(ref old_data) = *last {
-                            // 分析差异
-                            let diff = Self::analyze_diff(old_data, &new_data);
-
-                            if diff.has_changes {
-                                info!("📢 检测到数据库变化: {}", diff.summary);
-
-                                // 构建简化的事件数据：newData, oldData, diff
-                                let event_data = serde_json::json!({
-                                    "newData": new_data,
-                                    "oldData": old_data,
-                                    "diff": diff
-                                });
-
-                                // 推送事件到前端
-                                if let Err(e) = app_handle.emit("database-changed", &event_data) {
-                                    error!("❌ 推送数据库变化事件失败: {}", e);
-                                } else {
-                                    info!("✅ 数据库变化事件推送成功");
-                                }
-                            }
+            for target in &mut targets {
+                // 文件通知只在指纹变化时读取；定时检查总是读取
+                let force = match &trigger {
+                    Trigger::Files(changed) => {
+                        if !changed.contains(target.detector.state_db().path()) {
+                            continue;
                         }
+                        false
+                    }
+                    Trigger::Tick | Trigger::Reload => true,
+                };
 
-                        *last = Some(new_data);
+                let db_path = target.detector.state_db().path().to_path_buf();
+                match target.detector.poll(force) {
+                    Ok(change) => {
+                        Self::update_status(&status, |s| s.state = MonitorState::Running);
+                        if let Some(change) = change {
+                            let events = change.events();
+                            Self::record_login_changes(&db_path, &events);
+                            auto_backup.handle(&app_handle, &db_path, &events, &cancel);
+                            Self::emit_change(&app_handle, &db_path, &events, change);
+                        }
                     }
                     Err(e) => {
-                        warn!("⚠️ 获取完整数据失败: {}", e);
+                        let error = format!("读取数据库数据失败 ({}): {}", db_path.display(), e);
+                        warn!("⚠️ {}", error);
+                        Self::update_status(&status, |s| {
+                            s.state = MonitorState::Errored;
+                            s.last_error = Some(error);
+                        });
                     }
                 }
             }
-        });
+        }
+    }
 
-        Ok(())
+    /// 为每个数据库创建检测器和文件监听
+    fn monitor(paths: &[PathBuf], tx: &mpsc::UnboundedSender<PathBuf>) -> Vec<MonitoredDb> {
+        if paths.is_empty() {
+            warn!("⚠️ 未找到 Antigravity 数据库，暂不监控");
+        }
+
+        paths
+            .iter()
+            .map(|db_path| {
+                let watcher = match Self::watch(db_path, tx.clone()) {
+                    Ok(watcher) => {
+                        info!("👀 正在监听数据库文件: {}", db_path.display());
+                        Some(watcher)
+                    }
+                    Err(e) => {
+                        warn!(
+                            "⚠️ 无法监听数据库文件 {}，改为每 {} 秒轮询: {}",
+                            db_path.display(),
+                            POLL_INTERVAL.as_secs(),
+                            e
+                        );
+                        None
+                    }
+                };
+                MonitoredDb {
+                    detector: ChangeDetector::new(crate::state_db::StateDb::new(db_path)),
+                    watcher,
+                }
+            })
+            .collect()
     }
 
-    /// 停止数据库监控
-    pub async fn stop_monitoring(&self) {
-        info!("⏹️ 停止数据库自动监控");
-        *self.is_running.lock().await = false;
-    }
-
-    /// 获取完整数据库数据
-    async fn get_complete_data() -> Result<Value, Box<dyn std::error::Error + Send + Sync>> {
-        // 检测数据库路径
-        let db_path = if cfg!(windows) {
-            dirs::home_dir()
-                .unwrap_or_default()
-                .join("AppData")
-                .join("Roaming")
-                .join("Antigravity")
-                .join("User")
-                .join("globalStorage")
-                .join("state.vscdb")
+    /// 定时检查间隔（有数据库无法监听时退回到轮询）
+    fn check_interval(targets: &[MonitoredDb]) -> Duration {
+        if targets.iter().all(|target| target.watcher.is_some()) {
+            FALLBACK_INTERVAL
         } else {
-            dirs::config_dir()
-                .unwrap_or_default()
-                .join("Antigravity")
-                .join("User")
-                .join("globalStorage")
-                .join("state.vscdb")
-        };
+            POLL_INTERVAL
+        }
+    }
 
-        let mut complete_data = serde_json::Map::new();
-
-        if db_path.exists() {
-            let conn = rusqlite::Connection::open(&db_path)?;
-            
-            // 查询所有数据（完整的ItemTable）
-            let mut stmt = conn.prepare("SELECT key, value FROM ItemTable ORDER BY key")?;
-            
-            let rows: Vec<(String, String)> = stmt.query_map([], |row| {
-                Ok((row.get(0)?, row.get(1)?))
-            })?.collect::<Result<Vec<_>, _>>()?;
-
-            // 构建完整数据对象
-            for (key, value) in rows {
-                // 尝试解析为JSON，如果失败则保持原始字符串
-                let json_value: Value = match serde_json::from_str(&value) {
-                    Ok(parsed) => parsed,
-                    Err(_) => Value::String(value.clone()),
-                };
-                
-                complete_data.insert(key, json_value);
+    /// 监听数据库所在目录（SQLite 会创建和删除 -wal / -journal 文件，所以监听目录而不是文件）
+    fn watch(
+        db_path: &Path,
+        tx: mpsc::UnboundedSender<PathBuf>,
+    ) -> notify::Result<RecommendedWatcher> {
+        let target = db_path.to_path_buf();
+        let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            let relevant = event.paths.iter().any(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| WATCHED_FILES.contains(&name))
+            });
+            if relevant {
+                let _ = tx.send(target.clone());
+            }
+        })?;
+
+        let dir = db_path.parent().unwrap_or(db_path);
+        watcher.watch(dir, RecursiveMode::NonRecursive)?;
+        Ok(watcher)
+    }
+
+    /// 推送数据库变化事件
+    ///
+    /// 每个语义事件推送一次 `database-event`；开启原始模式时额外推送包含完整数据的 `database-changed`
+    fn emit_change(
+        app_handle: &AppHandle,
+        db_path: &Path,
+        events: &[StateEvent],
+        change: StateChange,
+    ) {
+        for event in events {
+            info!("📢 数据库事件 ({}): {}", db_path.display(), event);
+
+            let payload = DatabaseEvent {
+                db_path,
+                event,
+            };
+            if let Err(e) = app_handle.emit("database-event", &payload) {
+                error!("❌ 推送数据库事件失败: {}", e);
             }
         }
 
-        Ok(Value::Object(complete_data))
+        if Self::raw_events_enabled(app_handle) {
+            Self::emit_raw_change(app_handle, db_path, change);
+        }
+    }
+
+    /// 将检测到的登录状态变化（登录、登出、切换账户）记录到审计日志
+    fn record_login_changes(db_path: &Path, events: &[StateEvent]) {
+        for event in events {
+            let entry = match event {
+                StateEvent::LoggedIn { email, .. } => {
+                    AuditEntry::new(AuditAction::LoginDetected).to_account(email.as_deref())
+                }
+                StateEvent::LoggedOut { email } => {
+                    AuditEntry::new(AuditAction::LoginDetected).from_account(email.as_deref())
+                }
+                StateEvent::AccountChanged { from, to } => AuditEntry::new(AuditAction::LoginDetected)
+                    .from_account(from.as_deref())
+                    .to_account(to.as_deref()),
+                _ => continue,
+            };
+            audit_log::record(entry.detail(format!("{} ({})", event, db_path.display())));
+        }
+    }
+
+    /// 是否推送原始数据变化事件（调试用）
+    fn raw_events_enabled(app_handle: &AppHandle) -> bool {
+        app_handle
+            .try_state::<AppSettingsManager>()
+            .is_some_and(|settings| settings.get_settings().db_monitor_raw_events)
+    }
+
+    /// 推送原始数据变化事件（完整的新旧数据和字段差异）
+    fn emit_raw_change(app_handle: &AppHandle, db_path: &Path, change: StateChange) {
+        let old_data = Value::Object(change.old_data);
+        let new_data = Value::Object(change.new_data);
+
+        // 分析差异
+        let diff = Self::analyze_diff(&old_data, &new_data);
+        if !diff.has_changes {
+            return;
+        }
+        info!("📢 检测到数据库变化 ({}): {}", db_path.display(), diff.summary);
+
+        // 构建简化的事件数据：dbPath, newData, oldData, diff
+        let event_data = serde_json::json!({
+            "dbPath": db_path,
+            "newData": new_data,
+            "oldData": old_data,
+            "diff": diff
+        });
+
+        // 推送事件到前端
+        if let Err(e) = app_handle.emit("database-changed", &event_data) {
+            error!("❌ 推送数据库变化事件失败: {}", e);
+        } else {
+            info!("✅ 数据库变化事件推送成功");
+        }
+    }
+
+    /// 需要监控的数据库（与备份、恢复使用相同的路径解析，自定义路径优先，包含检测到的所有安装）
+    fn database_paths() -> Vec<PathBuf> {
+        crate::platform_utils::get_all_antigravity_db_paths()
     }
 
     /// 分析两个数据之间的差异
@@ -168,25 +454,8 @@ impl DatabaseMonitor {
         // 比较数据
         match (old, new) {
             (Value::Object(old_obj), Value::Object(new_obj)) => {
-                // 检查新增的字段
-                for key in new_obj.keys() {
-                    match old_obj.get(key) {
-                        Some(old_value) => {
-                            if old_value != new_obj.get(key).unwrap() {
-                                changed_fields.push(format!("{}: changed", key));
-                            }
-                        }
-                        None => {
-                            changed_fields.push(format!("{}: added", key));
-                        }
-                    }
-                }
-
-                // 检查删除的字段
-                for key in old_obj.keys() {
-                    if !new_obj.contains_key(key) {
-                        changed_fields.push(format!("{}: removed", key));
-                    }
+                for change in diff_fields(old_obj, new_obj) {
+                    changed_fields.push(format!("{}: {}", change.key, change.kind.as_str()));
                 }
             }
             (Value::Null, Value::Object(_)) => {
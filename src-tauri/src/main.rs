@@ -9,19 +9,14 @@ use std::fs;
 use std::path::PathBuf;
 use std::sync::Arc;
 
-use rusqlite::Connection;
-
-/// Antigravity 清理模块
-mod antigravity_cleanup;
-
-/// Antigravity 备份模块
-mod antigravity_backup;
-
-/// Antigravity 恢复模块
-mod antigravity_restore;
-
-/// Antigravity 启动模块
-mod antigravity_starter;
+// 核心逻辑位于 antigravity-agent-core（不依赖 Tauri），这里导入后仍可通过 crate:: 路径访问
+use antigravity_agent_core::{
+    account_metadata, account_ops, account_registry, account_store, account_vault,
+    antigravity_cleanup, antigravity_path_config, antigravity_restore, antigravity_starter,
+    audit_log, backup_history, backup_schedule, constants, crypto, data_category, db_health,
+    db_snapshot, extra_keys, path_utils, platform_utils, process, state_changes, state_db,
+    state_diff, switch_snapshot,
+};
 
 /// 窗口状态管理模块
 mod window_state_manager;
@@ -32,12 +27,6 @@ mod window_event_handler;
 /// 系统托盘模块
 mod system_tray;
 
-/// 平台工具模块
-mod platform_utils;
-
-/// 常量定义模块
-mod constants;
-
 /// 配置管理器模块
 mod config_manager;
 
@@ -47,18 +36,18 @@ mod app_settings;
 /// 工具模块
 mod utils;
 
-/// Antigravity 路径配置模块
-mod antigravity_path_config;
-
 /// 数据库监控模块
 mod db_monitor;
 
+/// 自动备份模块
+mod auto_backup;
+
+/// 定时备份模块
+mod backup_scheduler;
+
 /// 命令模块
 mod commands;
 
-/// 路径处理模块
-mod path_utils;
-
 // 重新导出命令函数以保持 invoke_handler 兼容性
 use crate::commands::{
   backup_and_restart_antigravity,
@@ -74,6 +63,7 @@ use crate::commands::{
   disable_system_tray,
   // tray_commands
   enable_system_tray,
+  export_audit_log,
   // 脱敏测试命令
   // 脱敏测试命令
   find_antigravity_installations,
@@ -81,12 +71,14 @@ use crate::commands::{
   get_antigravity_accounts,
   get_current_antigravity_info,
   get_current_paths,
+  get_extra_backup_keys,
   get_log_info,
   get_platform_info,  // 新增前端日志处理命令
   get_recent_accounts,
   // platform_commands
   get_system_tray_state,  // 新增
   is_antigravity_running,
+  get_database_monitoring_status,
   is_database_monitoring_running,
   is_db_monitoring_enabled,
   is_silent_start_enabled,
@@ -94,29 +86,61 @@ use crate::commands::{
   kill_antigravity,
   list_antigravity_processes,
   list_backups,
+  list_backup_revisions,
+  prune_backup_revisions,
+  create_db_snapshot,
+  list_db_snapshots,
+  restore_db_snapshot,
+  delete_db_snapshot,
+  prune_db_snapshots,
+  check_state_db_health,
+  vacuum_state_db,
+  check_state_db_marker,
+  repair_state_db_marker,
+  query_audit_log,
   // db_monitor_commands
   minimize_to_tray,
   restore_antigravity_account,
+  preview_antigravity_restore,
   restore_backup_files,
+  restore_backup_revision,
   // process_commands
   restore_from_tray,
   restore_profile,  // 新增
   save_antigravity_executable,  // 新增调试命令
   save_antigravity_path,
+  save_auto_backup_settings,
+  save_scheduled_backup_settings,
+  save_extra_backup_keys,
   save_db_monitoring_state,
+  save_db_monitor_raw_events,
   save_silent_start_state,
+  save_shutdown_timeouts,
   // 最后2个有依赖的函数
   save_system_tray_state,
   start_antigravity,
+  restart_database_monitoring,
   start_database_monitoring,
   stop_database_monitoring,
   switch_antigravity_account,  // 新增
   switch_to_antigravity_account,  // 新增
+  undo_last_switch,
+  list_switch_snapshots,
     // account_commands (前5个零依赖函数)
   toggle_system_tray,
   validate_antigravity_executable,
   validate_antigravity_path,  // 新增
   decrypt_config_data,  // 新增配置文件解密命令
+  encrypt_config_data,
+  get_vault_status,
+  enable_account_vault,
+  unlock_account_vault,
+  lock_account_vault,
+  disable_account_vault,
+  get_account_metadata,
+  update_account_metadata,
+  list_accounts_with_metadata,
+  list_account_tags,
   write_text_file,  // 新增通用文件写入命令
   write_frontend_log,
 };
@@ -130,54 +154,23 @@ struct ProfileInfo {
     last_updated: String,
 }
 
-// Antigravity 账户信息结构
-#[derive(Debug, Serialize, Deserialize)]
-struct AntigravityAccount {
-    id: String,
-    name: String,
-    email: String,
-    api_key: String,
-    profile_url: String,   // Base64 编码的头像
-    user_settings: String, // 编码后的用户设置
-    created_at: String,
-    last_switched: String,
-}
-
 // 导入系统托盘管理器
 
 #[derive(Debug, Serialize, Deserialize)]
 struct AppState {
     profiles: HashMap<String, ProfileInfo>,
     config_dir: PathBuf,
-    antigravity_accounts: HashMap<String, AntigravityAccount>,
-    current_account_id: Option<String>,
 }
 
 impl Default for AppState {
     fn default() -> Self {
-        // 智能检测配置目录，确保跨平台兼容性
-        let config_dir = if cfg!(windows) {
-            // Windows: 优先使用 APPDATA 环境变量
-            std::env::var_os("APPDATA")
-                .map(|appdata| PathBuf::from(appdata).join(".antigravity-agent"))
-                .or_else(|| {
-                    // 备用方案：通过用户主目录构建 AppData\Roaming 路径
-                    dirs::home_dir().map(|home| {
-                        home.join("AppData")
-                            .join("Roaming")
-                            .join(".antigravity-agent")
-                    })
-                })
-                .or_else(|| {
-                    // 最后备用：使用系统标准配置目录
-                    dirs::config_dir().map(|config| config.join(".antigravity-agent"))
-                })
-                .unwrap_or_else(|| PathBuf::from(".antigravity-agent"))
-        } else {
-            // macOS/Linux: 使用标准配置目录
-            dirs::config_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join(".antigravity-agent")
+        // 统一由 AppPaths 解析（支持 ANTIGRAVITY_AGENT_HOME 和便携模式）
+        let config_dir = match path_utils::AppPaths::agent_home() {
+            Some(home) => {
+                println!("📁 [main] 配置目录 ({:?}): {}", home.source, home.dir.display());
+                home.dir
+            }
+            None => PathBuf::from(constants::paths::CONFIG_DIR_NAME),
         };
 
         // 确保配置目录存在
@@ -188,8 +181,6 @@ impl Default for AppState {
         Self {
             profiles: HashMap::new(),
             config_dir,
-            antigravity_accounts: HashMap::new(),
-            current_account_id: None,
         }
     }
 }
@@ -252,11 +243,30 @@ fn main() {
             let db_monitor = Arc::new(db_monitor::DatabaseMonitor::new(app.handle().clone()));
             app.manage(db_monitor.clone());
 
-            // 数据库监控将在前端通过命令启动，避免在 setup 中使用 tokio::spawn
-            println!("ℹ️ [setup] 数据库监控将根据前端设置自动启动");
+            // 设置中已启用监控时直接启动（在 Tauri 的异步运行时中执行）
+            if app.state::<app_settings::AppSettingsManager>().get_settings().db_monitoring_enabled {
+                let db_monitor = db_monitor.clone();
+                tauri::async_runtime::spawn(async move {
+                    if let Err(e) = db_monitor.start_monitoring().await {
+                        eprintln!("⚠️ [setup] 数据库监控启动失败: {}", e);
+                    }
+                });
+            } else {
+                println!("ℹ️ [setup] 数据库监控未启用");
+            }
 
             println!("✅ [setup] 数据库监控器初始化完成");
 
+            // 初始化定时备份（按保存的设置安排计划）
+            let backup_scheduler = Arc::new(backup_scheduler::BackupScheduler::new(app.handle().clone()));
+            app.manage(backup_scheduler.clone());
+            let scheduled_backup = app.state::<app_settings::AppSettingsManager>().get_settings().scheduled_backup;
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = backup_scheduler.apply(scheduled_backup).await {
+                    eprintln!("⚠️ [setup] 定时备份启动失败: {}", e);
+                }
+            });
+
             // 初始化窗口事件处理器
             println!("🔧 [setup] 初始化窗口事件处理器...");
             if let Err(e) = window_event_handler::init_window_event_handler(app) {
@@ -319,14 +329,32 @@ fn main() {
             restore_backup_files,
             delete_backup,
             clear_all_backups,
+            list_backup_revisions,
+            restore_backup_revision,
+            prune_backup_revisions,
+            create_db_snapshot,
+            list_db_snapshots,
+            restore_db_snapshot,
+            delete_db_snapshot,
+            prune_db_snapshots,
+            check_state_db_health,
+            vacuum_state_db,
+            check_state_db_marker,
+            repair_state_db_marker,
             // Antigravity 相关命令
             switch_antigravity_account,
             get_antigravity_accounts,
             get_current_antigravity_info,
             backup_antigravity_current_account,
             restore_antigravity_account,
+            preview_antigravity_restore,
             switch_to_antigravity_account,
+            undo_last_switch,
+            list_switch_snapshots,
             clear_all_antigravity_data,
+            // 审计日志命令
+            query_audit_log,
+            export_audit_log,
             // 进程管理命令
             kill_antigravity,
             is_antigravity_running,  // 新增
@@ -355,16 +383,36 @@ fn main() {
             toggle_system_tray,
             is_db_monitoring_enabled,
             save_db_monitoring_state,
+            save_db_monitor_raw_events,
+            save_auto_backup_settings,
+            save_scheduled_backup_settings,
+            get_extra_backup_keys,
+            save_extra_backup_keys,
             is_silent_start_enabled,
             save_silent_start_state,
+            save_shutdown_timeouts,
             get_all_settings,
             // 数据库监控命令
             is_database_monitoring_running,
+            get_database_monitoring_status,
             start_database_monitoring,
+            restart_database_monitoring,
             stop_database_monitoring,
             get_log_info,
             clear_logs,
             decrypt_config_data,  // 新增配置文件解密命令
+            encrypt_config_data,
+            // 账户保险库命令
+            get_vault_status,
+            enable_account_vault,
+            unlock_account_vault,
+            lock_account_vault,
+            disable_account_vault,
+            // 账户元数据命令
+            get_account_metadata,
+            update_account_metadata,
+            list_accounts_with_metadata,
+            list_account_tags,
             write_text_file,  // 新增通用文件写入命令
             write_frontend_log,  // 新增前端日志处理命令
                     ])
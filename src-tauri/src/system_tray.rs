@@ -11,6 +11,7 @@ use tauri::{
     AppHandle, Manager, Wry,
 };
 
+use crate::account_metadata::AccountWithMetadata;
 use crate::app_settings::AppSettingsManager;
 
 /// 系统托盘管理器
@@ -220,24 +221,36 @@ impl SystemTrayManager {
     async fn build_menu(&self, app_handle: &AppHandle) -> tauri::Result<tauri::menu::Menu<Wry>> {
         let mut menu_builder = MenuBuilder::new(app_handle);
 
-        // 1. 获取账户列表
+        // 1. 获取账户列表（附带标签、置顶等元数据）
         let state = app_handle.state::<crate::AppState>();
-        let recent_accounts = crate::commands::backup_commands::get_recent_accounts(state.clone(), Some(2)).await.unwrap_or_default();
-        let all_accounts = crate::commands::backup_commands::get_recent_accounts(state.clone(), None).await.unwrap_or_default();
+        let recent_accounts = crate::commands::backup_commands::get_recent_accounts(state.clone(), None).await.unwrap_or_default();
+        let all_accounts = crate::commands::backup_commands::account_store(&state)
+            .accounts_with_metadata(&crate::account_metadata::AccountFilter::default())
+            .unwrap_or_default();
+
+        // 快速切换：置顶账户 + 最近使用的 2 个其他账户
+        let mut quick_accounts: Vec<&AccountWithMetadata> =
+            all_accounts.iter().filter(|entry| entry.metadata.pinned).collect();
+        quick_accounts.extend(
+            recent_accounts
+                .iter()
+                .filter_map(|name| all_accounts.iter().find(|entry| &entry.account == name))
+                .filter(|entry| !entry.metadata.pinned)
+                .take(2),
+        );
 
         // 2. 添加账户相关菜单
         if !all_accounts.is_empty() {
-            // 快速切换（最近2个账户）
-            if !recent_accounts.is_empty() {
+            if !quick_accounts.is_empty() {
                 let label_item = MenuItem::new(app_handle, "快速切换", false, None::<&str>)?;
                 menu_builder = menu_builder.item(&label_item);
 
-                for account in &recent_accounts {
-                    let menu_id = format!("switch_account:{}", account);
+                for entry in &quick_accounts {
+                    let menu_id = format!("switch_account:{}", entry.account);
                     let item = MenuItem::with_id(
                         app_handle,
                         &menu_id,
-                        format!("  {}", account),
+                        format!("  {}", Self::account_menu_text(entry)),
                         true,
                         None::<&str>,
                     )?;
@@ -247,16 +260,16 @@ impl SystemTrayManager {
                 menu_builder = menu_builder.separator();
             }
 
-            // 所有账户子菜单（超过2个时显示）
-            if all_accounts.len() > 2 {
+            // 所有账户子菜单（快速切换放不下时显示，置顶的在前）
+            if all_accounts.len() > quick_accounts.len() {
                 let mut submenu_builder = SubmenuBuilder::new(app_handle, "所有账户");
 
-                for account in &all_accounts {
-                    let menu_id = format!("switch_account:{}", account);
+                for entry in &all_accounts {
+                    let menu_id = format!("switch_account:{}", entry.account);
                     let item = MenuItem::with_id(
                         app_handle,
                         &menu_id,
-                        account,
+                        Self::account_menu_text(entry),
                         true,
                         None::<&str>,
                     )?;
@@ -268,6 +281,18 @@ impl SystemTrayManager {
                 menu_builder = menu_builder.separator();
             }
 
+            // 撤销上次切换（存在切换前快照时显示）
+            if crate::switch_snapshot::has_switch_snapshot() {
+                let undo_item = MenuItem::with_id(
+                    app_handle,
+                    "undo_last_switch",
+                    "撤销上次切换",
+                    true,
+                    None::<&str>,
+                )?;
+                menu_builder = menu_builder.item(&undo_item);
+            }
+
             // 刷新账户列表
             let refresh_item = MenuItem::with_id(
                 app_handle,
@@ -295,6 +320,25 @@ impl SystemTrayManager {
         menu_builder.build()
     }
 
+    /// 账户菜单项文字：置顶标记、标签（附邮箱）和分类标签
+    fn account_menu_text(entry: &AccountWithMetadata) -> String {
+        let metadata = &entry.metadata;
+        let mut text = String::new();
+
+        if metadata.pinned {
+            text.push_str("📌 ");
+        }
+        match &metadata.label {
+            Some(label) => text.push_str(&format!("{} ({})", label, entry.account)),
+            None => text.push_str(&entry.account),
+        }
+        if !metadata.tags.is_empty() {
+            text.push_str(&format!(" [{}]", metadata.tags.join(", ")));
+        }
+
+        text
+    }
+
     /// 处理菜单事件
     async fn handle_menu_event(app: &AppHandle, event_id: &str) {
         match event_id {
@@ -318,6 +362,17 @@ impl SystemTrayManager {
                     eprintln!("刷新托盘菜单失败: {}", e);
                 }
             }
+            "undo_last_switch" => {
+                println!("📋 菜单: 撤销上次切换");
+                match crate::commands::account_commands::undo_last_switch().await {
+                    Ok(msg) => println!("✅ 撤销切换成功: {}", msg),
+                    Err(e) => eprintln!("❌ 撤销切换失败: {}", e),
+                }
+                let system_tray = app.state::<SystemTrayManager>();
+                if let Err(e) = system_tray.update_menu(app).await {
+                    eprintln!("重建托盘菜单失败: {}", e);
+                }
+            }
             id if id.starts_with("switch_account:") => {
                 if let Some(account_name) = id.strip_prefix("switch_account:") {
                     println!("📋 菜单: 切换账户 -> {}", account_name);
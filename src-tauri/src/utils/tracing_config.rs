@@ -7,6 +7,7 @@ use std::io::{Write, BufWriter};
 use std::fs::OpenOptions;
 use std::sync::Mutex;
 use super::log_sanitizer::LogSanitizer;
+use crate::constants::paths;
 
 /// 每日日志文件 appender，生成格式: antigravity-agent.2025-11-23.log
 struct DailyLogFileAppender {
@@ -103,11 +104,11 @@ impl<'a> MakeWriter<'a> for DailyLogFileAppender {
 /// ```
 pub fn init_tracing(config_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
     // 创建日志目录
-    let log_dir = config_dir.join("logs");
+    let log_dir = config_dir.join(paths::LOGS_DIR_NAME);
     std::fs::create_dir_all(&log_dir)?;
 
     // 文件 appender (按日滚动，使用正确的文件名格式)
-    let file_appender = DailyLogFileAppender::new(&log_dir, "antigravity-agent");
+    let file_appender = DailyLogFileAppender::new(&log_dir, paths::LOG_FILE_PREFIX);
 
     // 控制台 appender (开发时使用)
     let (console_non_blocking, _console_guard) = tracing_appender::non_blocking(std::io::stdout());
@@ -49,14 +49,16 @@ function AppContent() {
   } = usePasswordDialog(showStatus);
 
   // 用户管理
-  const {addCurrentUser} = useUserManagement();
+  const {getUsers} = useUserManagement();
 
-  // 监听数据库变化事件
+  // 监听数据库变化事件（账户由后端自动备份，备份完成后刷新用户列表）
   const {loadSettings, addListener} = useDbMonitoringStore();
 
   useEffect(() => {
     loadSettings()
-    return addListener(DATABASE_EVENTS.DATA_CHANGED, addCurrentUser)
+    return addListener(DATABASE_EVENTS.ACCOUNT_BACKED_UP, () => {
+      getUsers()
+    })
   }, []);
 
   // 配置管理
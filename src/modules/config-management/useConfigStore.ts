@@ -223,9 +223,12 @@ export const useConfigStore = create<ConfigState & ConfigActions>()(
                 }))
               };
 
-              // 加密配置数据
+              // 加密配置数据（由后端使用认证加密完成）
               const configJson = JSON.stringify(configData, null, 2);
-              const encryptedData = SimpleEncryption.xorEncrypt(configJson, password);
+              const encryptedData: string = await invoke('encrypt_config_data', {
+                jsonData: configJson,
+                password
+              });
 
               // 选择保存位置
               const timestamp = new Date().toISOString().slice(0, 19).replace(/:/g, '-');
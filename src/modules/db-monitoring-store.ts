@@ -8,11 +8,27 @@ import { invoke } from '@tauri-apps/api/core';
 import {listen, UnlistenFn} from '@tauri-apps/api/event';
 import { EventEmitter } from 'events';
 
+// 后端推送的语义事件（type 区分事件类型，只携带相关字段）
+export type DatabaseEvent = { dbPath: string } & (
+    | { type: 'logged_in'; email: string | null; name: string | null }
+    | { type: 'logged_out'; email: string | null }
+    | { type: 'account_changed'; from: string | null; to: string | null }
+    | { type: 'token_refreshed'; email: string | null }
+    | { type: 'settings_changed'; keys: string[] }
+    | { type: 'notification_added'; key: string; value: unknown }
+);
+
+// 后端自动备份完成事件
+export interface AccountBackedUpEvent {
+    email: string;
+    dbPath: string;
+    isNew: boolean;
+}
+
 // 数据库变化事件数据接口
 export interface DatabaseChangeEvent {
     timestamp: number;
-    old_data?: any;
-    new_data?: any;
+    event: DatabaseEvent;
 }
 
 // 导出事件相关类型
@@ -22,16 +38,18 @@ export type { DatabaseEventMap, DatabaseEventListener };
 const databaseEventEmitter = new EventEmitter();
 
 // 全局 unlistenFn 变量
-let globalUnlistenFn: UnlistenFn | null = null;
+let globalUnlistenFns: UnlistenFn[] = [];
 
 // 数据库事件类型
 export const DATABASE_EVENTS = {
   DATA_CHANGED: 'database:data-changed',
+  ACCOUNT_BACKED_UP: 'database:account-backed-up',
 } as const;
 
 // 事件类型映射
 type DatabaseEventMap = {
   [DATABASE_EVENTS.DATA_CHANGED]: DatabaseChangeEvent;
+  [DATABASE_EVENTS.ACCOUNT_BACKED_UP]: AccountBackedUpEvent;
 };
 
 // 事件监听器类型
@@ -93,8 +111,10 @@ export const useDbMonitoringStore = create<DbMonitoringState & DbMonitoringActio
       setDbMonitoringEnabled: async (enabled: boolean): Promise<void> => {
         try {
           // 调用后端设置
-          await invoke('set_db_monitoring_enabled', { enabled });
-          if (!enabled) {
+          await invoke('save_db_monitoring_state', { enabled });
+          if (enabled) {
+            get().startListening()
+          } else {
             get().stopListening()
           }
           set({ dbMonitoringEnabled: enabled });
@@ -120,38 +140,25 @@ export const useDbMonitoringStore = create<DbMonitoringState & DbMonitoringActio
           // 清理之前的监听器
           await get().cleanup();
 
-          // 处理数据库变化事件
-          const handleDatabaseChange = async (event: any) => {
-            console.log('📡 接收到数据库变化事件', event);
+          // 处理数据库语义事件
+          const handleDatabaseEvent = (event: { payload: DatabaseEvent }) => {
+            console.log('📡 接收到数据库事件', event.payload);
 
-            // 解析事件数据：newData, oldData, diff
-            const { newData, oldData, diff } = event.payload;
-
-            if (diff) {
-              console.log('📊 变化摘要:', {
-                hasChanges: diff.hasChanges,
-                changedFields: diff.changedFields,
-                summary: diff.summary
-              });
-            }
-
-            // 触发界面更新（不管有没有变化）
-            console.log('🔄 数据库变化事件，触发界面更新');
-
-            // 发射内部数据库变化事件
+            // 发射内部数据库变化事件，触发界面更新
             databaseEventEmitter.emit(DATABASE_EVENTS.DATA_CHANGED, {
               timestamp: Date.now(),
-              newData,
-              oldData,
-              diff,
-              originalEvent: event
+              event: event.payload,
             });
-
-            console.log('✅ 数据库变化事件已发射');
           };
 
-          // 监听后端推送的数据库变化事件
-          globalUnlistenFn = await listen('database-changed', handleDatabaseChange);
+          // 监听后端推送的数据库语义事件和自动备份事件
+          globalUnlistenFns = [
+            await listen<DatabaseEvent>('database-event', handleDatabaseEvent),
+            await listen<AccountBackedUpEvent>('account-backed-up', (event) => {
+              console.log('💾 后端已自动备份账户', event.payload);
+              databaseEventEmitter.emit(DATABASE_EVENTS.ACCOUNT_BACKED_UP, event.payload);
+            }),
+          ];
 
           invoke('start_database_monitoring');
           console.log('✅ 数据库监听已启动');
@@ -176,10 +183,10 @@ export const useDbMonitoringStore = create<DbMonitoringState & DbMonitoringActio
 
       // 清理资源
       cleanup: async (): Promise<void> => {
-        if (globalUnlistenFn) {
+        if (globalUnlistenFns.length > 0) {
           try {
-            await globalUnlistenFn();
-            globalUnlistenFn = null;
+            await Promise.all(globalUnlistenFns.map(unlisten => unlisten()));
+            globalUnlistenFns = [];
             console.log('🧹 数据库监听器已清理');
           } catch (error) {
             console.error('⚠️ 清理数据库监听器失败:', error);
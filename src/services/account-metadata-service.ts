@@ -0,0 +1,62 @@
+/**
+ * 账户元数据服务
+ *
+ * 编辑账户的标签、备注、分类标签、颜色和置顶标记，并按这些信息筛选账户
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface AccountMetadata {
+  label: string | null;
+  note: string | null;
+  tags: string[];
+  /** #RRGGBB */
+  color: string | null;
+  pinned: boolean;
+}
+
+export interface AccountWithMetadata extends AccountMetadata {
+  account: string;
+}
+
+export interface AccountFilter {
+  /** 必须同时包含的标签 */
+  tags?: string[];
+  pinned?: boolean;
+  color?: string;
+  /** 在账户名、标签、备注中搜索 */
+  query?: string;
+}
+
+/**
+ * 账户元数据服务类
+ */
+export class AccountMetadataService {
+  /**
+   * 获取账户元数据
+   */
+  static async get(name: string): Promise<AccountMetadata> {
+    return invoke<AccountMetadata>('get_account_metadata', { name });
+  }
+
+  /**
+   * 更新账户元数据（返回规范化后的内容）
+   */
+  static async update(name: string, metadata: AccountMetadata): Promise<AccountMetadata> {
+    return invoke<AccountMetadata>('update_account_metadata', { name, metadata });
+  }
+
+  /**
+   * 按元数据筛选账户（置顶的在前）
+   */
+  static async list(filter?: AccountFilter): Promise<AccountWithMetadata[]> {
+    return invoke<AccountWithMetadata[]>('list_accounts_with_metadata', { filter });
+  }
+
+  /**
+   * 列出所有使用过的分类标签
+   */
+  static async listTags(): Promise<string[]> {
+    return invoke<string[]>('list_account_tags');
+  }
+}
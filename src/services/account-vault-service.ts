@@ -0,0 +1,52 @@
+/**
+ * 账户保险库服务
+ *
+ * 提供账户备份静态加密（保险库模式）的前端接口
+ */
+
+import { invoke } from '@tauri-apps/api/core';
+
+export interface VaultStatus {
+  enabled: boolean;
+  unlocked: boolean;
+}
+
+/**
+ * 账户保险库服务类
+ */
+export class AccountVaultService {
+  /**
+   * 获取保险库状态
+   */
+  static async getStatus(): Promise<VaultStatus> {
+    return invoke<VaultStatus>('get_vault_status');
+  }
+
+  /**
+   * 启用保险库模式（现有明文备份会被原地加密）
+   */
+  static async enable(password: string): Promise<string> {
+    return invoke<string>('enable_account_vault', { password });
+  }
+
+  /**
+   * 解锁保险库（本次会话内有效）
+   */
+  static async unlock(password: string): Promise<string> {
+    return invoke<string>('unlock_account_vault', { password });
+  }
+
+  /**
+   * 锁定保险库
+   */
+  static async lock(): Promise<string> {
+    return invoke<string>('lock_account_vault');
+  }
+
+  /**
+   * 关闭保险库模式（所有备份会被解密为明文）
+   */
+  static async disable(password: string): Promise<string> {
+    return invoke<string>('disable_account_vault', { password });
+  }
+}
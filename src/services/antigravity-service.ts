@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import type { AntigravityAccount } from '@/types/tauri';
 
 /**
  * Antigravity 服务 - 处理 Antigravity 相关操作
@@ -34,4 +35,18 @@ export class AntigravityService {
   static async getBackupList(): Promise<string[]> {
     return invoke('list_backups')
   }
+
+  /**
+   * 获取账户注册表（根据备份文件和当前登录状态刷新）
+   */
+  static async getAccounts(): Promise<AntigravityAccount[]> {
+    return invoke('get_antigravity_accounts')
+  }
+
+  /**
+   * 切换到注册表中的账户（接受账户 ID 或邮箱）
+   */
+  static async switchAccount(accountId: string): Promise<string> {
+    return invoke('switch_antigravity_account', { accountId })
+  }
 }
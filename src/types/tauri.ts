@@ -63,6 +63,17 @@ export interface AntigravityCurrentUserInfo {
   [key: string]: any;
 }
 
+// 账户注册表中的账户
+export interface AntigravityAccount {
+  id: string;
+  email: string;
+  name: string;
+  avatar: string | null;
+  created_at: string;
+  last_switched: string | null;
+  last_backed_up: string | null;
+}
+
 // 备份当前账户参数类型
 export interface BackupCurrentAccountParams {
   email: string;